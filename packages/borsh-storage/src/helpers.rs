@@ -121,11 +121,45 @@ pub(crate) fn query_raw<Q: CustomQuery>(
     }
 }
 
+/// query_deserialize reads the data stored under `key` in a remote contract and parses it,
+/// returning Ok(None) if no data present
+pub(crate) fn query_deserialize<T: BorshDeserialize, Q: CustomQuery>(
+    querier: &QuerierWrapper<Q>,
+    contract_addr: Addr,
+    code_hash: String,
+    key: &[u8],
+) -> StdResult<Option<T>> {
+    let result = query_raw(querier, contract_addr, code_hash, key.into())?;
+    if result.is_empty() {
+        Ok(None)
+    } else {
+        Borsh::deserialize(&result).map(Some)
+    }
+}
+
+/// Builds a querier that answers the queries sent by `query_raw` with the data found in
+/// `storage`, as if it was the storage of the remote contract.
+#[cfg(test)]
+pub(crate) fn mock_remote_querier(
+    storage: cosmwasm_std::testing::MockStorage,
+) -> cosmwasm_std::testing::MockQuerier {
+    use cosmwasm_std::Storage;
+
+    let mut querier = cosmwasm_std::testing::MockQuerier::new(&[]);
+    querier.update_wasm(move |query| match query {
+        WasmQuery::Smart { msg, .. } => SystemResult::Ok(ContractResult::Ok(
+            storage.get(msg).unwrap_or_default().into(),
+        )),
+        _ => panic!("unexpected query {:?}", query),
+    });
+    querier
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use borsh::BorshSerialize;
-    use cosmwasm_std::{to_vec, StdError};
+    use cosmwasm_std::StdError;
     use serde::{Deserialize, Serialize};
 
     #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, PartialEq, Debug)]
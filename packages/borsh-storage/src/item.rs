@@ -1,10 +1,11 @@
+use std::any::type_name;
 use std::marker::PhantomData;
 
 use borsh::{BorshDeserialize, BorshSerialize};
-use cosmwasm_std::{Addr, CustomQuery, QuerierWrapper, StdError, StdResult, Storage, WasmQuery};
+use cosmwasm_std::{Addr, CustomQuery, QuerierWrapper, StdError, StdResult, Storage};
 
 use crate::{
-    helpers::{may_deserialize, must_deserialize},
+    helpers::{may_deserialize, must_deserialize, query_deserialize},
     traits::Borsh,
 };
 
@@ -71,13 +72,33 @@ impl<'a, T: BorshSerialize + BorshDeserialize> Item<'a, T> {
         self.save(store, &output)?;
         Ok(output)
     }
+
+    /// If you import the proper Item from the remote contract, this will let you read the data
+    /// from a remote contract in a type-safe way using WasmQuery::RawQuery.
+    ///
+    /// Note that we expect an Item to be set, and error if there is no data there
+    pub fn query<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+    ) -> StdResult<T> {
+        query_deserialize::<T, Q>(
+            querier,
+            remote_contract,
+            remote_contract_code_hash,
+            self.storage_key,
+        )?
+        .ok_or_else(|| StdError::not_found(type_name::<T>()))
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::helpers::mock_remote_querier;
     use borsh::{BorshDeserialize, BorshSerialize};
-    use cosmwasm_std::{testing::MockStorage, to_vec};
+    use cosmwasm_std::{testing::MockStorage, to_vec, Empty};
     use rstest::*;
     use rstest_reuse::{self, *};
 
@@ -135,6 +156,30 @@ mod test {
         assert_eq!(None, config.may_load(&store).unwrap());
     }
 
+    #[apply(serialization)]
+    fn query_works(#[case] config: Item<Config>) {
+        let mut store = MockStorage::new();
+        let cfg = Config {
+            owner: "admin".to_string(),
+            max_tokens: 1234,
+        };
+        config.save(&mut store, &cfg).unwrap();
+
+        let querier = mock_remote_querier(store);
+        let querier = QuerierWrapper::<Empty>::new(&querier);
+        let loaded = config
+            .query(&querier, Addr::unchecked("remote"), "hash".to_string())
+            .unwrap();
+        assert_eq!(cfg, loaded);
+
+        let missing = Item::<Config>::new("missing").query(
+            &querier,
+            Addr::unchecked("remote"),
+            "hash".to_string(),
+        );
+        assert!(matches!(missing, Err(StdError::NotFound { .. })));
+    }
+
     #[test]
     fn isolated_reads() {
         let mut store = MockStorage::new();
@@ -0,0 +1,38 @@
+#![cfg(feature = "iterator")]
+
+use borsh::BorshDeserialize;
+
+use cosmwasm_std::Record;
+use cosmwasm_std::StdResult;
+
+use crate::de::KeyDeserialize;
+use crate::traits::Borsh;
+
+#[allow(dead_code)]
+pub(crate) fn deserialize_v<T: BorshDeserialize>(kv: Record) -> StdResult<Record<T>> {
+    let (k, v) = kv;
+    let t = Borsh::deserialize::<T>(&v)?;
+    Ok((k, t))
+}
+
+pub(crate) fn deserialize_kv<K: KeyDeserialize, T: BorshDeserialize>(
+    kv: Record,
+) -> StdResult<(K::Output, T)> {
+    let (k, v) = kv;
+    let kt = K::from_vec(k)?;
+    let vt = Borsh::deserialize::<T>(&v)?;
+    Ok((kt, vt))
+}
+
+// TODO: add a check here that it is the real prefix?
+#[inline]
+pub(crate) fn trim(namespace: &[u8], key: &[u8]) -> Vec<u8> {
+    key[namespace.len()..].to_vec()
+}
+
+#[inline]
+pub(crate) fn concat(namespace: &[u8], key: &[u8]) -> Vec<u8> {
+    let mut k = namespace.to_vec();
+    k.extend_from_slice(key);
+    k
+}
@@ -1,24 +1,47 @@
 pub(crate) mod append_store;
+pub(crate) mod bound;
 pub(crate) mod de;
 pub(crate) mod deque_store;
 pub(crate) mod endian;
 pub(crate) mod helpers;
+pub(crate) mod indexed_map;
+pub(crate) mod indexes;
 pub(crate) mod int_key;
 pub(crate) mod item;
+pub(crate) mod iter_helpers;
 pub(crate) mod keys;
 pub(crate) mod map;
 pub(crate) mod path;
+pub(crate) mod prefix;
+pub(crate) mod snapshot;
 pub(crate) mod traits;
 
 pub use append_store::AppendStore as BorshAppendStore;
+#[cfg(feature = "iterator")]
+pub use bound::{
+    Bound as BorshBound, Bounder as BorshBounder, PrefixBound as BorshPrefixBound,
+    RawBound as BorshRawBound,
+};
 pub use de::KeyDeserialize;
 pub use deque_store::DequeStore as BorshDequeStore;
 pub use endian::Endian;
+#[cfg(feature = "iterator")]
+pub use indexed_map::{IndexList as BorshIndexList, IndexedMap as BorshIndexedMap};
+#[cfg(feature = "iterator")]
+pub use indexes::{
+    Index as BorshIndex, MultiIndex as BorshMultiIndex, UniqueIndex as BorshUniqueIndex,
+};
 pub use int_key::CwIntKey;
 pub use item::Item as BorshItem;
 pub use keys::{Key as BorshKey, Prefixer as BorshPrefixer, PrimaryKey as BorshPrimaryKey};
 pub use map::Map as BorshMap;
 pub use path::Path as BorshPath;
+#[cfg(feature = "iterator")]
+pub use prefix::{range_with_prefix, Prefix as BorshPrefix};
+#[cfg(feature = "iterator")]
+pub use snapshot::{
+    SnapshotItem as BorshSnapshotItem, SnapshotMap as BorshSnapshotMap, Strategy as BorshStrategy,
+};
 pub use traits::{
     GenericItemStorage as GenericBorshItemStorage, GenericMapStorage as GenericBorshMapStorage,
     ItemStorage as BorshItemStorage, MapStorage as BorshMapStorage,
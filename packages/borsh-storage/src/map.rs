@@ -1,7 +1,18 @@
+#[cfg(feature = "iterator")]
+use crate::bound::{Bound, PrefixBound};
+#[cfg(feature = "iterator")]
+use crate::de::KeyDeserialize;
+use crate::helpers::query_deserialize;
+#[cfg(feature = "iterator")]
+use crate::iter_helpers::{deserialize_kv, deserialize_v};
+#[cfg(feature = "iterator")]
+use crate::keys::Prefixer;
 use crate::keys::{Key, PrimaryKey};
 use crate::path::Path;
+#[cfg(feature = "iterator")]
+use crate::prefix::{namespaced_prefix_range, Prefix};
 use borsh::{BorshDeserialize, BorshSerialize};
-use cosmwasm_std::{StdError, StdResult, Storage};
+use cosmwasm_std::{Addr, CustomQuery, QuerierWrapper, StdError, StdResult, Storage};
 use std::marker::PhantomData;
 
 #[derive(Debug, Clone)]
@@ -37,6 +48,11 @@ where
         )
     }
 
+    #[cfg(feature = "iterator")]
+    pub(crate) fn no_prefix_raw(&self) -> Prefix<Vec<u8>, T, K> {
+        Prefix::new(self.namespace, &[])
+    }
+
     pub fn save(&self, store: &mut dyn Storage, k: K, data: &T) -> StdResult<()> {
         self.key(k).save(store, data)
     }
@@ -73,6 +89,155 @@ where
     {
         self.key(k).update(store, action)
     }
+
+    /// If you import the proper Map from the remote contract, this will let you read the data
+    /// from a remote contract in a type-safe way using WasmQuery::RawQuery
+    pub fn query<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+        k: K,
+    ) -> StdResult<Option<T>> {
+        query_deserialize::<T, Q>(
+            querier,
+            remote_contract,
+            remote_contract_code_hash,
+            &self.key(k).storage_key,
+        )
+    }
+}
+
+#[cfg(feature = "iterator")]
+impl<'a, K, T: BorshSerialize + BorshDeserialize> Map<'a, K, T>
+where
+    K: PrimaryKey<'a>,
+{
+    pub fn sub_prefix(&self, p: K::SubPrefix) -> Prefix<K::SuperSuffix, T, K::SuperSuffix> {
+        Prefix::new(self.namespace, &p.prefix())
+    }
+
+    pub fn prefix(&self, p: K::Prefix) -> Prefix<K::Suffix, T, K::Suffix> {
+        Prefix::new(self.namespace, &p.prefix())
+    }
+}
+
+// short-cut for simple keys, rather than .prefix(()).range_raw(...)
+#[cfg(feature = "iterator")]
+impl<'a, K, T: BorshSerialize + BorshDeserialize> Map<'a, K, T>
+where
+    // TODO: this should only be when K::Prefix == ()
+    // Other cases need to call prefix() first
+    K: PrimaryKey<'a>,
+{
+    /// While `range_raw` over a `prefix` fixes the prefix to one element and iterates over the
+    /// remaining, `prefix_range_raw` accepts bounds for the lowest and highest elements of the `Prefix`
+    /// itself, and iterates over those (inclusively or exclusively, depending on `PrefixBound`).
+    /// There are some issues that distinguish these two, and blindly casting to `Vec<u8>` doesn't
+    /// solve them.
+    pub fn prefix_range_raw<'c>(
+        &self,
+        store: &'c dyn Storage,
+        min: Option<PrefixBound<'a, K::Prefix>>,
+        max: Option<PrefixBound<'a, K::Prefix>>,
+        order: cosmwasm_std::Order,
+    ) -> Box<dyn Iterator<Item = StdResult<cosmwasm_std::Record<T>>> + 'c>
+    where
+        T: 'c,
+        'a: 'c,
+    {
+        let mapped =
+            namespaced_prefix_range(store, self.namespace, min, max, order).map(deserialize_v::<T>);
+        Box::new(mapped)
+    }
+
+    pub fn range_raw<'c>(
+        &self,
+        store: &'c dyn Storage,
+        min: Option<Bound<'a, K>>,
+        max: Option<Bound<'a, K>>,
+        order: cosmwasm_std::Order,
+    ) -> Box<dyn Iterator<Item = StdResult<cosmwasm_std::Record<T>>> + 'c>
+    where
+        T: 'c,
+    {
+        self.no_prefix_raw().range_raw(store, min, max, order)
+    }
+
+    pub fn keys_raw<'c>(
+        &self,
+        store: &'c dyn Storage,
+        min: Option<Bound<'a, K>>,
+        max: Option<Bound<'a, K>>,
+        order: cosmwasm_std::Order,
+    ) -> Box<dyn Iterator<Item = Vec<u8>> + 'c>
+    where
+        T: 'c,
+    {
+        self.no_prefix_raw().keys_raw(store, min, max, order)
+    }
+}
+
+#[cfg(feature = "iterator")]
+impl<'a, K, T: BorshSerialize + BorshDeserialize> Map<'a, K, T>
+where
+    K: PrimaryKey<'a> + KeyDeserialize,
+{
+    /// While `range` over a `prefix` fixes the prefix to one element and iterates over the
+    /// remaining, `prefix_range` accepts bounds for the lowest and highest elements of the
+    /// `Prefix` itself, and iterates over those (inclusively or exclusively, depending on
+    /// `PrefixBound`).
+    /// There are some issues that distinguish these two, and blindly casting to `Vec<u8>` doesn't
+    /// solve them.
+    pub fn prefix_range<'c>(
+        &self,
+        store: &'c dyn Storage,
+        min: Option<PrefixBound<'a, K::Prefix>>,
+        max: Option<PrefixBound<'a, K::Prefix>>,
+        order: cosmwasm_std::Order,
+    ) -> Box<dyn Iterator<Item = StdResult<(K::Output, T)>> + 'c>
+    where
+        T: 'c,
+        'a: 'c,
+        K: 'c,
+        K::Output: 'static,
+    {
+        let mapped = namespaced_prefix_range(store, self.namespace, min, max, order)
+            .map(deserialize_kv::<K, T>);
+        Box::new(mapped)
+    }
+
+    pub fn range<'c>(
+        &self,
+        store: &'c dyn Storage,
+        min: Option<Bound<'a, K>>,
+        max: Option<Bound<'a, K>>,
+        order: cosmwasm_std::Order,
+    ) -> Box<dyn Iterator<Item = StdResult<(K::Output, T)>> + 'c>
+    where
+        T: 'c,
+        K::Output: 'static,
+    {
+        self.no_prefix().range(store, min, max, order)
+    }
+
+    pub fn keys<'c>(
+        &self,
+        store: &'c dyn Storage,
+        min: Option<Bound<'a, K>>,
+        max: Option<Bound<'a, K>>,
+        order: cosmwasm_std::Order,
+    ) -> Box<dyn Iterator<Item = StdResult<K::Output>> + 'c>
+    where
+        T: 'c,
+        K::Output: 'static,
+    {
+        self.no_prefix().keys(store, min, max, order)
+    }
+
+    fn no_prefix(&self) -> Prefix<K, T, K> {
+        Prefix::new(self.namespace, &[])
+    }
 }
 
 #[cfg(test)]
@@ -82,8 +247,14 @@ mod test {
     use rstest_reuse::{self, *};
     use std::ops::Deref;
 
+    #[cfg(feature = "iterator")]
+    use crate::bound::Bounder;
+    use crate::helpers::mock_remote_querier;
     use crate::int_key::CwIntKey;
     use cosmwasm_std::testing::MockStorage;
+    use cosmwasm_std::Empty;
+    #[cfg(feature = "iterator")]
+    use cosmwasm_std::Order;
 
     #[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
     struct Data {
@@ -92,6 +263,8 @@ mod test {
     }
 
     const PEOPLE: Map<&[u8], Data> = Map::new("people");
+    #[cfg(feature = "iterator")]
+    const PEOPLE_ID: Map<u32, Data> = Map::new("people_id");
     const ALLOWANCE: Map<(&[u8], &[u8]), u64> = Map::new("allow");
     const TRIPLE: Map<(&[u8], u8, &str), u64> = Map::new("triple");
 
@@ -171,6 +344,29 @@ mod test {
         assert_eq!(None, john.may_load(&store).unwrap());
     }
 
+    #[rstest]
+    fn query_works(#[values(PEOPLE)] people: Map<Key, Data>) {
+        let mut store = MockStorage::new();
+        let data = Data {
+            name: "John".to_string(),
+            age: 32,
+        };
+        people.save(&mut store, b"john", &data).unwrap();
+
+        let querier = mock_remote_querier(store);
+        let querier = QuerierWrapper::<Empty>::new(&querier);
+        let remote = || Addr::unchecked("remote");
+
+        let loaded = people
+            .query(&querier, remote(), "hash".to_string(), b"john")
+            .unwrap();
+        assert_eq!(Some(data), loaded);
+        let missing = people
+            .query(&querier, remote(), "hash".to_string(), b"jack")
+            .unwrap();
+        assert_eq!(None, missing);
+    }
+
     #[rstest]
     fn existence(#[values(PEOPLE)] people: Map<Key, Data>) {
         let mut store = MockStorage::new();
@@ -238,6 +434,509 @@ mod test {
         assert_eq!(1234, same);
     }
 
+    #[rstest]
+    #[cfg(feature = "iterator")]
+    fn range_raw_simple_key(#[values(PEOPLE)] people: Map<Key, Data>) {
+        let mut store = MockStorage::new();
+
+        // save and load on two keys
+        let data = Data {
+            name: "John".to_string(),
+            age: 32,
+        };
+        people.save(&mut store, b"john", &data).unwrap();
+
+        let data2 = Data {
+            name: "Jim".to_string(),
+            age: 44,
+        };
+        people.save(&mut store, b"jim", &data2).unwrap();
+
+        // let's try to iterate!
+        let all: StdResult<Vec<_>> = people
+            .range_raw(&store, None, None, Order::Ascending)
+            .collect();
+        let all = all.unwrap();
+        assert_eq!(2, all.len());
+        assert_eq!(
+            all,
+            vec![
+                (b"jim".to_vec(), data2.clone()),
+                (b"john".to_vec(), data.clone())
+            ]
+        );
+
+        // let's try to iterate over a range
+        let all: StdResult<Vec<_>> = people
+            .range_raw(
+                &store,
+                Some(Bound::inclusive(b"j" as &[u8])),
+                None,
+                Order::Ascending,
+            )
+            .collect();
+        let all = all.unwrap();
+        assert_eq!(2, all.len());
+        assert_eq!(
+            all,
+            vec![(b"jim".to_vec(), data2), (b"john".to_vec(), data.clone())]
+        );
+
+        // let's try to iterate over a more restrictive range
+        let all: StdResult<Vec<_>> = people
+            .range_raw(
+                &store,
+                Some(Bound::inclusive(b"jo" as &[u8])),
+                None,
+                Order::Ascending,
+            )
+            .collect();
+        let all = all.unwrap();
+        assert_eq!(1, all.len());
+        assert_eq!(all, vec![(b"john".to_vec(), data)]);
+    }
+
+    #[rstest]
+    #[cfg(feature = "iterator")]
+    fn range_simple_string_key(#[values(PEOPLE)] people: Map<Key, Data>) {
+        let mut store = MockStorage::new();
+
+        // save and load on three keys
+        let data = Data {
+            name: "John".to_string(),
+            age: 32,
+        };
+        people.save(&mut store, b"john", &data).unwrap();
+
+        let data2 = Data {
+            name: "Jim".to_string(),
+            age: 44,
+        };
+        people.save(&mut store, b"jim", &data2).unwrap();
+
+        let data3 = Data {
+            name: "Ada".to_string(),
+            age: 23,
+        };
+        people.save(&mut store, b"ada", &data3).unwrap();
+
+        // let's try to iterate!
+        let all: StdResult<Vec<_>> = people.range(&store, None, None, Order::Ascending).collect();
+        let all = all.unwrap();
+        assert_eq!(3, all.len());
+        assert_eq!(
+            all,
+            vec![
+                (b"ada".to_vec(), data3),
+                (b"jim".to_vec(), data2.clone()),
+                (b"john".to_vec(), data.clone())
+            ]
+        );
+
+        // let's try to iterate over a range
+        let all: StdResult<Vec<_>> = people
+            .range(&store, b"j".inclusive_bound(), None, Order::Ascending)
+            .collect();
+        let all = all.unwrap();
+        assert_eq!(2, all.len());
+        assert_eq!(
+            all,
+            vec![(b"jim".to_vec(), data2), (b"john".to_vec(), data.clone())]
+        );
+
+        // let's try to iterate over a more restrictive range
+        let all: StdResult<Vec<_>> = people
+            .range(&store, b"jo".inclusive_bound(), None, Order::Ascending)
+            .collect();
+        let all = all.unwrap();
+        assert_eq!(1, all.len());
+        assert_eq!(all, vec![(b"john".to_vec(), data)]);
+    }
+
+    #[rstest]
+    #[cfg(feature = "iterator")]
+    fn range_simple_integer_key(#[values(PEOPLE_ID)] people_id: Map<u32, Data>) {
+        let mut store = MockStorage::new();
+
+        // save and load on two keys
+        let data = Data {
+            name: "John".to_string(),
+            age: 32,
+        };
+        people_id.save(&mut store, 1234, &data).unwrap();
+
+        let data2 = Data {
+            name: "Jim".to_string(),
+            age: 44,
+        };
+        people_id.save(&mut store, 56, &data2).unwrap();
+
+        // let's try to iterate!
+        let all: StdResult<Vec<_>> = people_id
+            .range(&store, None, None, Order::Ascending)
+            .collect();
+        let all = all.unwrap();
+        assert_eq!(2, all.len());
+        assert_eq!(all, vec![(56, data2.clone()), (1234, data.clone())]);
+
+        // let's try to iterate over a range
+        let all: StdResult<Vec<_>> = people_id
+            .range(
+                &store,
+                Some(Bound::inclusive(56u32)),
+                None,
+                Order::Ascending,
+            )
+            .collect();
+        let all = all.unwrap();
+        assert_eq!(2, all.len());
+        assert_eq!(all, vec![(56, data2), (1234, data.clone())]);
+
+        // let's try to iterate over a more restrictive range
+        let all: StdResult<Vec<_>> = people_id
+            .range(
+                &store,
+                Some(Bound::inclusive(57u32)),
+                None,
+                Order::Ascending,
+            )
+            .collect();
+        let all = all.unwrap();
+        assert_eq!(1, all.len());
+        assert_eq!(all, vec![(1234, data)]);
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn range_simple_integer_key_with_bounder_trait() {
+        let mut store = MockStorage::new();
+
+        // save and load on two keys
+        let data = Data {
+            name: "John".to_string(),
+            age: 32,
+        };
+        PEOPLE_ID.save(&mut store, 1234, &data).unwrap();
+
+        let data2 = Data {
+            name: "Jim".to_string(),
+            age: 44,
+        };
+        PEOPLE_ID.save(&mut store, 56, &data2).unwrap();
+
+        // let's try to iterate!
+        let all: StdResult<Vec<_>> = PEOPLE_ID
+            .range(&store, None, None, Order::Ascending)
+            .collect();
+        let all = all.unwrap();
+        assert_eq!(2, all.len());
+        assert_eq!(all, vec![(56, data2.clone()), (1234, data.clone())]);
+
+        // let's try to iterate over a range
+        let all: StdResult<Vec<_>> = PEOPLE_ID
+            .range(&store, 56u32.inclusive_bound(), None, Order::Ascending)
+            .collect();
+        let all = all.unwrap();
+        assert_eq!(2, all.len());
+        assert_eq!(all, vec![(56, data2), (1234, data.clone())]);
+
+        // let's try to iterate over a more restrictive range
+        let all: StdResult<Vec<_>> = PEOPLE_ID
+            .range(&store, 57u32.inclusive_bound(), None, Order::Ascending)
+            .collect();
+        let all = all.unwrap();
+        assert_eq!(1, all.len());
+        assert_eq!(all, vec![(1234, data)]);
+    }
+
+    #[rstest]
+    #[cfg(feature = "iterator")]
+    fn range_raw_composite_key(#[values(ALLOWANCE)] allowance: Map<AKey, u64>) {
+        let mut store = MockStorage::new();
+
+        // save and load on three keys, one under different owner
+        allowance
+            .save(&mut store, (b"owner", b"spender"), &1000)
+            .unwrap();
+        allowance
+            .save(&mut store, (b"owner", b"spender2"), &3000)
+            .unwrap();
+        allowance
+            .save(&mut store, (b"owner2", b"spender"), &5000)
+            .unwrap();
+
+        // let's try to iterate!
+        let all: StdResult<Vec<_>> = allowance
+            .range_raw(&store, None, None, Order::Ascending)
+            .collect();
+        let all = all.unwrap();
+        assert_eq!(3, all.len());
+        assert_eq!(
+            all,
+            vec![
+                ((b"owner".to_vec(), b"spender".to_vec()).joined_key(), 1000),
+                ((b"owner".to_vec(), b"spender2".to_vec()).joined_key(), 3000),
+                ((b"owner2".to_vec(), b"spender".to_vec()).joined_key(), 5000),
+            ]
+        );
+
+        // let's try to iterate over a prefix
+        let all: StdResult<Vec<_>> = allowance
+            .prefix(b"owner")
+            .range_raw(&store, None, None, Order::Ascending)
+            .collect();
+        let all = all.unwrap();
+        assert_eq!(2, all.len());
+        assert_eq!(
+            all,
+            vec![(b"spender".to_vec(), 1000), (b"spender2".to_vec(), 3000)]
+        );
+    }
+
+    #[rstest]
+    #[cfg(feature = "iterator")]
+    fn range_composite_key(#[values(ALLOWANCE)] allowance: Map<AKey, u64>) {
+        let mut store = MockStorage::new();
+
+        // save and load on three keys, one under different owner
+        allowance
+            .save(&mut store, (b"owner", b"spender"), &1000)
+            .unwrap();
+        allowance
+            .save(&mut store, (b"owner", b"spender2"), &3000)
+            .unwrap();
+        allowance
+            .save(&mut store, (b"owner2", b"spender"), &5000)
+            .unwrap();
+
+        // let's try to iterate!
+        let all: StdResult<Vec<_>> = allowance
+            .range(&store, None, None, Order::Ascending)
+            .collect();
+        let all = all.unwrap();
+        assert_eq!(3, all.len());
+        assert_eq!(
+            all,
+            vec![
+                ((b"owner".to_vec(), b"spender".to_vec()), 1000),
+                ((b"owner".to_vec(), b"spender2".to_vec()), 3000),
+                ((b"owner2".to_vec(), b"spender".to_vec()), 5000)
+            ]
+        );
+
+        // let's try to iterate over a prefix
+        let all: StdResult<Vec<_>> = allowance
+            .prefix(b"owner")
+            .range(&store, None, None, Order::Ascending)
+            .collect();
+        let all = all.unwrap();
+        assert_eq!(2, all.len());
+        assert_eq!(
+            all,
+            vec![(b"spender".to_vec(), 1000), (b"spender2".to_vec(), 3000),]
+        );
+
+        // let's try to iterate over a prefixed restricted inclusive range
+        let all: StdResult<Vec<_>> = allowance
+            .prefix(b"owner")
+            .range(&store, b"spender".inclusive_bound(), None, Order::Ascending)
+            .collect();
+        let all = all.unwrap();
+        assert_eq!(2, all.len());
+        assert_eq!(
+            all,
+            vec![(b"spender".to_vec(), 1000), (b"spender2".to_vec(), 3000),]
+        );
+
+        // let's try to iterate over a prefixed restricted exclusive range
+        let all: StdResult<Vec<_>> = allowance
+            .prefix(b"owner")
+            .range(&store, b"spender".exclusive_bound(), None, Order::Ascending)
+            .collect();
+        let all = all.unwrap();
+        assert_eq!(1, all.len());
+        assert_eq!(all, vec![(b"spender2".to_vec(), 3000),]);
+    }
+
+    #[rstest]
+    #[cfg(feature = "iterator")]
+    fn range_raw_triple_key(#[values(TRIPLE)] triple: Map<TKey, u64>) {
+        let mut store = MockStorage::new();
+
+        // save and load on three keys, one under different owner
+        triple
+            .save(&mut store, (b"owner", 9, "recipient"), &1000)
+            .unwrap();
+        triple
+            .save(&mut store, (b"owner", 9, "recipient2"), &3000)
+            .unwrap();
+        triple
+            .save(&mut store, (b"owner", 10, "recipient3"), &3000)
+            .unwrap();
+        triple
+            .save(&mut store, (b"owner2", 9, "recipient"), &5000)
+            .unwrap();
+
+        // let's try to iterate!
+        let all: StdResult<Vec<_>> = triple
+            .range_raw(&store, None, None, Order::Ascending)
+            .collect();
+        let all = all.unwrap();
+        assert_eq!(4, all.len());
+        assert_eq!(
+            all,
+            vec![
+                (
+                    (b"owner".to_vec(), 9u8, b"recipient".to_vec()).joined_key(),
+                    1000
+                ),
+                (
+                    (b"owner".to_vec(), 9u8, b"recipient2".to_vec()).joined_key(),
+                    3000
+                ),
+                (
+                    (b"owner".to_vec(), 10u8, b"recipient3".to_vec()).joined_key(),
+                    3000
+                ),
+                (
+                    (b"owner2".to_vec(), 9u8, b"recipient".to_vec()).joined_key(),
+                    5000
+                )
+            ]
+        );
+
+        // let's iterate over a prefix
+        let all: StdResult<Vec<_>> = triple
+            .prefix((b"owner", 9))
+            .range_raw(&store, None, None, Order::Ascending)
+            .collect();
+        let all = all.unwrap();
+        assert_eq!(2, all.len());
+        assert_eq!(
+            all,
+            vec![
+                (b"recipient".to_vec(), 1000),
+                (b"recipient2".to_vec(), 3000)
+            ]
+        );
+
+        // let's iterate over a sub prefix
+        let all: StdResult<Vec<_>> = triple
+            .sub_prefix(b"owner")
+            .range_raw(&store, None, None, Order::Ascending)
+            .collect();
+        let all = all.unwrap();
+        assert_eq!(3, all.len());
+        // Use range() if you want key deserialization
+        assert_eq!(
+            all,
+            vec![
+                ((9u8, b"recipient".to_vec()).joined_key(), 1000),
+                ((9u8, b"recipient2".to_vec()).joined_key(), 3000),
+                ((10u8, b"recipient3".to_vec()).joined_key(), 3000)
+            ]
+        );
+    }
+
+    #[rstest]
+    #[cfg(feature = "iterator")]
+    fn range_triple_key(#[values(TRIPLE)] triple: Map<TKey, u64>) {
+        let mut store = MockStorage::new();
+
+        // save and load on three keys, one under different owner
+        triple
+            .save(&mut store, (b"owner", 9u8, "recipient"), &1000)
+            .unwrap();
+        triple
+            .save(&mut store, (b"owner", 9u8, "recipient2"), &3000)
+            .unwrap();
+        triple
+            .save(&mut store, (b"owner", 10u8, "recipient3"), &3000)
+            .unwrap();
+        triple
+            .save(&mut store, (b"owner2", 9u8, "recipient"), &5000)
+            .unwrap();
+
+        // let's try to iterate!
+        let all: StdResult<Vec<_>> = triple.range(&store, None, None, Order::Ascending).collect();
+        let all = all.unwrap();
+        assert_eq!(4, all.len());
+        assert_eq!(
+            all,
+            vec![
+                ((b"owner".to_vec(), 9, "recipient".to_string()), 1000),
+                ((b"owner".to_vec(), 9, "recipient2".to_string()), 3000),
+                ((b"owner".to_vec(), 10, "recipient3".to_string()), 3000),
+                ((b"owner2".to_vec(), 9, "recipient".to_string()), 5000)
+            ]
+        );
+
+        // let's iterate over a sub_prefix
+        let all: StdResult<Vec<_>> = triple
+            .sub_prefix(b"owner")
+            .range(&store, None, None, Order::Ascending)
+            .collect();
+        let all = all.unwrap();
+        assert_eq!(3, all.len());
+        assert_eq!(
+            all,
+            vec![
+                ((9, "recipient".to_string()), 1000),
+                ((9, "recipient2".to_string()), 3000),
+                ((10, "recipient3".to_string()), 3000),
+            ]
+        );
+
+        // let's iterate over a prefix
+        let all: StdResult<Vec<_>> = triple
+            .prefix((b"owner", 9))
+            .range(&store, None, None, Order::Ascending)
+            .collect();
+        let all = all.unwrap();
+        assert_eq!(2, all.len());
+        assert_eq!(
+            all,
+            vec![
+                ("recipient".to_string(), 1000),
+                ("recipient2".to_string(), 3000),
+            ]
+        );
+
+        // let's try to iterate over a prefixed restricted inclusive range
+        let all: StdResult<Vec<_>> = triple
+            .prefix((b"owner", 9))
+            .range(
+                &store,
+                "recipient".inclusive_bound(),
+                None,
+                Order::Ascending,
+            )
+            .collect();
+        let all = all.unwrap();
+        assert_eq!(2, all.len());
+        assert_eq!(
+            all,
+            vec![
+                ("recipient".to_string(), 1000),
+                ("recipient2".to_string(), 3000),
+            ]
+        );
+
+        // let's try to iterate over a prefixed restricted exclusive range
+        let all: StdResult<Vec<_>> = triple
+            .prefix((b"owner", 9))
+            .range(
+                &store,
+                "recipient".exclusive_bound(),
+                None,
+                Order::Ascending,
+            )
+            .collect();
+        let all = all.unwrap();
+        assert_eq!(1, all.len());
+        assert_eq!(all, vec![("recipient2".to_string(), 3000),]);
+    }
+
     #[rstest]
     fn basic_update(#[values(ALLOWANCE)] allowance: Map<AKey, u64>) {
         let mut store = MockStorage::new();
@@ -370,4 +1069,177 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn prefixed_range_raw_works() {
+        // this is designed to look as much like a secondary index as possible
+        // we want to query over a range of u32 for the first key and all subkeys
+        const AGES: Map<(u32, Vec<u8>), u64> = Map::new("ages");
+
+        let mut store = MockStorage::new();
+        AGES.save(&mut store, (2, vec![1, 2, 3]), &123).unwrap();
+        AGES.save(&mut store, (3, vec![4, 5, 6]), &456).unwrap();
+        AGES.save(&mut store, (5, vec![7, 8, 9]), &789).unwrap();
+        AGES.save(&mut store, (5, vec![9, 8, 7]), &987).unwrap();
+        AGES.save(&mut store, (7, vec![20, 21, 22]), &2002).unwrap();
+        AGES.save(&mut store, (8, vec![23, 24, 25]), &2332).unwrap();
+
+        // typical range under one prefix as a control
+        let fives = AGES
+            .prefix(5)
+            .range_raw(&store, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(fives.len(), 2);
+        assert_eq!(fives, vec![(vec![7, 8, 9], 789), (vec![9, 8, 7], 987)]);
+
+        let keys: Vec<_> = AGES
+            .keys_raw(&store, None, None, Order::Ascending)
+            .collect();
+        assert_eq!(keys.len(), 6);
+
+        // using inclusive bounds both sides
+        let include = AGES
+            .prefix_range_raw(
+                &store,
+                Some(PrefixBound::inclusive(3u32)),
+                Some(PrefixBound::inclusive(7u32)),
+                Order::Ascending,
+            )
+            .map(|r| r.map(|(_, v)| v))
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(include.len(), 4);
+        assert_eq!(include, vec![456, 789, 987, 2002]);
+
+        // using exclusive bounds both sides
+        let exclude = AGES
+            .prefix_range_raw(
+                &store,
+                Some(PrefixBound::exclusive(3u32)),
+                Some(PrefixBound::exclusive(7u32)),
+                Order::Ascending,
+            )
+            .map(|r| r.map(|(_, v)| v))
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(exclude.len(), 2);
+        assert_eq!(exclude, vec![789, 987]);
+
+        // using inclusive in descending
+        let include = AGES
+            .prefix_range_raw(
+                &store,
+                Some(PrefixBound::inclusive(3u32)),
+                Some(PrefixBound::inclusive(5u32)),
+                Order::Descending,
+            )
+            .map(|r| r.map(|(_, v)| v))
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(include.len(), 3);
+        assert_eq!(include, vec![987, 789, 456]);
+
+        // using exclusive in descending
+        let include = AGES
+            .prefix_range_raw(
+                &store,
+                Some(PrefixBound::exclusive(2u32)),
+                Some(PrefixBound::exclusive(5u32)),
+                Order::Descending,
+            )
+            .map(|r| r.map(|(_, v)| v))
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(include.len(), 1);
+        assert_eq!(include, vec![456]);
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn prefixed_range_works() {
+        // this is designed to look as much like a secondary index as possible
+        // we want to query over a range of u32 for the first key and all subkeys
+        const AGES: Map<(u32, &str), u64> = Map::new("ages");
+
+        let mut store = MockStorage::new();
+        AGES.save(&mut store, (2, "123"), &123).unwrap();
+        AGES.save(&mut store, (3, "456"), &456).unwrap();
+        AGES.save(&mut store, (5, "789"), &789).unwrap();
+        AGES.save(&mut store, (5, "987"), &987).unwrap();
+        AGES.save(&mut store, (7, "202122"), &2002).unwrap();
+        AGES.save(&mut store, (8, "232425"), &2332).unwrap();
+
+        // typical range under one prefix as a control
+        let fives = AGES
+            .prefix(5)
+            .range(&store, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(fives.len(), 2);
+        assert_eq!(
+            fives,
+            vec![("789".to_string(), 789), ("987".to_string(), 987)]
+        );
+
+        let keys: Vec<_> = AGES.keys(&store, None, None, Order::Ascending).collect();
+        assert_eq!(keys.len(), 6);
+
+        // using inclusive bounds both sides
+        let include = AGES
+            .prefix_range(
+                &store,
+                Some(PrefixBound::inclusive(3u32)),
+                Some(PrefixBound::inclusive(7u32)),
+                Order::Ascending,
+            )
+            .map(|r| r.map(|(_, v)| v))
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(include.len(), 4);
+        assert_eq!(include, vec![456, 789, 987, 2002]);
+
+        // using exclusive bounds both sides
+        let exclude = AGES
+            .prefix_range(
+                &store,
+                Some(PrefixBound::exclusive(3u32)),
+                Some(PrefixBound::exclusive(7u32)),
+                Order::Ascending,
+            )
+            .map(|r| r.map(|(_, v)| v))
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(exclude.len(), 2);
+        assert_eq!(exclude, vec![789, 987]);
+
+        // using inclusive in descending
+        let include = AGES
+            .prefix_range(
+                &store,
+                Some(PrefixBound::inclusive(3u32)),
+                Some(PrefixBound::inclusive(5u32)),
+                Order::Descending,
+            )
+            .map(|r| r.map(|(_, v)| v))
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(include.len(), 3);
+        assert_eq!(include, vec![987, 789, 456]);
+
+        // using exclusive in descending
+        let include = AGES
+            .prefix_range(
+                &store,
+                Some(PrefixBound::exclusive(2u32)),
+                Some(PrefixBound::exclusive(5u32)),
+                Order::Descending,
+            )
+            .map(|r| r.map(|(_, v)| v))
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(include.len(), 1);
+        assert_eq!(include, vec![456]);
+    }
 }
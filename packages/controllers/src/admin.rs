@@ -4,9 +4,17 @@ use thiserror::Error;
 
 use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{
-    attr, Addr, CustomQuery, Deps, DepsMut, MessageInfo, Response, StdError, StdResult,
+    attr, Addr, CustomQuery, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
 };
-use cw_storage_plus::Item;
+use cw_storage_plus::{Item, Map};
+use cw_utils::{Duration, Expiration, WEEK};
+
+/// How long an admin transfer proposal stays valid when it is made without an expiration
+pub const DEFAULT_PROPOSAL_TTL: Duration = WEEK;
+
+/// Namespace of the admin transfers that are waiting to be accepted, keyed by the namespace of
+/// their Admin, unless it is created `with_pending_namespace`
+pub const PENDING_NAMESPACE: &str = "admin_pending";
 
 // TODO: should the return values end up in utils, so eg. cw4 can import them as well as this module?
 /// Returned from Admin.query_admin()
@@ -15,6 +23,20 @@ pub struct AdminResponse {
     pub admin: Option<String>,
 }
 
+/// Returned from Admin.query_pending_admin()
+#[cw_serde]
+pub struct PendingAdminResponse {
+    pub pending_admin: Option<String>,
+    pub expires: Option<Expiration>,
+}
+
+/// An admin transfer that was proposed by the current admin, but not accepted yet
+#[cw_serde]
+struct PendingAdmin {
+    address: Addr,
+    expires: Expiration,
+}
+
 /// Errors returned from Admin
 #[derive(Error, Debug, PartialEq)]
 pub enum AdminError {
@@ -23,29 +45,69 @@ pub enum AdminError {
 
     #[error("Caller is not admin")]
     NotAdmin {},
+
+    #[error("No admin transfer is pending")]
+    NoPendingAdmin {},
+
+    #[error("Caller is not the pending admin")]
+    NotPendingAdmin {},
+
+    #[error("Admin transfer proposal has expired")]
+    ProposalExpired {},
+
+    #[error("Admin transfer proposal must expire")]
+    ProposalNeverExpires {},
 }
 
 // state/logic
-pub struct Admin<'a>(Item<'a, Option<Addr>>);
+pub struct Admin<'a> {
+    namespace: &'a str,
+    admin: Item<'a, Option<Addr>>,
+    pending: Map<'a, &'a str, PendingAdmin>,
+}
 
 // this is the core business logic we expose
 impl<'a> Admin<'a> {
     pub const fn new(namespace: &'a str) -> Self {
-        Admin(Item::new(namespace))
+        Self::with_pending_namespace(namespace, PENDING_NAMESPACE)
+    }
+
+    /// Like `new`, but keeps the admin transfer that is waiting to be accepted under
+    /// `pending_namespace` instead of `PENDING_NAMESPACE`
+    pub const fn with_pending_namespace(namespace: &'a str, pending_namespace: &'a str) -> Self {
+        Admin {
+            namespace,
+            admin: Item::new(namespace),
+            pending: Map::new(pending_namespace),
+        }
     }
 
+    /// Sets the admin right away, and cancels any pending admin transfer
     pub fn set<Q: CustomQuery>(&self, deps: DepsMut<Q>, admin: Option<Addr>) -> StdResult<()> {
-        self.0.save(deps.storage, &admin)
+        if self.pending.has(deps.storage, self.namespace) {
+            self.pending.remove(deps.storage, self.namespace);
+        }
+        self.admin.save(deps.storage, &admin)
     }
 
     pub fn get<Q: CustomQuery>(&self, deps: Deps<Q>) -> StdResult<Option<Addr>> {
-        self.0.load(deps.storage)
+        self.admin.load(deps.storage)
+    }
+
+    /// Returns the proposed admin and when the proposal expires, if a transfer is pending.
+    /// Expired proposals are returned as well, until they are replaced or cancelled.
+    pub fn get_pending<Q: CustomQuery>(
+        &self,
+        deps: Deps<Q>,
+    ) -> StdResult<Option<(Addr, Expiration)>> {
+        let pending = self.pending.may_load(deps.storage, self.namespace)?;
+        Ok(pending.map(|p| (p.address, p.expires)))
     }
 
     /// Returns Ok(true) if this is an admin, Ok(false) if not and an Error if
     /// we hit an error with Api or Storage usage
     pub fn is_admin<Q: CustomQuery>(&self, deps: Deps<Q>, caller: &Addr) -> StdResult<bool> {
-        match self.0.load(deps.storage)? {
+        match self.admin.load(deps.storage)? {
             Some(owner) => Ok(caller == &owner),
             None => Ok(false),
         }
@@ -91,17 +153,121 @@ impl<'a> Admin<'a> {
         Ok(Response::new().add_attributes(attributes))
     }
 
+    /// Proposes to transfer control to `new_admin`, which only happens once they accept it.
+    /// This way a typo in the address can't lock everyone out.
+    /// A new proposal replaces the pending one, if any.
+    /// Without `expires`, the proposal expires after DEFAULT_PROPOSAL_TTL.
+    pub fn execute_propose_admin<C, Q: CustomQuery>(
+        &self,
+        deps: DepsMut<Q>,
+        env: Env,
+        info: MessageInfo,
+        new_admin: Addr,
+        expires: Option<Expiration>,
+    ) -> Result<Response<C>, AdminError>
+    where
+        C: Clone + fmt::Debug + PartialEq + JsonSchema,
+    {
+        self.assert_admin(deps.as_ref(), &info.sender)?;
+
+        let expires = expires.unwrap_or_else(|| DEFAULT_PROPOSAL_TTL.after(&env.block));
+        if matches!(expires, Expiration::Never {}) {
+            return Err(AdminError::ProposalNeverExpires {});
+        }
+        if expires.is_expired(&env.block) {
+            return Err(AdminError::ProposalExpired {});
+        }
+        let attributes = vec![
+            attr("action", "propose_admin"),
+            attr("pending_admin", new_admin.as_str()),
+            attr("expires", expires.to_string()),
+            attr("sender", info.sender),
+        ];
+
+        let pending = PendingAdmin {
+            address: new_admin,
+            expires,
+        };
+        self.pending.save(deps.storage, self.namespace, &pending)?;
+
+        Ok(Response::new().add_attributes(attributes))
+    }
+
+    /// Lets the pending admin take over control, if the proposal hasn't expired
+    pub fn execute_accept_admin<C, Q: CustomQuery>(
+        &self,
+        deps: DepsMut<Q>,
+        env: Env,
+        info: MessageInfo,
+    ) -> Result<Response<C>, AdminError>
+    where
+        C: Clone + fmt::Debug + PartialEq + JsonSchema,
+    {
+        let (pending_admin, expires) = self
+            .get_pending(deps.as_ref())?
+            .ok_or(AdminError::NoPendingAdmin {})?;
+        if info.sender != pending_admin {
+            return Err(AdminError::NotPendingAdmin {});
+        }
+        if expires.is_expired(&env.block) {
+            return Err(AdminError::ProposalExpired {});
+        }
+        let attributes = vec![
+            attr("action", "accept_admin"),
+            attr("admin", info.sender.as_str()),
+            attr("sender", info.sender.as_str()),
+        ];
+
+        self.set(deps, Some(info.sender))?;
+
+        Ok(Response::new().add_attributes(attributes))
+    }
+
+    /// Lets the admin withdraw a pending proposal, expired or not
+    pub fn execute_cancel_proposal<C, Q: CustomQuery>(
+        &self,
+        deps: DepsMut<Q>,
+        info: MessageInfo,
+    ) -> Result<Response<C>, AdminError>
+    where
+        C: Clone + fmt::Debug + PartialEq + JsonSchema,
+    {
+        self.assert_admin(deps.as_ref(), &info.sender)?;
+        if self.get_pending(deps.as_ref())?.is_none() {
+            return Err(AdminError::NoPendingAdmin {});
+        }
+        let attributes = vec![
+            attr("action", "cancel_admin_proposal"),
+            attr("sender", info.sender),
+        ];
+
+        self.pending.remove(deps.storage, self.namespace);
+
+        Ok(Response::new().add_attributes(attributes))
+    }
+
     pub fn query_admin<Q: CustomQuery>(&self, deps: Deps<Q>) -> StdResult<AdminResponse> {
         let admin = self.get(deps)?.map(String::from);
         Ok(AdminResponse { admin })
     }
+
+    pub fn query_pending_admin<Q: CustomQuery>(
+        &self,
+        deps: Deps<Q>,
+    ) -> StdResult<PendingAdminResponse> {
+        let pending = self.get_pending(deps)?;
+        Ok(PendingAdminResponse {
+            pending_admin: pending.as_ref().map(|(addr, _)| addr.to_string()),
+            expires: pending.map(|(_, expires)| expires),
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    use cosmwasm_std::testing::{mock_dependencies, mock_info};
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
     use cosmwasm_std::Empty;
 
     #[test]
@@ -181,4 +347,240 @@ mod tests {
         let res = control.query_admin(deps.as_ref()).unwrap();
         assert_eq!(Some(friend.to_string()), res.admin);
     }
+
+    #[test]
+    fn propose_and_accept() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let control = Admin::new("foo");
+        let owner = Addr::unchecked("big boss");
+        let friend = Addr::unchecked("buddy");
+        let imposter = Addr::unchecked("imposter");
+        control.set(deps.as_mut(), Some(owner.clone())).unwrap();
+
+        // only the admin can propose
+        let err = control
+            .execute_propose_admin::<Empty, Empty>(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(imposter.as_ref(), &[]),
+                imposter.clone(),
+                None,
+            )
+            .unwrap_err();
+        assert_eq!(AdminError::NotAdmin {}, err);
+
+        let expires = Expiration::AtHeight(env.block.height + 10);
+        control
+            .execute_propose_admin::<Empty, Empty>(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(owner.as_ref(), &[]),
+                friend.clone(),
+                Some(expires),
+            )
+            .unwrap();
+        let res = control.query_pending_admin(deps.as_ref()).unwrap();
+        assert_eq!(
+            PendingAdminResponse {
+                pending_admin: Some(friend.to_string()),
+                expires: Some(expires),
+            },
+            res
+        );
+        // the admin doesn't change until the proposal is accepted
+        let res = control.query_admin(deps.as_ref()).unwrap();
+        assert_eq!(Some(owner.to_string()), res.admin);
+
+        // only the proposed admin can accept
+        let err = control
+            .execute_accept_admin::<Empty, Empty>(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(imposter.as_ref(), &[]),
+            )
+            .unwrap_err();
+        assert_eq!(AdminError::NotPendingAdmin {}, err);
+
+        control
+            .execute_accept_admin::<Empty, Empty>(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(friend.as_ref(), &[]),
+            )
+            .unwrap();
+        let res = control.query_admin(deps.as_ref()).unwrap();
+        assert_eq!(Some(friend.to_string()), res.admin);
+        let res = control.query_pending_admin(deps.as_ref()).unwrap();
+        assert_eq!(None, res.pending_admin);
+
+        // it can't be accepted twice
+        let err = control
+            .execute_accept_admin::<Empty, Empty>(
+                deps.as_mut(),
+                env,
+                mock_info(friend.as_ref(), &[]),
+            )
+            .unwrap_err();
+        assert_eq!(AdminError::NoPendingAdmin {}, err);
+    }
+
+    #[test]
+    fn expired_proposals() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+
+        let control = Admin::new("foo");
+        let owner = Addr::unchecked("big boss");
+        let friend = Addr::unchecked("buddy");
+        control.set(deps.as_mut(), Some(owner.clone())).unwrap();
+
+        // can't propose with an expiration in the past
+        let err = control
+            .execute_propose_admin::<Empty, Empty>(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(owner.as_ref(), &[]),
+                friend.clone(),
+                Some(Expiration::AtHeight(env.block.height)),
+            )
+            .unwrap_err();
+        assert_eq!(AdminError::ProposalExpired {}, err);
+
+        // nor one that never expires
+        let err = control
+            .execute_propose_admin::<Empty, Empty>(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(owner.as_ref(), &[]),
+                friend.clone(),
+                Some(Expiration::Never {}),
+            )
+            .unwrap_err();
+        assert_eq!(AdminError::ProposalNeverExpires {}, err);
+
+        // without an expiration, the default one applies
+        control
+            .execute_propose_admin::<Empty, Empty>(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(owner.as_ref(), &[]),
+                friend.clone(),
+                None,
+            )
+            .unwrap();
+        let res = control.query_pending_admin(deps.as_ref()).unwrap();
+        assert_eq!(Some(DEFAULT_PROPOSAL_TTL.after(&env.block)), res.expires);
+
+        control
+            .execute_propose_admin::<Empty, Empty>(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(owner.as_ref(), &[]),
+                friend.clone(),
+                Some(Expiration::AtHeight(env.block.height + 10)),
+            )
+            .unwrap();
+
+        env.block.height += 10;
+        let err = control
+            .execute_accept_admin::<Empty, Empty>(
+                deps.as_mut(),
+                env,
+                mock_info(friend.as_ref(), &[]),
+            )
+            .unwrap_err();
+        assert_eq!(AdminError::ProposalExpired {}, err);
+        let res = control.query_admin(deps.as_ref()).unwrap();
+        assert_eq!(Some(owner.to_string()), res.admin);
+    }
+
+    #[test]
+    fn cancel_proposals() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let control = Admin::new("foo");
+        let other = Admin::new("bar");
+        let owner = Addr::unchecked("big boss");
+        let friend = Addr::unchecked("buddy");
+        control.set(deps.as_mut(), Some(owner.clone())).unwrap();
+        other.set(deps.as_mut(), Some(owner.clone())).unwrap();
+
+        let propose = |deps: DepsMut, control: &Admin| {
+            control
+                .execute_propose_admin::<Empty, Empty>(
+                    deps,
+                    env.clone(),
+                    mock_info(owner.as_ref(), &[]),
+                    friend.clone(),
+                    None,
+                )
+                .unwrap();
+        };
+        propose(deps.as_mut(), &control);
+        propose(deps.as_mut(), &other);
+
+        // only the admin can cancel
+        let err = control
+            .execute_cancel_proposal::<Empty, Empty>(deps.as_mut(), mock_info(friend.as_ref(), &[]))
+            .unwrap_err();
+        assert_eq!(AdminError::NotAdmin {}, err);
+
+        control
+            .execute_cancel_proposal::<Empty, Empty>(deps.as_mut(), mock_info(owner.as_ref(), &[]))
+            .unwrap();
+        let err = control
+            .execute_accept_admin::<Empty, Empty>(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(friend.as_ref(), &[]),
+            )
+            .unwrap_err();
+        assert_eq!(AdminError::NoPendingAdmin {}, err);
+
+        // proposals of other admins are kept apart
+        let res = other.query_pending_admin(deps.as_ref()).unwrap();
+        assert_eq!(Some(friend.to_string()), res.pending_admin);
+
+        // updating the admin directly drops the pending proposal
+        other
+            .execute_update_admin::<Empty, Empty>(
+                deps.as_mut(),
+                mock_info(owner.as_ref(), &[]),
+                None,
+            )
+            .unwrap();
+        let res = other.query_pending_admin(deps.as_ref()).unwrap();
+        assert_eq!(None, res.pending_admin);
+    }
+
+    #[test]
+    fn custom_pending_namespace() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        // both share the admin, but keep their proposals apart
+        let control = Admin::new("foo");
+        let custom = Admin::with_pending_namespace("foo", "foo_pending");
+        let owner = Addr::unchecked("big boss");
+        let friend = Addr::unchecked("buddy");
+        control.set(deps.as_mut(), Some(owner.clone())).unwrap();
+        assert!(custom.is_admin(deps.as_ref(), &owner).unwrap());
+
+        custom
+            .execute_propose_admin::<Empty, Empty>(
+                deps.as_mut(),
+                env,
+                mock_info(owner.as_ref(), &[]),
+                friend.clone(),
+                None,
+            )
+            .unwrap();
+        let res = custom.query_pending_admin(deps.as_ref()).unwrap();
+        assert_eq!(Some(friend.to_string()), res.pending_admin);
+        let res = control.query_pending_admin(deps.as_ref()).unwrap();
+        assert_eq!(None, res.pending_admin);
+    }
 }
@@ -12,12 +12,28 @@ goes for smaller scale units.
 
 Supported controllers:
 
-* Admin (`UpdateAdmin` handler, `Admin` querier, set_admin and is_admin methods)
+* Admin (`UpdateAdmin`, `ProposeAdmin`, `AcceptAdmin` and `CancelProposal` handlers, `Admin` and `PendingAdmin` queriers, set_admin and is_admin methods)
+* Permits (`RevokePermit` handler, `RevokedPermits` querier with the `iterator` feature, SNIP-24 permit validation)
+* Roles, with the `iterator` feature (`GrantRole`, `RevokeRole`, `RenounceRole` and `SetRoleAdmin` handlers, `RoleMembers` querier, has_role and assert_role methods)
+* ViewingKeys (`CreateViewingKey` and `SetViewingKey` handlers, `query_with_key` guard, check method)
 */
 mod admin;
 mod claim;
 mod hooks;
+mod permit;
+#[cfg(feature = "iterator")]
+mod roles;
+mod viewing_key;
 
-pub use admin::{Admin, AdminError, AdminResponse};
+pub use admin::{
+    Admin, AdminError, AdminResponse, PendingAdminResponse, DEFAULT_PROPOSAL_TTL, PENDING_NAMESPACE,
+};
 pub use claim::{Claim, Claims, ClaimsResponse};
 pub use hooks::{HookError, Hooks, HooksResponse};
+pub use permit::{
+    Permit, PermitError, PermitParams, PermitSignature, Permits, PubKey, RevokedPermitsResponse,
+    TokenPermissions, PUB_KEY_TYPE, SECRET_HRP,
+};
+#[cfg(feature = "iterator")]
+pub use roles::{RoleMembersResponse, Roles, RolesError};
+pub use viewing_key::{ViewingKeyError, ViewingKeyResponse, ViewingKeys, VIEWING_KEY_PREFIX};
@@ -0,0 +1,483 @@
+use bech32::{ToBase32, Variant};
+use ripemd::Ripemd160;
+use schemars::JsonSchema;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use thiserror::Error;
+
+use cosmwasm_schema::cw_serde;
+#[cfg(feature = "iterator")]
+use cosmwasm_std::Order;
+use cosmwasm_std::{
+    attr, to_binary, Addr, Binary, CustomQuery, Deps, DepsMut, MessageInfo, Response, StdError,
+    StdResult, Storage, Uint128,
+};
+#[cfg(feature = "iterator")]
+use cw_storage_plus::Bound;
+use cw_storage_plus::Map;
+
+#[cfg(feature = "iterator")]
+const DEFAULT_LIMIT: u32 = 10;
+#[cfg(feature = "iterator")]
+const MAX_LIMIT: u32 = 30;
+
+/// The type of the only public keys permits can be signed with
+pub const PUB_KEY_TYPE: &str = "tendermint/PubKeySecp256k1";
+
+/// The bech32 prefix of Secret Network addresses
+pub const SECRET_HRP: &str = "secret";
+
+/// A query permit, as defined by SNIP-24: a signed statement that lets whoever holds it run
+/// queries on behalf of the signer.
+#[cw_serde]
+pub struct Permit<Permission = TokenPermissions> {
+    pub params: PermitParams<Permission>,
+    pub signature: PermitSignature,
+}
+
+impl<Permission: PartialEq> Permit<Permission> {
+    /// Returns true if the permit grants the permission
+    pub fn check_permission(&self, permission: &Permission) -> bool {
+        self.params.permissions.contains(permission)
+    }
+}
+
+#[cw_serde]
+pub struct PermitParams<Permission = TokenPermissions> {
+    /// the contracts the permit can be used with
+    pub allowed_tokens: Vec<String>,
+    pub permit_name: String,
+    pub chain_id: String,
+    pub permissions: Vec<Permission>,
+}
+
+#[cw_serde]
+pub struct PermitSignature {
+    pub pub_key: PubKey,
+    pub signature: Binary,
+}
+
+#[cw_serde]
+pub struct PubKey {
+    /// ignored, but must be "tendermint/PubKeySecp256k1" otherwise the verification will fail
+    pub r#type: String,
+    /// Secp256k1 PubKey
+    pub value: Binary,
+}
+
+impl PubKey {
+    /// Derives the bech32 address of the account that owns this key
+    pub fn account(&self, hrp: &str) -> Result<Addr, PermitError> {
+        if self.r#type != PUB_KEY_TYPE || self.value.len() != 33 {
+            return Err(PermitError::InvalidPubKey {});
+        }
+        let hash = Ripemd160::digest(Sha256::digest(self.value.as_slice()));
+        let account = bech32::encode(hrp, hash.to_base32(), Variant::Bech32)
+            .map_err(|err| StdError::generic_err(err.to_string()))?;
+        Ok(Addr::unchecked(account))
+    }
+}
+
+/// The permissions defined by SNIP-24 for token contracts
+#[cw_serde]
+pub enum TokenPermissions {
+    /// Allowance for SNIP-20 - Permission to query allowance of the owner & spender
+    Allowance,
+    /// Balance for SNIP-20 - Permission to query balance
+    Balance,
+    /// History for SNIP-20 - Permission to query transfer_history & transaction_history
+    History,
+    /// Owner permission indicates that the bearer of this permit should be granted all
+    /// the access of the creator/signer of the permit.  SNIP-721 uses this to grant
+    /// viewing access to all data that the permit creator owns and is whitelisted for.
+    /// For SNIP-721 use, a permit with Owner permission should NEVER be given to
+    /// anyone else.  If someone wants to share private data, they should whitelist
+    /// the address they want to share with via a SetWhitelistedApproval tx, and that
+    /// address will view the data by creating their own permit with Owner permission
+    Owner,
+}
+
+/// The document that is actually signed, in the Amino JSON format wallets use.
+/// Fields are declared in alphabetical order, as the format requires.
+#[derive(Serialize)]
+struct SignedPermit<'p, Permission> {
+    account_number: Uint128,
+    chain_id: &'p str,
+    fee: Fee,
+    memo: &'p str,
+    msgs: [PermitMsg<'p, Permission>; 1],
+    sequence: Uint128,
+}
+
+#[derive(Serialize)]
+struct Fee {
+    amount: [FeeCoin; 1],
+    gas: Uint128,
+}
+
+#[derive(Serialize)]
+struct FeeCoin {
+    amount: Uint128,
+    denom: &'static str,
+}
+
+#[derive(Serialize)]
+struct PermitMsg<'p, Permission> {
+    r#type: &'static str,
+    value: PermitContent<'p, Permission>,
+}
+
+#[derive(Serialize)]
+struct PermitContent<'p, Permission> {
+    allowed_tokens: &'p [String],
+    permissions: &'p [Permission],
+    permit_name: &'p str,
+}
+
+impl<'p, Permission> SignedPermit<'p, Permission> {
+    fn from_params(params: &'p PermitParams<Permission>) -> Self {
+        SignedPermit {
+            account_number: Uint128::zero(),
+            chain_id: &params.chain_id,
+            fee: Fee {
+                amount: [FeeCoin {
+                    amount: Uint128::zero(),
+                    denom: "uscrt",
+                }],
+                gas: Uint128::new(1),
+            },
+            memo: "",
+            msgs: [PermitMsg {
+                r#type: "query_permit",
+                value: PermitContent {
+                    allowed_tokens: &params.allowed_tokens,
+                    permissions: &params.permissions,
+                    permit_name: &params.permit_name,
+                },
+            }],
+            sequence: Uint128::zero(),
+        }
+    }
+}
+
+/// Returned from Permits.query_revoked()
+#[cw_serde]
+pub struct RevokedPermitsResponse {
+    pub permit_names: Vec<String>,
+}
+
+/// Errors returned from Permits
+#[derive(Error, Debug, PartialEq)]
+pub enum PermitError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Permit doesn't apply to contract {contract}")]
+    NotAllowed { contract: String },
+
+    #[error("Permit {name} was revoked by {account}")]
+    Revoked { name: String, account: String },
+
+    #[error("Permit public key must be a compressed secp256k1 key")]
+    InvalidPubKey {},
+
+    #[error("Failed to verify permit signature")]
+    InvalidSignature {},
+}
+
+// state/logic
+pub struct Permits<'a>(Map<'a, (&'a Addr, &'a str), ()>);
+
+impl<'a> Permits<'a> {
+    pub const fn new(revoked_namespace: &'a str) -> Self {
+        Permits(Map::new(revoked_namespace))
+    }
+
+    /// Revokes every permit the account signed with this name
+    pub fn revoke(
+        &self,
+        storage: &mut dyn Storage,
+        account: &Addr,
+        permit_name: &str,
+    ) -> StdResult<()> {
+        self.0.save(storage, (account, permit_name), &())
+    }
+
+    pub fn is_revoked(
+        &self,
+        storage: &dyn Storage,
+        account: &Addr,
+        permit_name: &str,
+    ) -> StdResult<bool> {
+        Ok(self.0.has(storage, (account, permit_name)))
+    }
+
+    /// Checks that the permit can be used with `contract`, that it wasn't revoked and that it
+    /// was signed by the owner of its public key, and returns the address of that account.
+    ///
+    /// The address is derived with the `hrp` bech32 prefix, "secret" by default.
+    pub fn validate<Permission: Serialize, Q: CustomQuery>(
+        &self,
+        deps: Deps<Q>,
+        contract: &Addr,
+        hrp: Option<&str>,
+        permit: &Permit<Permission>,
+    ) -> Result<Addr, PermitError> {
+        let params = &permit.params;
+        if !params.allowed_tokens.iter().any(|token| token == contract) {
+            return Err(PermitError::NotAllowed {
+                contract: contract.to_string(),
+            });
+        }
+
+        let pub_key = &permit.signature.pub_key;
+        let account = pub_key.account(hrp.unwrap_or(SECRET_HRP))?;
+        if self.is_revoked(deps.storage, &account, &params.permit_name)? {
+            return Err(PermitError::Revoked {
+                name: params.permit_name.clone(),
+                account: account.into(),
+            });
+        }
+
+        let signed_bytes = to_binary(&SignedPermit::from_params(params))?;
+        let hash = Sha256::digest(signed_bytes.as_slice());
+        let verified = deps
+            .api
+            .secp256k1_verify(&hash, &permit.signature.signature, &pub_key.value)
+            .map_err(StdError::from)?;
+        if !verified {
+            return Err(PermitError::InvalidSignature {});
+        }
+        Ok(account)
+    }
+
+    pub fn execute_revoke_permit<C, Q: CustomQuery>(
+        &self,
+        deps: DepsMut<Q>,
+        info: MessageInfo,
+        permit_name: String,
+    ) -> Result<Response<C>, PermitError>
+    where
+        C: Clone + fmt::Debug + PartialEq + JsonSchema,
+    {
+        self.revoke(deps.storage, &info.sender, &permit_name)?;
+
+        let attributes = vec![
+            attr("action", "revoke_permit"),
+            attr("permit_name", permit_name),
+            attr("sender", info.sender),
+        ];
+        Ok(Response::new().add_attributes(attributes))
+    }
+
+    /// Lists the names of the permits the account revoked, in ascending order
+    #[cfg(feature = "iterator")]
+    pub fn query_revoked<Q: CustomQuery>(
+        &self,
+        deps: Deps<Q>,
+        account: &Addr,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<RevokedPermitsResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(|name| Bound::ExclusiveRaw(name.into_bytes()));
+        let permit_names = self
+            .0
+            .prefix(account)
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<_>>()?;
+        Ok(RevokedPermitsResponse { permit_names })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cosmwasm_std::testing::{mock_dependencies, mock_info};
+    use cosmwasm_std::Empty;
+    use k256::ecdsa::signature::Signer;
+    use k256::ecdsa::{Signature, SigningKey};
+
+    const PERMITS: Permits = Permits::new("revoked_permits");
+    const TOKEN: &str = "secret18vd8fpwxzck93qlwghaj6arh4p7c5n8978vsyg";
+
+    fn signing_key(seed: u8) -> SigningKey {
+        SigningKey::from_bytes(&[seed; 32]).unwrap()
+    }
+
+    fn sign(key: &SigningKey, params: PermitParams) -> Permit {
+        let signed_bytes = to_binary(&SignedPermit::from_params(&params)).unwrap();
+        let signature: Signature = key.sign(signed_bytes.as_slice());
+        Permit {
+            params,
+            signature: PermitSignature {
+                pub_key: PubKey {
+                    r#type: PUB_KEY_TYPE.to_string(),
+                    value: key.verifying_key().to_bytes().to_vec().into(),
+                },
+                signature: signature.as_ref().to_vec().into(),
+            },
+        }
+    }
+
+    fn params(name: &str) -> PermitParams {
+        PermitParams {
+            allowed_tokens: vec![TOKEN.to_string()],
+            permit_name: name.to_string(),
+            chain_id: "secret-4".to_string(),
+            permissions: vec![TokenPermissions::Balance],
+        }
+    }
+
+    #[test]
+    fn signed_document_format() {
+        let doc = to_binary(&SignedPermit::from_params(&params("test"))).unwrap();
+        assert_eq!(
+            std::str::from_utf8(doc.as_slice()).unwrap(),
+            concat!(
+                r#"{"account_number":"0","chain_id":"secret-4","#,
+                r#""fee":{"amount":[{"amount":"0","denom":"uscrt"}],"gas":"1"},"memo":"","#,
+                r#""msgs":[{"type":"query_permit","value":{"allowed_tokens":["#,
+                r#""secret18vd8fpwxzck93qlwghaj6arh4p7c5n8978vsyg"],"#,
+                r#""permissions":["balance"],"permit_name":"test"}}],"sequence":"0"}"#
+            )
+        );
+    }
+
+    #[test]
+    fn derive_account() {
+        // the well known address of the secp256k1 generator point, i.e. private key 1
+        let mut key = [0u8; 32];
+        key[31] = 1;
+        let pub_key = PubKey {
+            r#type: PUB_KEY_TYPE.to_string(),
+            value: SigningKey::from_bytes(&key)
+                .unwrap()
+                .verifying_key()
+                .to_bytes()
+                .to_vec()
+                .into(),
+        };
+        assert_eq!(
+            pub_key.account("cosmos").unwrap(),
+            "cosmos1w508d6qejxtdg4y5r3zarvary0c5xw7k6ah60c"
+        );
+
+        let wrong_type = PubKey {
+            r#type: "tendermint/PubKeyEd25519".to_string(),
+            ..pub_key
+        };
+        assert_eq!(
+            wrong_type.account(SECRET_HRP).unwrap_err(),
+            PermitError::InvalidPubKey {}
+        );
+    }
+
+    #[test]
+    fn validate_permits() {
+        let deps = mock_dependencies();
+        let contract = Addr::unchecked(TOKEN);
+        let alice = signing_key(1);
+
+        let permit = sign(&alice, params("test"));
+        let account = PERMITS
+            .validate(deps.as_ref(), &contract, None, &permit)
+            .unwrap();
+        assert!(account.as_str().starts_with("secret1"));
+        assert!(permit.check_permission(&TokenPermissions::Balance));
+        assert!(!permit.check_permission(&TokenPermissions::History));
+
+        // other contracts can't use it
+        let other = Addr::unchecked("other");
+        let err = PERMITS
+            .validate(deps.as_ref(), &other, None, &permit)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            PermitError::NotAllowed {
+                contract: "other".to_string()
+            }
+        );
+
+        // tampering with the params or the key breaks the signature
+        let mut tampered = permit.clone();
+        tampered.params.permissions.push(TokenPermissions::Owner);
+        let err = PERMITS
+            .validate(deps.as_ref(), &contract, None, &tampered)
+            .unwrap_err();
+        assert_eq!(err, PermitError::InvalidSignature {});
+
+        let mut stolen = sign(&signing_key(2), params("test"));
+        stolen.signature.signature = permit.signature.signature;
+        let err = PERMITS
+            .validate(deps.as_ref(), &contract, None, &stolen)
+            .unwrap_err();
+        assert_eq!(err, PermitError::InvalidSignature {});
+    }
+
+    #[test]
+    fn revoke_permits() {
+        let mut deps = mock_dependencies();
+        let contract = Addr::unchecked(TOKEN);
+        let alice = signing_key(1);
+        let permit = sign(&alice, params("test"));
+        let account = PERMITS
+            .validate(deps.as_ref(), &contract, None, &permit)
+            .unwrap();
+
+        let info = mock_info(account.as_str(), &[]);
+        PERMITS
+            .execute_revoke_permit::<Empty, Empty>(deps.as_mut(), info.clone(), "test".to_string())
+            .unwrap();
+        // revoking twice is fine
+        PERMITS
+            .execute_revoke_permit::<Empty, Empty>(deps.as_mut(), info, "test".to_string())
+            .unwrap();
+
+        let err = PERMITS
+            .validate(deps.as_ref(), &contract, None, &permit)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            PermitError::Revoked {
+                name: "test".to_string(),
+                account: account.to_string()
+            }
+        );
+
+        // other permits of the account, and permits of other accounts, still work
+        let renamed = sign(&alice, params("other"));
+        PERMITS
+            .validate(deps.as_ref(), &contract, None, &renamed)
+            .unwrap();
+        let bob = sign(&signing_key(2), params("test"));
+        PERMITS
+            .validate(deps.as_ref(), &contract, None, &bob)
+            .unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn query_revoked_pages() {
+        let mut deps = mock_dependencies();
+        let alice = Addr::unchecked("alice");
+        for name in ["c", "a", "b", "a"] {
+            PERMITS.revoke(&mut deps.storage, &alice, name).unwrap();
+        }
+        PERMITS
+            .revoke(&mut deps.storage, &Addr::unchecked("bob"), "d")
+            .unwrap();
+
+        let res = PERMITS
+            .query_revoked(deps.as_ref(), &alice, None, Some(2))
+            .unwrap();
+        assert_eq!(res.permit_names, vec!["a".to_string(), "b".to_string()]);
+        let res = PERMITS
+            .query_revoked(deps.as_ref(), &alice, Some("b".to_string()), Some(2))
+            .unwrap();
+        assert_eq!(res.permit_names, vec!["c".to_string()]);
+    }
+}
@@ -0,0 +1,438 @@
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt;
+use thiserror::Error;
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    attr, Addr, CustomQuery, Deps, DepsMut, MessageInfo, Order, Response, StdError, StdResult,
+    Storage,
+};
+use cw_storage_plus::{Bound, KeyDeserialize, Map, Prefixer, PrimaryKey};
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+/// Returned from Roles.query_role_members()
+#[cw_serde]
+pub struct RoleMembersResponse {
+    pub members: Vec<String>,
+}
+
+/// Errors returned from Roles
+#[derive(Error, Debug, PartialEq)]
+pub enum RolesError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{account} is missing role {role}")]
+    MissingRole { role: String, account: String },
+}
+
+/// Role based access control, for contracts that need more than a single `Admin`.
+///
+/// Every role is managed by an admin role: only accounts that have the admin role of a role can
+/// grant it or revoke it. The admin role of every role is `R::default()` unless it is changed
+/// with `set_role_admin`, so the accounts with the default role manage all the others.
+///
+/// Roles can be any key type that can also be stored as a value, such as `String` or an enum.
+pub struct Roles<'a, R> {
+    // keyed by owned addresses, as `&Addr` keys would have to live as long as the map
+    members: Map<'a, (R, Addr), ()>,
+    admins: Map<'a, R, R>,
+}
+
+impl<'a, R> Roles<'a, R> {
+    pub const fn new(members_namespace: &'a str, admins_namespace: &'a str) -> Self {
+        Roles {
+            members: Map::new(members_namespace),
+            admins: Map::new(admins_namespace),
+        }
+    }
+}
+
+// this is the core business logic we expose
+impl<'a, R> Roles<'a, R>
+where
+    R: PrimaryKey<'a>
+        + Prefixer<'a>
+        + KeyDeserialize
+        + Serialize
+        + DeserializeOwned
+        + Clone
+        + Default
+        + fmt::Display,
+{
+    pub fn has_role(&self, storage: &dyn Storage, role: R, account: &Addr) -> bool {
+        self.members.has(storage, (role, account.clone()))
+    }
+
+    /// Like has_role but returns RolesError::MissingRole if the account doesn't have the role.
+    /// Helper for a nice one-line auth check.
+    pub fn assert_role(
+        &self,
+        storage: &dyn Storage,
+        role: R,
+        account: &Addr,
+    ) -> Result<(), RolesError> {
+        if !self.has_role(storage, role.clone(), account) {
+            Err(RolesError::MissingRole {
+                role: role.to_string(),
+                account: account.to_string(),
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Gives the role to the account, without checking who is asking for it.
+    /// Use this to set up the initial roles when instantiating.
+    pub fn grant(&self, storage: &mut dyn Storage, role: R, account: &Addr) -> StdResult<()> {
+        self.members.save(storage, (role, account.clone()), &())
+    }
+
+    /// Takes the role from the account, without checking who is asking for it
+    pub fn revoke(&self, storage: &mut dyn Storage, role: R, account: &Addr) {
+        self.members.remove(storage, (role, account.clone()))
+    }
+
+    /// Returns the role that manages `role`
+    pub fn role_admin(&self, storage: &dyn Storage, role: R) -> StdResult<R> {
+        Ok(self.admins.may_load(storage, role)?.unwrap_or_default())
+    }
+
+    /// Makes `admin_role` the role that manages `role`, without checking who is asking for it
+    pub fn set_role_admin(
+        &self,
+        storage: &mut dyn Storage,
+        role: R,
+        admin_role: R,
+    ) -> StdResult<()> {
+        self.admins.save(storage, role, &admin_role)
+    }
+
+    /// Returns RolesError::MissingRole if the account doesn't have the admin role of `role`
+    pub fn assert_role_admin(
+        &self,
+        storage: &dyn Storage,
+        role: R,
+        account: &Addr,
+    ) -> Result<(), RolesError> {
+        let admin_role = self.role_admin(storage, role)?;
+        self.assert_role(storage, admin_role, account)
+    }
+
+    pub fn execute_grant_role<C, Q: CustomQuery>(
+        &self,
+        deps: DepsMut<Q>,
+        info: MessageInfo,
+        role: R,
+        account: Addr,
+    ) -> Result<Response<C>, RolesError>
+    where
+        C: Clone + fmt::Debug + PartialEq + JsonSchema,
+    {
+        self.assert_role_admin(deps.storage, role.clone(), &info.sender)?;
+
+        let attributes = vec![
+            attr("action", "grant_role"),
+            attr("role", role.to_string()),
+            attr("account", account.as_str()),
+            attr("sender", info.sender),
+        ];
+
+        self.grant(deps.storage, role, &account)?;
+
+        Ok(Response::new().add_attributes(attributes))
+    }
+
+    pub fn execute_revoke_role<C, Q: CustomQuery>(
+        &self,
+        deps: DepsMut<Q>,
+        info: MessageInfo,
+        role: R,
+        account: Addr,
+    ) -> Result<Response<C>, RolesError>
+    where
+        C: Clone + fmt::Debug + PartialEq + JsonSchema,
+    {
+        self.assert_role_admin(deps.storage, role.clone(), &info.sender)?;
+
+        let attributes = vec![
+            attr("action", "revoke_role"),
+            attr("role", role.to_string()),
+            attr("account", account.as_str()),
+            attr("sender", info.sender),
+        ];
+
+        self.revoke(deps.storage, role, &account);
+
+        Ok(Response::new().add_attributes(attributes))
+    }
+
+    /// Lets the sender give up one of their own roles
+    pub fn execute_renounce_role<C, Q: CustomQuery>(
+        &self,
+        deps: DepsMut<Q>,
+        info: MessageInfo,
+        role: R,
+    ) -> Result<Response<C>, RolesError>
+    where
+        C: Clone + fmt::Debug + PartialEq + JsonSchema,
+    {
+        self.assert_role(deps.storage, role.clone(), &info.sender)?;
+
+        let attributes = vec![
+            attr("action", "renounce_role"),
+            attr("role", role.to_string()),
+            attr("sender", info.sender.as_str()),
+        ];
+
+        self.revoke(deps.storage, role, &info.sender);
+
+        Ok(Response::new().add_attributes(attributes))
+    }
+
+    pub fn execute_set_role_admin<C, Q: CustomQuery>(
+        &self,
+        deps: DepsMut<Q>,
+        info: MessageInfo,
+        role: R,
+        admin_role: R,
+    ) -> Result<Response<C>, RolesError>
+    where
+        C: Clone + fmt::Debug + PartialEq + JsonSchema,
+    {
+        self.assert_role_admin(deps.storage, role.clone(), &info.sender)?;
+
+        let attributes = vec![
+            attr("action", "set_role_admin"),
+            attr("role", role.to_string()),
+            attr("admin_role", admin_role.to_string()),
+            attr("sender", info.sender),
+        ];
+
+        self.set_role_admin(deps.storage, role, admin_role)?;
+
+        Ok(Response::new().add_attributes(attributes))
+    }
+
+    /// Lists the accounts that have the role, in ascending order
+    pub fn query_role_members<Q: CustomQuery>(
+        &self,
+        deps: Deps<Q>,
+        role: R,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<RoleMembersResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(|addr| Bound::ExclusiveRaw(addr.into_bytes()));
+        let members = self
+            .members
+            .prefix(role)
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|addr| addr.map(String::from))
+            .collect::<StdResult<_>>()?;
+        Ok(RoleMembersResponse { members })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cosmwasm_std::testing::{mock_dependencies, mock_info};
+    use cosmwasm_std::Empty;
+
+    const ROLES: Roles<String> = Roles::new("roles", "role_admins");
+
+    fn role(name: &str) -> String {
+        name.to_string()
+    }
+
+    #[test]
+    fn grant_and_revoke() {
+        let mut deps = mock_dependencies();
+        let minter = Addr::unchecked("minter");
+        let pauser = Addr::unchecked("pauser");
+
+        ROLES
+            .grant(&mut deps.storage, role("minter"), &minter)
+            .unwrap();
+        ROLES
+            .grant(&mut deps.storage, role("pauser"), &pauser)
+            .unwrap();
+        assert!(ROLES.has_role(&deps.storage, role("minter"), &minter));
+        assert!(!ROLES.has_role(&deps.storage, role("minter"), &pauser));
+        ROLES
+            .assert_role(&deps.storage, role("pauser"), &pauser)
+            .unwrap();
+        let err = ROLES
+            .assert_role(&deps.storage, role("pauser"), &minter)
+            .unwrap_err();
+        assert_eq!(
+            RolesError::MissingRole {
+                role: role("pauser"),
+                account: minter.to_string()
+            },
+            err
+        );
+
+        ROLES.revoke(&mut deps.storage, role("minter"), &minter);
+        assert!(!ROLES.has_role(&deps.storage, role("minter"), &minter));
+        assert!(ROLES.has_role(&deps.storage, role("pauser"), &pauser));
+    }
+
+    #[test]
+    fn role_admins() {
+        let mut deps = mock_dependencies();
+        let owner = Addr::unchecked("owner");
+        let manager = Addr::unchecked("manager");
+        let minter = Addr::unchecked("minter");
+        ROLES
+            .grant(&mut deps.storage, String::new(), &owner)
+            .unwrap();
+
+        // the default role manages every role
+        assert_eq!(
+            String::new(),
+            ROLES.role_admin(&deps.storage, role("minter")).unwrap()
+        );
+        let info = mock_info(owner.as_str(), &[]);
+        ROLES
+            .execute_grant_role::<Empty, Empty>(
+                deps.as_mut(),
+                info.clone(),
+                role("manager"),
+                manager.clone(),
+            )
+            .unwrap();
+
+        // hand minters over to the managers
+        ROLES
+            .execute_set_role_admin::<Empty, Empty>(
+                deps.as_mut(),
+                info,
+                role("minter"),
+                role("manager"),
+            )
+            .unwrap();
+        assert_eq!(
+            role("manager"),
+            ROLES.role_admin(&deps.storage, role("minter")).unwrap()
+        );
+
+        // now only managers can grant it
+        let err = ROLES
+            .execute_grant_role::<Empty, Empty>(
+                deps.as_mut(),
+                mock_info(owner.as_str(), &[]),
+                role("minter"),
+                minter.clone(),
+            )
+            .unwrap_err();
+        assert_eq!(
+            RolesError::MissingRole {
+                role: role("manager"),
+                account: owner.to_string()
+            },
+            err
+        );
+        let res = ROLES
+            .execute_grant_role::<Empty, Empty>(
+                deps.as_mut(),
+                mock_info(manager.as_str(), &[]),
+                role("minter"),
+                minter.clone(),
+            )
+            .unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "grant_role"),
+                attr("role", "minter"),
+                attr("account", "minter"),
+                attr("sender", "manager"),
+            ]
+        );
+        assert!(ROLES.has_role(&deps.storage, role("minter"), &minter));
+
+        // and revoke it
+        ROLES
+            .execute_revoke_role::<Empty, Empty>(
+                deps.as_mut(),
+                mock_info(manager.as_str(), &[]),
+                role("minter"),
+                minter.clone(),
+            )
+            .unwrap();
+        assert!(!ROLES.has_role(&deps.storage, role("minter"), &minter));
+    }
+
+    #[test]
+    fn renounce() {
+        let mut deps = mock_dependencies();
+        let pauser = Addr::unchecked("pauser");
+        ROLES
+            .grant(&mut deps.storage, role("pauser"), &pauser)
+            .unwrap();
+
+        let info = mock_info(pauser.as_str(), &[]);
+        ROLES
+            .execute_renounce_role::<Empty, Empty>(deps.as_mut(), info.clone(), role("pauser"))
+            .unwrap();
+        assert!(!ROLES.has_role(&deps.storage, role("pauser"), &pauser));
+
+        // can't renounce a role twice
+        let err = ROLES
+            .execute_renounce_role::<Empty, Empty>(deps.as_mut(), info, role("pauser"))
+            .unwrap_err();
+        assert_eq!(
+            RolesError::MissingRole {
+                role: role("pauser"),
+                account: pauser.to_string()
+            },
+            err
+        );
+    }
+
+    #[test]
+    fn query_members() {
+        let mut deps = mock_dependencies();
+        for i in 0..5 {
+            let account = Addr::unchecked(format!("minter{}", i));
+            ROLES
+                .grant(&mut deps.storage, role("minter"), &account)
+                .unwrap();
+        }
+        ROLES
+            .grant(
+                &mut deps.storage,
+                role("minters"),
+                &Addr::unchecked("other"),
+            )
+            .unwrap();
+
+        let res = ROLES
+            .query_role_members(deps.as_ref(), role("minter"), None, Some(3))
+            .unwrap();
+        assert_eq!(res.members, vec!["minter0", "minter1", "minter2"]);
+
+        let res = ROLES
+            .query_role_members(
+                deps.as_ref(),
+                role("minter"),
+                Some("minter2".to_string()),
+                None,
+            )
+            .unwrap();
+        assert_eq!(res.members, vec!["minter3", "minter4"]);
+
+        let res = ROLES
+            .query_role_members(deps.as_ref(), role("pauser"), None, None)
+            .unwrap();
+        assert!(res.members.is_empty());
+    }
+}
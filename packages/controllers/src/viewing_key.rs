@@ -0,0 +1,338 @@
+use schemars::JsonSchema;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use thiserror::Error;
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    attr, to_binary, Addr, Binary, CustomQuery, Deps, DepsMut, Env, MessageInfo, Response,
+    StdError, StdResult, Storage,
+};
+use cw_storage_plus::{Item, Map};
+
+/// Every generated viewing key starts with this prefix
+pub const VIEWING_KEY_PREFIX: &str = "api_key_";
+
+/// Returned in the data of ViewingKeys.execute_create_viewing_key()
+#[cw_serde]
+pub struct ViewingKeyResponse {
+    pub key: String,
+}
+
+/// Errors returned from ViewingKeys
+#[derive(Error, Debug, PartialEq)]
+pub enum ViewingKeyError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Wrong viewing key for this address or viewing key not set")]
+    Unauthorized {},
+
+    #[error("Viewing key seed has not been set")]
+    SeedNotSet {},
+}
+
+// state/logic
+pub struct ViewingKeys<'a> {
+    /// the sha256 hash of the viewing key of every account
+    keys: Map<'a, &'a Addr, Binary>,
+    /// the secret that new keys are derived from, updated every time a key is created
+    seed: Item<'a, Binary>,
+}
+
+impl<'a> ViewingKeys<'a> {
+    pub const fn new(keys_namespace: &'a str, seed_key: &'a str) -> Self {
+        ViewingKeys {
+            keys: Map::new(keys_namespace),
+            seed: Item::new(seed_key),
+        }
+    }
+
+    /// Sets the secret that keys are generated from. Call this once when instantiating, with
+    /// entropy provided by the instantiator, so keys can't be predicted from public data.
+    /// Creating keys fails until it is set.
+    pub fn set_seed(&self, storage: &mut dyn Storage, seed: &[u8]) -> StdResult<()> {
+        self.seed.save(storage, &sha_256(&[seed]).into())
+    }
+
+    /// Sets the viewing key of the account. Only its hash is stored.
+    pub fn set(&self, storage: &mut dyn Storage, account: &Addr, key: &str) -> StdResult<()> {
+        self.keys
+            .save(storage, account, &sha_256(&[key.as_bytes()]).into())
+    }
+
+    /// Removes the viewing key of the account
+    pub fn remove(&self, storage: &mut dyn Storage, account: &Addr) {
+        self.keys.remove(storage, account)
+    }
+
+    /// Generates a new viewing key for the sender, saves it and returns it.
+    ///
+    /// The key is derived from the stored seed, the entropy provided by the sender, the block
+    /// randomness if the chain provides it, and the block and transaction the message was sent
+    /// in. The seed is updated every time, so every key that is created is different.
+    ///
+    /// Returns ViewingKeyError::SeedNotSet if `set_seed` was never called.
+    pub fn create(
+        &self,
+        storage: &mut dyn Storage,
+        env: &Env,
+        info: &MessageInfo,
+        entropy: &str,
+    ) -> Result<String, ViewingKeyError> {
+        let seed = self
+            .seed
+            .may_load(storage)?
+            .ok_or(ViewingKeyError::SeedNotSet {})?;
+        let block_random = env.block.random.as_ref().map_or(&[][..], |r| r.as_slice());
+        let tx_index = env.transaction.as_ref().map_or(0, |tx| tx.index);
+        let rand = sha_256(&[
+            seed.as_slice(),
+            block_random,
+            entropy.as_bytes(),
+            &env.block.height.to_be_bytes(),
+            &env.block.time.nanos().to_be_bytes(),
+            &tx_index.to_be_bytes(),
+            info.sender.as_bytes(),
+        ]);
+        self.seed.save(storage, &rand.into())?;
+
+        let key = format!(
+            "{}{}",
+            VIEWING_KEY_PREFIX,
+            Binary::from(sha_256(&[&rand])).to_base64()
+        );
+        self.set(storage, &info.sender, &key)?;
+        Ok(key)
+    }
+
+    /// Returns Ok(true) if this is the viewing key of the account, Ok(false) if not or if the
+    /// account has no key, and an Error if we hit an error with Storage usage.
+    ///
+    /// The comparison takes the same time whether or not the key matches, or the account has
+    /// a key at all, so keys can't be guessed byte by byte.
+    pub fn is_valid(&self, storage: &dyn Storage, account: &Addr, key: &str) -> StdResult<bool> {
+        let stored = self.keys.may_load(storage, account)?;
+        let hash = sha_256(&[key.as_bytes()]);
+        // compare against a dummy hash when there is no key, so this takes the same time
+        let valid = match &stored {
+            Some(stored) => ct_eq(stored, &hash),
+            None => ct_eq(&[0u8; 32], &hash),
+        };
+        Ok(valid && stored.is_some())
+    }
+
+    /// Like is_valid but returns ViewingKeyError::Unauthorized if the key is wrong.
+    /// Helper for a nice one-line auth check.
+    pub fn check(
+        &self,
+        storage: &dyn Storage,
+        account: &Addr,
+        key: &str,
+    ) -> Result<(), ViewingKeyError> {
+        if !self.is_valid(storage, account, key)? {
+            Err(ViewingKeyError::Unauthorized {})
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn execute_create_viewing_key<C, Q: CustomQuery>(
+        &self,
+        deps: DepsMut<Q>,
+        env: Env,
+        info: MessageInfo,
+        entropy: String,
+    ) -> Result<Response<C>, ViewingKeyError>
+    where
+        C: Clone + fmt::Debug + PartialEq + JsonSchema,
+    {
+        let key = self.create(deps.storage, &env, &info, &entropy)?;
+
+        // the key only goes in the data, which is encrypted for the sender
+        let attributes = vec![
+            attr("action", "create_viewing_key"),
+            attr("sender", info.sender),
+        ];
+        Ok(Response::new()
+            .add_attributes(attributes)
+            .set_data(to_binary(&ViewingKeyResponse { key })?))
+    }
+
+    pub fn execute_set_viewing_key<C, Q: CustomQuery>(
+        &self,
+        deps: DepsMut<Q>,
+        info: MessageInfo,
+        key: String,
+    ) -> Result<Response<C>, ViewingKeyError>
+    where
+        C: Clone + fmt::Debug + PartialEq + JsonSchema,
+    {
+        self.set(deps.storage, &info.sender, &key)?;
+
+        let attributes = vec![
+            attr("action", "set_viewing_key"),
+            attr("sender", info.sender),
+        ];
+        Ok(Response::new().add_attributes(attributes))
+    }
+
+    /// Runs the query only if `key` is the viewing key of `account`, and returns
+    /// ViewingKeyError::Unauthorized otherwise.
+    pub fn query_with_key<Q, T, E, F>(
+        &self,
+        deps: Deps<Q>,
+        account: &Addr,
+        key: &str,
+        query: F,
+    ) -> Result<T, E>
+    where
+        Q: CustomQuery,
+        E: From<ViewingKeyError>,
+        F: FnOnce(Deps<Q>) -> Result<T, E>,
+    {
+        self.check(deps.storage, account, key)?;
+        query(deps)
+    }
+}
+
+fn sha_256(parts: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize().into()
+}
+
+/// Compares two byte strings of the same length in constant time
+fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{from_binary, Empty};
+
+    const VIEWING_KEYS: ViewingKeys = ViewingKeys::new("viewing_keys", "vk_seed");
+
+    #[test]
+    fn set_and_check() {
+        let mut deps = mock_dependencies();
+        let alice = Addr::unchecked("alice");
+        let bob = Addr::unchecked("bob");
+
+        // no key set
+        assert!(!VIEWING_KEYS.is_valid(&deps.storage, &alice, "").unwrap());
+        let err = VIEWING_KEYS
+            .check(&deps.storage, &alice, "key")
+            .unwrap_err();
+        assert_eq!(ViewingKeyError::Unauthorized {}, err);
+
+        VIEWING_KEYS
+            .set(&mut deps.storage, &alice, "secret")
+            .unwrap();
+        VIEWING_KEYS.check(&deps.storage, &alice, "secret").unwrap();
+        let err = VIEWING_KEYS
+            .check(&deps.storage, &alice, "wrong")
+            .unwrap_err();
+        assert_eq!(ViewingKeyError::Unauthorized {}, err);
+        let err = VIEWING_KEYS
+            .check(&deps.storage, &bob, "secret")
+            .unwrap_err();
+        assert_eq!(ViewingKeyError::Unauthorized {}, err);
+
+        // only the hash is stored
+        let stored = VIEWING_KEYS.keys.load(&deps.storage, &alice).unwrap();
+        assert_ne!(stored.as_slice(), b"secret");
+
+        VIEWING_KEYS.remove(&mut deps.storage, &alice);
+        let err = VIEWING_KEYS
+            .check(&deps.storage, &alice, "secret")
+            .unwrap_err();
+        assert_eq!(ViewingKeyError::Unauthorized {}, err);
+    }
+
+    #[test]
+    fn create_keys() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("alice", &[]);
+
+        // keys can't be created before the seed is set
+        let err = VIEWING_KEYS
+            .create(&mut deps.storage, &env, &info, "entropy")
+            .unwrap_err();
+        assert_eq!(ViewingKeyError::SeedNotSet {}, err);
+        VIEWING_KEYS.set_seed(&mut deps.storage, b"seed").unwrap();
+
+        let first = VIEWING_KEYS
+            .create(&mut deps.storage, &env, &info, "entropy")
+            .unwrap();
+        assert!(first.starts_with(VIEWING_KEY_PREFIX));
+        VIEWING_KEYS
+            .check(&deps.storage, &info.sender, &first)
+            .unwrap();
+
+        // the block randomness goes into the key
+        let mut other = mock_dependencies();
+        VIEWING_KEYS.set_seed(&mut other.storage, b"seed").unwrap();
+        let mut other_env = env.clone();
+        other_env.block.random = Some(Binary::from(b"other random".as_slice()));
+        let other_key = VIEWING_KEYS
+            .create(&mut other.storage, &other_env, &info, "entropy")
+            .unwrap();
+        assert_ne!(first, other_key);
+
+        // the same entropy in the same block still gives a new key, replacing the old one
+        let second = VIEWING_KEYS
+            .create(&mut deps.storage, &env, &info, "entropy")
+            .unwrap();
+        assert_ne!(first, second);
+        VIEWING_KEYS
+            .check(&deps.storage, &info.sender, &second)
+            .unwrap();
+        let err = VIEWING_KEYS
+            .check(&deps.storage, &info.sender, &first)
+            .unwrap_err();
+        assert_eq!(ViewingKeyError::Unauthorized {}, err);
+    }
+
+    #[test]
+    fn test_execute_query() {
+        let mut deps = mock_dependencies();
+        let alice = Addr::unchecked("alice");
+        VIEWING_KEYS.set_seed(&mut deps.storage, b"seed").unwrap();
+
+        // create returns the key in the data, without leaking it in the attributes
+        let res = VIEWING_KEYS
+            .execute_create_viewing_key::<Empty, Empty>(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(alice.as_ref(), &[]),
+                "entropy".to_string(),
+            )
+            .unwrap();
+        let ViewingKeyResponse { key } = from_binary(&res.data.unwrap()).unwrap();
+        assert!(res.attributes.iter().all(|attr| attr.value != key));
+
+        let query = |_: Deps| -> Result<u64, ViewingKeyError> { Ok(42) };
+        let res = VIEWING_KEYS.query_with_key(deps.as_ref(), &alice, &key, query);
+        assert_eq!(Ok(42), res);
+
+        // set replaces the key
+        VIEWING_KEYS
+            .execute_set_viewing_key::<Empty, Empty>(
+                deps.as_mut(),
+                mock_info(alice.as_ref(), &[]),
+                "chosen".to_string(),
+            )
+            .unwrap();
+        let res = VIEWING_KEYS.query_with_key(deps.as_ref(), &alice, &key, query);
+        assert_eq!(Err(ViewingKeyError::Unauthorized {}), res);
+        let res = VIEWING_KEYS.query_with_key(deps.as_ref(), &alice, "chosen", query);
+        assert_eq!(Ok(42), res);
+    }
+}
@@ -5,7 +5,13 @@ use criterion::{black_box, criterion_group, Criterion};
 use secret_borsh_storage::BorshItem;
 use serde::{Deserialize, Serialize};
 
-use secret_storage_plus::{Bincode2, Item, Json, Serde};
+#[cfg(feature = "cbor")]
+use secret_storage_plus::Cbor;
+#[cfg(feature = "msgpack")]
+use secret_storage_plus::MsgPack;
+#[cfg(feature = "postcard")]
+use secret_storage_plus::Postcard;
+use secret_storage_plus::{Bincode2, Borsh, Item, Json, Serde};
 
 #[derive(Serialize, Deserialize, BorshDeserialize, BorshSerialize, PartialEq, Debug)]
 struct Config {
@@ -50,12 +56,26 @@ impl BigChungis {
 const CONFIG: Item<Config, Bincode2> = Item::new("blahblahbalh");
 const CONFIG_JSON: Item<Config, Json> = Item::new("blahblahbalh");
 const CONFIG_BORSH: BorshItem<Config> = BorshItem::new("adwadwa");
+const CONFIG_PLUS_BORSH: Item<Config, Borsh> = Item::new("blahblahbalh");
+#[cfg(feature = "msgpack")]
+const CONFIG_MSGPACK: Item<Config, MsgPack> = Item::new("blahblahbalh");
+#[cfg(feature = "cbor")]
+const CONFIG_CBOR: Item<Config, Cbor> = Item::new("blahblahbalh");
+#[cfg(feature = "postcard")]
+const CONFIG_POSTCARD: Item<Config, Postcard> = Item::new("blahblahbalh");
 
 const BIG: Item<BigChungis, Bincode2> = Item::new("blahblahbalh");
 const BIG_JSON: Item<BigChungis, Json> = Item::new("blahblahbalh");
 const BIG_BORSH: BorshItem<BigChungis> = BorshItem::new("adwadwa");
-
-fn big_save_and_load(item: Item<BigChungis, impl Serde>) {
+const BIG_PLUS_BORSH: Item<BigChungis, Borsh> = Item::new("blahblahbalh");
+#[cfg(feature = "msgpack")]
+const BIG_MSGPACK: Item<BigChungis, MsgPack> = Item::new("blahblahbalh");
+#[cfg(feature = "cbor")]
+const BIG_CBOR: Item<BigChungis, Cbor> = Item::new("blahblahbalh");
+#[cfg(feature = "postcard")]
+const BIG_POSTCARD: Item<BigChungis, Postcard> = Item::new("blahblahbalh");
+
+fn big_save_and_load(item: Item<BigChungis, impl Serde<BigChungis>>) {
     let mut store = MockStorage::new();
 
     assert!(item.load(&store).is_err());
@@ -91,7 +111,7 @@ fn borsh_save_and_load(item: BorshItem<Config>) {
     assert_eq!(cfg, item.load(&store).unwrap());
 }
 
-fn save_and_load(item: Item<Config, impl Serde>) {
+fn save_and_load(item: Item<Config, impl Serde<Config>>) {
     let mut store = MockStorage::new();
 
     assert!(item.load(&store).is_err());
@@ -118,6 +138,25 @@ fn bench_bincode2_json(c: &mut Criterion) {
         b.iter(|| borsh_save_and_load(black_box(CONFIG_BORSH)))
     });
 
+    group.bench_function("storage-plus borsh save & load", |b| {
+        b.iter(|| save_and_load(black_box(CONFIG_PLUS_BORSH)))
+    });
+
+    #[cfg(feature = "msgpack")]
+    group.bench_function("msgpack save & load", |b| {
+        b.iter(|| save_and_load(black_box(CONFIG_MSGPACK)))
+    });
+
+    #[cfg(feature = "cbor")]
+    group.bench_function("cbor save & load", |b| {
+        b.iter(|| save_and_load(black_box(CONFIG_CBOR)))
+    });
+
+    #[cfg(feature = "postcard")]
+    group.bench_function("postcard save & load", |b| {
+        b.iter(|| save_and_load(black_box(CONFIG_POSTCARD)))
+    });
+
     group.bench_function("bincode2 big save & load", |b| {
         b.iter(|| big_save_and_load(black_box(BIG)))
     });
@@ -130,6 +169,25 @@ fn bench_bincode2_json(c: &mut Criterion) {
         b.iter(|| borsh_big_save_and_load(black_box(BIG_BORSH)))
     });
 
+    group.bench_function("storage-plus borsh big save & load", |b| {
+        b.iter(|| big_save_and_load(black_box(BIG_PLUS_BORSH)))
+    });
+
+    #[cfg(feature = "msgpack")]
+    group.bench_function("msgpack big save & load", |b| {
+        b.iter(|| big_save_and_load(black_box(BIG_MSGPACK)))
+    });
+
+    #[cfg(feature = "cbor")]
+    group.bench_function("cbor big save & load", |b| {
+        b.iter(|| big_save_and_load(black_box(BIG_CBOR)))
+    });
+
+    #[cfg(feature = "postcard")]
+    group.bench_function("postcard big save & load", |b| {
+        b.iter(|| big_save_and_load(black_box(BIG_POSTCARD)))
+    });
+
     group.finish();
 }
 
@@ -0,0 +1,225 @@
+use cosmwasm_std::{
+    Addr, CustomQuery, OverflowError, OverflowOperation, QuerierWrapper, StdError, StdResult,
+    Storage, Uint128, Uint256, Uint64,
+};
+
+use crate::helpers::query_deserialize;
+use crate::keys::PrimaryKey;
+use crate::path::Path;
+use crate::{Json, Map, Serde};
+
+/// Numbers that can be kept as running sums by an [`Accumulator`].
+pub trait Summable: Copy + PartialEq {
+    fn zero() -> Self;
+    fn checked_add(self, other: Self) -> Result<Self, OverflowError>;
+    fn checked_sub(self, other: Self) -> Result<Self, OverflowError>;
+}
+
+impl Summable for u64 {
+    fn zero() -> Self {
+        0
+    }
+
+    fn checked_add(self, other: Self) -> Result<Self, OverflowError> {
+        u64::checked_add(self, other)
+            .ok_or_else(|| OverflowError::new(OverflowOperation::Add, self, other))
+    }
+
+    fn checked_sub(self, other: Self) -> Result<Self, OverflowError> {
+        u64::checked_sub(self, other)
+            .ok_or_else(|| OverflowError::new(OverflowOperation::Sub, self, other))
+    }
+}
+
+macro_rules! impl_summable {
+    ($($t:ty),*) => {
+        $(impl Summable for $t {
+            fn zero() -> Self {
+                <$t>::zero()
+            }
+
+            fn checked_add(self, other: Self) -> Result<Self, OverflowError> {
+                <$t>::checked_add(self, other)
+            }
+
+            fn checked_sub(self, other: Self) -> Result<Self, OverflowError> {
+                <$t>::checked_sub(self, other)
+            }
+        })*
+    };
+}
+
+impl_summable!(Uint64, Uint128, Uint256);
+
+/// Accumulator keeps a running sum for every key, such as the balance of every account.
+///
+/// Sums are stored like a `Map<K, N>` under the given namespace. A key that was never added to
+/// reads as zero, and sums that drop back to zero are removed from storage.
+pub struct Accumulator<'a, K, N = Uint128, Ser = Json> {
+    map: Map<'a, K, N, Ser>,
+}
+
+impl<'a, K, N, Ser> Accumulator<'a, K, N, Ser> {
+    pub const fn new(namespace: &'a str) -> Self {
+        Accumulator {
+            map: Map::new(namespace),
+        }
+    }
+
+    pub fn namespace(&self) -> &'a [u8] {
+        self.map.namespace()
+    }
+}
+
+impl<'a, K, N, Ser> Accumulator<'a, K, N, Ser>
+where
+    K: PrimaryKey<'a>,
+    N: Summable,
+    Ser: Serde<N>,
+{
+    /// get returns the sum stored for the key, or zero if there is none
+    pub fn get(&self, store: &dyn Storage, k: K) -> StdResult<N> {
+        Ok(self.map.may_load(store, k)?.unwrap_or_else(N::zero))
+    }
+
+    /// add adds `amount` to the sum of the key and returns the new sum.
+    /// Returns an error if the sum would overflow.
+    pub fn add(&self, store: &mut dyn Storage, k: K, amount: N) -> StdResult<N> {
+        let path = self.map.key(k);
+        let current = path.may_load(store)?.unwrap_or_else(N::zero);
+        let sum = current.checked_add(amount).map_err(StdError::overflow)?;
+        self.write(store, &path, sum)
+    }
+
+    /// sub subtracts `amount` from the sum of the key and returns the new sum.
+    /// Returns an error if the sum would drop below zero.
+    pub fn sub(&self, store: &mut dyn Storage, k: K, amount: N) -> StdResult<N> {
+        let path = self.map.key(k);
+        let current = path.may_load(store)?.unwrap_or_else(N::zero);
+        let sum = current.checked_sub(amount).map_err(StdError::overflow)?;
+        self.write(store, &path, sum)
+    }
+
+    /// set replaces the sum of the key
+    pub fn set(&self, store: &mut dyn Storage, k: K, value: N) -> StdResult<()> {
+        self.write(store, &self.map.key(k), value)?;
+        Ok(())
+    }
+
+    /// remove resets the sum of the key to zero
+    pub fn remove(&self, store: &mut dyn Storage, k: K) {
+        self.map.remove(store, k);
+    }
+
+    /// If you import the proper Accumulator from the remote contract, this will let you read
+    /// the sum of a key from a remote contract in a type-safe way.
+    pub fn query<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+        k: K,
+    ) -> StdResult<N> {
+        Ok(query_deserialize::<N, Ser, Q>(
+            querier,
+            remote_contract,
+            remote_contract_code_hash,
+            &self.map.key(k).storage_key,
+        )?
+        .unwrap_or_else(N::zero))
+    }
+
+    fn write(&self, store: &mut dyn Storage, path: &Path<N, Ser>, sum: N) -> StdResult<N> {
+        if sum == N::zero() {
+            path.remove(store);
+        } else {
+            path.save(store, &sum)?;
+        }
+        Ok(sum)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use cosmwasm_std::testing::MockStorage;
+
+    use crate::Bincode2;
+
+    const BALANCES: Accumulator<&Addr> = Accumulator::new("balances");
+
+    #[test]
+    fn add_and_sub() {
+        let mut store = MockStorage::new();
+        let alice = Addr::unchecked("alice");
+        let bob = Addr::unchecked("bob");
+
+        assert_eq!(Uint128::zero(), BALANCES.get(&store, &alice).unwrap());
+
+        assert_eq!(
+            Uint128::new(100),
+            BALANCES.add(&mut store, &alice, Uint128::new(100)).unwrap()
+        );
+        assert_eq!(
+            Uint128::new(150),
+            BALANCES.add(&mut store, &alice, Uint128::new(50)).unwrap()
+        );
+        assert_eq!(
+            Uint128::new(30),
+            BALANCES.sub(&mut store, &alice, Uint128::new(120)).unwrap()
+        );
+        assert_eq!(Uint128::new(30), BALANCES.get(&store, &alice).unwrap());
+        assert_eq!(Uint128::zero(), BALANCES.get(&store, &bob).unwrap());
+
+        // it is stored as a plain map
+        let map: Map<&Addr, Uint128> = Map::new("balances");
+        assert_eq!(Uint128::new(30), map.load(&store, &alice).unwrap());
+    }
+
+    #[test]
+    fn underflow_and_overflow_are_rejected() {
+        let mut store = MockStorage::new();
+        let alice = Addr::unchecked("alice");
+        BALANCES.add(&mut store, &alice, Uint128::new(10)).unwrap();
+
+        let err = BALANCES
+            .sub(&mut store, &alice, Uint128::new(11))
+            .unwrap_err();
+        assert!(matches!(err, StdError::Overflow { .. }));
+
+        let err = BALANCES.add(&mut store, &alice, Uint128::MAX).unwrap_err();
+        assert!(matches!(err, StdError::Overflow { .. }));
+
+        // failed updates leave the sum untouched
+        assert_eq!(Uint128::new(10), BALANCES.get(&store, &alice).unwrap());
+    }
+
+    #[test]
+    fn zero_sums_are_removed() {
+        let mut store = MockStorage::new();
+        let alice = Addr::unchecked("alice");
+        let map: Map<&Addr, Uint128> = Map::new("balances");
+
+        BALANCES.add(&mut store, &alice, Uint128::new(10)).unwrap();
+        BALANCES.sub(&mut store, &alice, Uint128::new(10)).unwrap();
+        assert!(!map.has(&store, &alice));
+
+        BALANCES.set(&mut store, &alice, Uint128::new(5)).unwrap();
+        assert!(map.has(&store, &alice));
+        BALANCES.remove(&mut store, &alice);
+        assert_eq!(Uint128::zero(), BALANCES.get(&store, &alice).unwrap());
+    }
+
+    #[test]
+    fn uint256_sums() {
+        let mut store = MockStorage::new();
+        let totals: Accumulator<(&str, u64), Uint256, Bincode2> = Accumulator::new("totals");
+
+        let big = Uint256::from(u128::MAX);
+        totals.add(&mut store, ("pool", 1), big).unwrap();
+        let sum = totals.add(&mut store, ("pool", 1), big).unwrap();
+        assert_eq!(big + big, sum);
+        assert_eq!(Uint256::zero(), totals.get(&store, ("pool", 2)).unwrap());
+    }
+}
@@ -6,38 +6,44 @@
 use std::any::type_name;
 use std::convert::TryInto;
 use std::marker::PhantomData;
-use std::sync::Mutex;
-
-use serde::{de::DeserializeOwned, Serialize};
 
+use crate::helpers::{encode_length, nested_namespaces_with_key, query_deserialize, query_u32};
+use crate::keys::{Key, PrimaryKey};
+use crate::paging::{Order, Page, PositionedItems};
 use crate::{Json, Serde};
 
-use cosmwasm_std::{StdError, StdResult, Storage};
+use cosmwasm_std::{Addr, CustomQuery, QuerierWrapper, StdError, StdResult, Storage};
 
-const LEN_KEY: &[u8] = b"len";
+pub(crate) const LEN_KEY: &[u8] = b"len";
 
 pub struct AppendStore<'a, T, Ser = Json>
 where
-    T: Serialize + DeserializeOwned,
-    Ser: Serde,
+    Ser: Serde<T>,
 {
     /// prefix of the newly constructed Storage
     namespace: &'a [u8],
     /// needed if any suffixes were added to the original namespace.
     /// therefore it is not necessarily same as the namespace.
     prefix: Option<Vec<u8>>,
-    length: Mutex<Option<u32>>,
     item_type: PhantomData<T>,
     serialization_type: PhantomData<Ser>,
 }
 
-impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> AppendStore<'a, T, Ser> {
+impl<'a, T, Ser: Serde<T>> AppendStore<'a, T, Ser> {
     /// constructor
     pub const fn new(prefix: &'a str) -> Self {
         Self {
             namespace: prefix.as_bytes(),
             prefix: None,
-            length: Mutex::new(None),
+            item_type: PhantomData,
+            serialization_type: PhantomData,
+        }
+    }
+    /// constructor for a store whose keys all start with `prefix`, which already includes the namespace
+    pub(crate) fn with_prefix(namespace: &'a [u8], prefix: Vec<u8>) -> Self {
+        Self {
+            namespace,
+            prefix: Some(prefix),
             item_type: PhantomData,
             serialization_type: PhantomData,
         }
@@ -53,33 +59,37 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> AppendStore<'a, T, Ser> {
         Self {
             namespace: self.namespace,
             prefix: Some(prefix),
-            length: Mutex::new(None),
             item_type: self.item_type,
             serialization_type: self.serialization_type,
         }
     }
+    /// Like `add_suffix`, but the suffix is length-prefixed the same way as the keys of an
+    /// `AppendStoreMap`, so different suffixes can't end up in the same store
+    pub(crate) fn add_length_prefixed_suffix(&self, suffix: &str) -> Self {
+        let suffix = suffix.as_bytes();
+        let prefix = if let Some(prefix) = &self.prefix {
+            [prefix.as_slice(), &encode_length(suffix), suffix].concat()
+        } else {
+            nested_namespaces_with_key(&[self.namespace], &[Key::Ref(suffix)], b"")
+        };
+        Self::with_prefix(self.namespace, prefix)
+    }
 }
 
-impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> AppendStore<'a, T, Ser> {
-    /// gets the length from storage, and otherwise sets it to 0
+impl<'a, T, Ser: Serde<T>> AppendStore<'a, T, Ser> {
+    /// gets the length from storage, and otherwise returns 0
+    ///
+    /// The length is not cached, so the same store can safely be used with different storages.
     pub fn get_len(&self, storage: &dyn Storage) -> StdResult<u32> {
-        let mut may_len = self.length.lock().unwrap();
-        if let Some(len) = *may_len {
-            Ok(len)
+        let len_key = [self.as_slice(), LEN_KEY].concat();
+        if let Some(len_vec) = storage.get(&len_key) {
+            let len_bytes = len_vec
+                .as_slice()
+                .try_into()
+                .map_err(|err| StdError::parse_err("u32", err))?;
+            Ok(u32::from_be_bytes(len_bytes))
         } else {
-            let len_key = [self.as_slice(), LEN_KEY].concat();
-            if let Some(len_vec) = storage.get(&len_key) {
-                let len_bytes = len_vec
-                    .as_slice()
-                    .try_into()
-                    .map_err(|err| StdError::parse_err("u32", err))?;
-                let len = u32::from_be_bytes(len_bytes);
-                *may_len = Some(len);
-                Ok(len)
-            } else {
-                *may_len = Some(0);
-                Ok(0)
-            }
+            Ok(0)
         }
     }
     /// checks if the collection has any elements
@@ -95,22 +105,41 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> AppendStore<'a, T, Ser> {
         self.get_at_unchecked(storage, pos)
     }
     /// tries to get the element at pos
-    fn get_at_unchecked(&self, storage: &dyn Storage, pos: u32) -> StdResult<T> {
+    pub(crate) fn get_at_unchecked(&self, storage: &dyn Storage, pos: u32) -> StdResult<T> {
         let key = pos.to_be_bytes();
         self.load_impl(storage, &key)
     }
 
     /// Set the length of the collection
-    fn set_len(&self, storage: &mut dyn Storage, len: u32) {
+    pub(crate) fn set_len(&self, storage: &mut dyn Storage, len: u32) {
         let len_key = [self.as_slice(), LEN_KEY].concat();
         storage.set(&len_key, &len.to_be_bytes());
-
-        let mut may_len = self.length.lock().unwrap();
-        *may_len = Some(len);
     }
-    /// Clear the collection
-    pub fn clear(&self, storage: &mut dyn Storage) {
-        self.set_len(storage, 0);
+    /// Clear the collection and remove its elements from storage.
+    ///
+    /// The cost grows with the length of the collection, so use `clear_full` with a limit to
+    /// clear a large one over several transactions.
+    pub fn clear(&self, storage: &mut dyn Storage) -> StdResult<()> {
+        self.clear_full(storage, None)?;
+        Ok(())
+    }
+    /// Clear the collection and remove its elements from storage.
+    ///
+    /// At most `limit` elements are removed per call, starting from the back, so a large
+    /// collection can be cleared over several transactions. Returns true once it is empty,
+    /// at which point the length is removed from storage as well.
+    pub fn clear_full(&self, storage: &mut dyn Storage, limit: Option<u32>) -> StdResult<bool> {
+        let len = self.get_len(storage)?;
+        let new_len = len.saturating_sub(limit.unwrap_or(u32::MAX));
+        for pos in new_len..len {
+            self.remove_at_unchecked(storage, pos);
+        }
+        if new_len == 0 {
+            storage.remove(&[self.as_slice(), LEN_KEY].concat());
+        } else {
+            self.set_len(storage, new_len);
+        }
+        Ok(new_len == 0)
     }
     /// Replaces data at a position within bounds
     pub fn set_at(&self, storage: &mut dyn Storage, pos: u32, item: &T) -> StdResult<()> {
@@ -121,9 +150,18 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> AppendStore<'a, T, Ser> {
         self.set_at_unchecked(storage, pos, item)
     }
     /// Sets data at a given index
-    fn set_at_unchecked(&self, storage: &mut dyn Storage, pos: u32, item: &T) -> StdResult<()> {
+    pub(crate) fn set_at_unchecked(
+        &self,
+        storage: &mut dyn Storage,
+        pos: u32,
+        item: &T,
+    ) -> StdResult<()> {
         self.save_impl(storage, &pos.to_be_bytes(), item)
     }
+    /// Removes the data at a given index from storage
+    pub(crate) fn remove_at_unchecked(&self, storage: &mut dyn Storage, pos: u32) {
+        storage.remove(&[self.as_slice(), &pos.to_be_bytes()].concat());
+    }
     /// Pushes an item to AppendStorage
     pub fn push(&self, storage: &mut dyn Storage, item: &T) -> StdResult<()> {
         let len = self.get_len(storage)?;
@@ -131,10 +169,41 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> AppendStore<'a, T, Ser> {
         self.set_len(storage, len + 1);
         Ok(())
     }
+    /// Pushes every item of `items` to the end of the AppendStore, writing the length only once
+    pub fn extend<'i, I>(&self, storage: &mut dyn Storage, items: I) -> StdResult<()>
+    where
+        I: IntoIterator<Item = &'i T>,
+        T: 'i,
+    {
+        let mut len = self.get_len(storage)?;
+        let start = len;
+        for item in items {
+            self.set_at_unchecked(storage, len, item)?;
+            len += 1;
+        }
+        if len != start {
+            self.set_len(storage, len);
+        }
+        Ok(())
+    }
+    /// Shortens the collection to `len` elements, doing nothing if it is already shorter.
+    ///
+    /// The dropped elements are removed from storage, so the cost grows with their number.
+    pub fn truncate(&self, storage: &mut dyn Storage, len: u32) -> StdResult<()> {
+        let old_len = self.get_len(storage)?;
+        if len < old_len {
+            for pos in len..old_len {
+                self.remove_at_unchecked(storage, pos);
+            }
+            self.set_len(storage, len);
+        }
+        Ok(())
+    }
     /// Pops an item from AppendStore
     pub fn pop(&self, storage: &mut dyn Storage) -> StdResult<T> {
         if let Some(len) = self.get_len(storage)?.checked_sub(1) {
             let item = self.get_at_unchecked(storage, len);
+            self.remove_at_unchecked(storage, len);
             self.set_len(storage, len);
             item
         } else {
@@ -161,9 +230,59 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> AppendStore<'a, T, Ser> {
             let element_to_shift = self.get_at_unchecked(storage, i + 1)?;
             self.set_at_unchecked(storage, i, &element_to_shift)?;
         }
+        self.remove_at_unchecked(storage, len - 1);
         self.set_len(storage, len - 1);
         item
     }
+    /// Remove an element from the collection at the specified position, replacing it with the
+    /// last element.
+    ///
+    /// This has a constant cost, but does not preserve the order of the collection.
+    pub fn swap_remove(&self, storage: &mut dyn Storage, pos: u32) -> StdResult<T> {
+        let len = self.get_len(storage)?;
+
+        if pos >= len {
+            return Err(StdError::generic_err("AppendStore access out of bounds"));
+        }
+        let item = self.get_at_unchecked(storage, pos)?;
+
+        let last = len - 1;
+        if pos != last {
+            let last_item = self.get_at_unchecked(storage, last)?;
+            self.set_at_unchecked(storage, pos, &last_item)?;
+        }
+        self.remove_at_unchecked(storage, last);
+        self.set_len(storage, last);
+        Ok(item)
+    }
+    /// Keeps only the elements for which `f` returns true, preserving their order.
+    ///
+    /// Every element is read once, and only the elements that have to move are written again.
+    /// The positions left over at the end are removed from storage.
+    pub fn retain<F>(&self, storage: &mut dyn Storage, mut f: F) -> StdResult<()>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let len = self.get_len(storage)?;
+
+        let mut kept = 0;
+        for pos in 0..len {
+            let item = self.get_at_unchecked(storage, pos)?;
+            if f(&item) {
+                if kept != pos {
+                    self.set_at_unchecked(storage, kept, &item)?;
+                }
+                kept += 1;
+            }
+        }
+        if kept != len {
+            for pos in kept..len {
+                self.remove_at_unchecked(storage, pos);
+            }
+            self.set_len(storage, kept);
+        }
+        Ok(())
+    }
     /// Returns a readonly iterator
     pub fn iter(&self, storage: &'a dyn Storage) -> StdResult<AppendStoreIter<T, Ser>> {
         let len = self.get_len(storage)?;
@@ -177,22 +296,128 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> AppendStore<'a, T, Ser> {
             .take(size as usize)
             .collect()
     }
+    /// does paging with the given parameters, starting from the last element
+    pub fn paging_reverse(
+        &self,
+        storage: &dyn Storage,
+        start_page: u32,
+        size: u32,
+    ) -> StdResult<Vec<T>> {
+        self.iter(storage)?
+            .rev()
+            .skip((start_page as usize) * (size as usize))
+            .take(size as usize)
+            .collect()
+    }
+    /// does paging in the given order, and also returns the length of the collection
+    pub fn page(
+        &self,
+        storage: &dyn Storage,
+        start_page: u32,
+        size: u32,
+        order: Order,
+    ) -> StdResult<Page<T>> {
+        let items = match order {
+            Order::Ascending => self.paging(storage, start_page, size)?,
+            Order::Descending => self.paging_reverse(storage, start_page, size)?,
+        };
+        Ok(Page {
+            items,
+            total: self.get_len(storage)?,
+        })
+    }
+    /// Returns the elements at positions `start..end` along with their positions.
+    ///
+    /// Missing bounds default to the ends of the collection, and `end` is capped at its length,
+    /// so the position of the last element returned can be used as a cursor for the next call.
+    pub fn range<'c>(
+        &'c self,
+        storage: &'c dyn Storage,
+        start: Option<u32>,
+        end: Option<u32>,
+        order: Order,
+    ) -> StdResult<PositionedItems<'c, T>>
+    where
+        T: 'c,
+        Ser: 'c,
+    {
+        let len = self.get_len(storage)?;
+        let end = end.map_or(len, |end| end.min(len));
+        let start = start.unwrap_or(0).min(end);
+        let iter = AppendStoreIter::new(self, storage, start, end);
+        let with_pos = |(pos, item): (u32, StdResult<T>)| item.map(|item| (pos, item));
+        let range: PositionedItems<T> = match order {
+            Order::Ascending => Box::new((start..end).zip(iter).map(with_pos)),
+            Order::Descending => Box::new((start..end).rev().zip(iter.rev()).map(with_pos)),
+        };
+        Ok(range)
+    }
 }
 
-impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> Clone for AppendStore<'a, T, Ser> {
+impl<'a, T, Ser: Serde<T>> AppendStore<'a, T, Ser> {
+    /// If you import the proper AppendStore from the remote contract, this will let you read its
+    /// length from a remote contract in a type-safe way using WasmQuery::RawQuery
+    pub fn query_len<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+    ) -> StdResult<u32> {
+        let len_key = [self.as_slice(), LEN_KEY].concat();
+        query_u32(
+            querier,
+            remote_contract,
+            remote_contract_code_hash,
+            &len_key,
+        )
+    }
+    /// Reads the element at pos from a remote contract, decoding it with `Ser`.
+    ///
+    /// This sends two queries, since the length has to be read to check the bounds.
+    pub fn query_at<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+        pos: u32,
+    ) -> StdResult<T> {
+        let len = self.query_len(
+            querier,
+            remote_contract.clone(),
+            remote_contract_code_hash.clone(),
+        )?;
+        if pos >= len {
+            return Err(StdError::generic_err("AppendStore access out of bounds"));
+        }
+        self.query_at_unchecked(querier, remote_contract, remote_contract_code_hash, pos)
+    }
+    /// Reads the element at pos from a remote contract without checking the bounds
+    pub(crate) fn query_at_unchecked<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+        pos: u32,
+    ) -> StdResult<T> {
+        let key = [self.as_slice(), &pos.to_be_bytes()].concat();
+        query_deserialize::<T, Ser, Q>(querier, remote_contract, remote_contract_code_hash, &key)?
+            .ok_or_else(|| StdError::not_found(type_name::<T>()))
+    }
+}
+
+impl<'a, T, Ser: Serde<T>> Clone for AppendStore<'a, T, Ser> {
     fn clone(&self) -> Self {
         Self {
             namespace: self.namespace,
             prefix: self.prefix.clone(),
-            length: Mutex::new(None),
             item_type: self.item_type,
             serialization_type: self.serialization_type,
         }
     }
 }
 
-impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> AppendStore<'a, T, Ser> {
-    fn as_slice(&self) -> &[u8] {
+impl<'a, T, Ser: Serde<T>> AppendStore<'a, T, Ser> {
+    pub(crate) fn as_slice(&self) -> &[u8] {
         if let Some(prefix) = &self.prefix {
             prefix
         } else {
@@ -230,11 +455,51 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> AppendStore<'a, T, Ser> {
     }
 }
 
+/// A collection of an append stores, one for each key.
+///
+/// The prefix of every store is derived from the namespace and the key using the same
+/// length-prefixed scheme as `Map`, so stores for different keys can never overlap, and any
+/// `PrimaryKey` (addresses, integers, tuples...) can be used without converting it to a string.
+#[derive(Debug, Clone)]
+pub struct AppendStoreMap<'a, K, T, Ser = Json> {
+    namespace: &'a [u8],
+    // see https://doc.rust-lang.org/std/marker/struct.PhantomData.html#unused-type-parameters for why this is needed
+    key_type: PhantomData<K>,
+    data_type: PhantomData<T>,
+    serialization_type: PhantomData<*const Ser>,
+}
+
+impl<'a, K, T, Ser> AppendStoreMap<'a, K, T, Ser> {
+    pub const fn new(namespace: &'a str) -> Self {
+        AppendStoreMap {
+            namespace: namespace.as_bytes(),
+            key_type: PhantomData,
+            data_type: PhantomData,
+            serialization_type: PhantomData,
+        }
+    }
+
+    pub fn namespace(&self) -> &'a [u8] {
+        self.namespace
+    }
+}
+
+impl<'a, K, T, Ser> AppendStoreMap<'a, K, T, Ser>
+where
+    K: PrimaryKey<'a>,
+    Ser: Serde<T>,
+{
+    /// Returns the store that belongs to the given key
+    pub fn key(&self, k: K) -> AppendStore<'a, T, Ser> {
+        let prefix = nested_namespaces_with_key(&[self.namespace], &k.key(), b"");
+        AppendStore::with_prefix(self.namespace, prefix)
+    }
+}
+
 /// An iterator over the contents of the append store.
 pub struct AppendStoreIter<'a, T, Ser>
 where
-    T: Serialize + DeserializeOwned,
-    Ser: Serde,
+    Ser: Serde<T>,
 {
     append_store: &'a AppendStore<'a, T, Ser>,
     storage: &'a dyn Storage,
@@ -244,8 +509,7 @@ where
 
 impl<'a, T, Ser> AppendStoreIter<'a, T, Ser>
 where
-    T: Serialize + DeserializeOwned,
-    Ser: Serde,
+    Ser: Serde<T>,
 {
     /// constructor
     pub fn new(
@@ -265,8 +529,7 @@ where
 
 impl<'a, T, Ser> Iterator for AppendStoreIter<'a, T, Ser>
 where
-    T: Serialize + DeserializeOwned,
-    Ser: Serde,
+    Ser: Serde<T>,
 {
     type Item = StdResult<T>;
 
@@ -274,7 +537,7 @@ where
         if self.start >= self.end {
             return None;
         }
-        let item = self.append_store.get_at(self.storage, self.start);
+        let item = self.append_store.get_at_unchecked(self.storage, self.start);
         self.start += 1;
         Some(item)
     }
@@ -299,15 +562,14 @@ where
 
 impl<'a, T, Ser> DoubleEndedIterator for AppendStoreIter<'a, T, Ser>
 where
-    T: Serialize + DeserializeOwned,
-    Ser: Serde,
+    Ser: Serde<T>,
 {
     fn next_back(&mut self) -> Option<Self::Item> {
         if self.start >= self.end {
             return None;
         }
         self.end -= 1;
-        let item = self.append_store.get_at(self.storage, self.end);
+        let item = self.append_store.get_at_unchecked(self.storage, self.end);
         Some(item)
     }
 
@@ -324,19 +586,18 @@ where
 }
 
 // This enables writing `append_store.iter().skip(n).rev()`
-impl<'a, T, Ser> ExactSizeIterator for AppendStoreIter<'a, T, Ser>
-where
-    T: Serialize + DeserializeOwned,
-    Ser: Serde,
-{
-}
+impl<'a, T, Ser> ExactSizeIterator for AppendStoreIter<'a, T, Ser> where Ser: Serde<T> {}
 
 #[cfg(test)]
 mod tests {
     use cosmwasm_std::testing::MockStorage;
+    use cosmwasm_std::Addr;
+    use cosmwasm_std::Empty;
+
+    use crate::helpers::mock_remote_querier;
 
     use super::*;
-    use crate::Json;
+    use crate::{Borsh, Json};
 
     #[test]
     fn test_push_pop() -> StdResult<()> {
@@ -361,29 +622,23 @@ mod tests {
         let mut storage = MockStorage::new();
         let append_store: AppendStore<i32> = AppendStore::new("test");
 
-        assert!(append_store.length.lock().unwrap().eq(&None));
         assert_eq!(append_store.get_len(&storage)?, 0);
-        assert!(append_store.length.lock().unwrap().eq(&Some(0)));
 
         append_store.push(&mut storage, &1234)?;
         append_store.push(&mut storage, &2143)?;
         append_store.push(&mut storage, &3412)?;
         append_store.push(&mut storage, &4321)?;
-        assert!(append_store.length.lock().unwrap().eq(&Some(4)));
         assert_eq!(append_store.get_len(&storage)?, 4);
 
         assert_eq!(append_store.pop(&mut storage), Ok(4321));
         assert_eq!(append_store.pop(&mut storage), Ok(3412));
-        assert!(append_store.length.lock().unwrap().eq(&Some(2)));
         assert_eq!(append_store.get_len(&storage)?, 2);
 
         assert_eq!(append_store.pop(&mut storage), Ok(2143));
         assert_eq!(append_store.pop(&mut storage), Ok(1234));
-        assert!(append_store.length.lock().unwrap().eq(&Some(0)));
         assert_eq!(append_store.get_len(&storage)?, 0);
 
         assert!(append_store.pop(&mut storage).is_err());
-        assert!(append_store.length.lock().unwrap().eq(&Some(0)));
         assert_eq!(append_store.get_len(&storage)?, 0);
 
         Ok(())
@@ -600,6 +855,16 @@ mod tests {
         let bytes = storage.get(&key);
         assert_eq!(bytes, Some(b"1234".to_vec()));
 
+        // Check that overriding the serializer with Borsh works
+        let mut storage = MockStorage::new();
+        let borsh_append_store: AppendStore<i32, Borsh> = AppendStore::new("test3");
+        borsh_append_store.push(&mut storage, &1234)?;
+
+        let key = [borsh_append_store.as_slice(), &0_u32.to_be_bytes()].concat();
+        let bytes = storage.get(&key);
+        assert_eq!(bytes, Some(1234_i32.to_le_bytes().to_vec()));
+        assert_eq!(borsh_append_store.get_at(&storage, 0)?, 1234);
+
         Ok(())
     }
 
@@ -691,4 +956,406 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_paging_reverse() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let append_store: AppendStore<u32> = AppendStore::new("test");
+
+        let page_size: u32 = 5;
+        let total_items: u32 = 50;
+
+        for i in 0..total_items {
+            append_store.push(&mut storage, &i)?;
+        }
+
+        for start_page in 0..(total_items / page_size) {
+            let values = append_store.paging_reverse(&storage, start_page, page_size)?;
+            assert_eq!(values.len(), page_size as usize);
+
+            for (index, value) in values.iter().enumerate() {
+                assert_eq!(
+                    value,
+                    &(total_items - 1 - page_size * start_page - index as u32)
+                )
+            }
+        }
+
+        // pages past the end are empty
+        assert!(append_store
+            .paging_reverse(&storage, total_items / page_size, page_size)?
+            .is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_page() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let append_store: AppendStore<u32> = AppendStore::new("test");
+
+        for i in 0..7 {
+            append_store.push(&mut storage, &i)?;
+        }
+
+        let page = append_store.page(&storage, 1, 3, Order::Ascending)?;
+        assert_eq!(
+            page,
+            Page {
+                items: vec![3, 4, 5],
+                total: 7
+            }
+        );
+
+        let page = append_store.page(&storage, 2, 3, Order::Descending)?;
+        assert_eq!(
+            page,
+            Page {
+                items: vec![0],
+                total: 7
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_range() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let append_store: AppendStore<u32> = AppendStore::new("test");
+
+        assert_eq!(
+            append_store
+                .range(&storage, None, None, Order::Ascending)?
+                .count(),
+            0
+        );
+
+        for i in 0..6 {
+            append_store.push(&mut storage, &(i * 10))?;
+        }
+
+        let all: Vec<_> = append_store
+            .range(&storage, None, None, Order::Ascending)?
+            .collect::<StdResult<_>>()?;
+        assert_eq!(
+            all,
+            vec![(0, 0), (1, 10), (2, 20), (3, 30), (4, 40), (5, 50)]
+        );
+
+        let some: Vec<_> = append_store
+            .range(&storage, Some(2), Some(4), Order::Ascending)?
+            .collect::<StdResult<_>>()?;
+        assert_eq!(some, vec![(2, 20), (3, 30)]);
+
+        let some: Vec<_> = append_store
+            .range(&storage, Some(2), Some(4), Order::Descending)?
+            .collect::<StdResult<_>>()?;
+        assert_eq!(some, vec![(3, 30), (2, 20)]);
+
+        // use the last position as a cursor, newest first
+        let first: Vec<_> = append_store
+            .range(&storage, None, None, Order::Descending)?
+            .take(4)
+            .collect::<StdResult<_>>()?;
+        assert_eq!(first, vec![(5, 50), (4, 40), (3, 30), (2, 20)]);
+        let next: Vec<_> = append_store
+            .range(&storage, None, Some(first[3].0), Order::Descending)?
+            .take(4)
+            .collect::<StdResult<_>>()?;
+        assert_eq!(next, vec![(1, 10), (0, 0)]);
+
+        // bounds are capped at the length
+        let capped: Vec<_> = append_store
+            .range(&storage, Some(4), Some(100), Order::Ascending)?
+            .collect::<StdResult<_>>()?;
+        assert_eq!(capped, vec![(4, 40), (5, 50)]);
+        assert_eq!(
+            append_store
+                .range(&storage, Some(8), None, Order::Ascending)?
+                .count(),
+            0
+        );
+        assert_eq!(
+            append_store
+                .range(&storage, Some(3), Some(1), Order::Descending)?
+                .count(),
+            0
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_extend() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let append_store: AppendStore<i32> = AppendStore::new("test");
+        append_store.push(&mut storage, &1)?;
+
+        append_store.extend(&mut storage, &[2, 3, 4])?;
+        assert_eq!(append_store.get_len(&storage)?, 4);
+
+        append_store.extend(&mut storage, [5, 6].iter())?;
+        append_store.extend(&mut storage, &[])?;
+        let items: StdResult<Vec<_>> = append_store.iter(&storage)?.collect();
+        assert_eq!(items?, vec![1, 2, 3, 4, 5, 6]);
+
+        // the length is read back from storage correctly
+        let reloaded: AppendStore<i32> = AppendStore::new("test");
+        assert_eq!(reloaded.get_len(&storage)?, 6);
+        Ok(())
+    }
+
+    #[test]
+    fn test_truncate() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let append_store: AppendStore<i32> = AppendStore::new("test");
+        append_store.extend(&mut storage, &[1, 2, 3, 4, 5])?;
+
+        append_store.truncate(&mut storage, 10)?;
+        assert_eq!(append_store.get_len(&storage)?, 5);
+
+        append_store.truncate(&mut storage, 2)?;
+        assert_eq!(append_store.get_len(&storage)?, 2);
+        // the dropped elements are gone from storage
+        let dropped = [append_store.as_slice(), &2u32.to_be_bytes()].concat();
+        assert_eq!(storage.get(&dropped), None);
+        assert_eq!(append_store.pop(&mut storage), Ok(2));
+
+        append_store.push(&mut storage, &7)?;
+        let items: StdResult<Vec<_>> = append_store.iter(&storage)?.collect();
+        assert_eq!(items?, vec![1, 7]);
+
+        append_store.truncate(&mut storage, 0)?;
+        assert!(append_store.is_empty(&storage)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_swap_remove() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let append_store: AppendStore<i32> = AppendStore::new("test");
+        append_store.extend(&mut storage, &[1, 2, 3, 4])?;
+
+        assert_eq!(append_store.swap_remove(&mut storage, 1), Ok(2));
+        let items: StdResult<Vec<_>> = append_store.iter(&storage)?.collect();
+        assert_eq!(items?, vec![1, 4, 3]);
+
+        // the old last position is gone from storage
+        let element = |pos: u32| [b"test".as_slice(), &pos.to_be_bytes()].concat();
+        assert!(storage.get(&element(3)).is_none());
+
+        // removing the last element just shortens the collection
+        assert_eq!(append_store.swap_remove(&mut storage, 2), Ok(3));
+        assert!(storage.get(&element(2)).is_none());
+        let items: StdResult<Vec<_>> = append_store.iter(&storage)?.collect();
+        assert_eq!(items?, vec![1, 4]);
+
+        assert!(append_store.swap_remove(&mut storage, 2).is_err());
+        assert_eq!(append_store.swap_remove(&mut storage, 0), Ok(1));
+        assert_eq!(append_store.swap_remove(&mut storage, 0), Ok(4));
+        assert!(append_store.swap_remove(&mut storage, 0).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_retain() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let append_store: AppendStore<i32> = AppendStore::new("test");
+        append_store.extend(&mut storage, &[1, 2, 3, 4, 5, 6, 7])?;
+
+        append_store.retain(&mut storage, |item| item % 2 == 1)?;
+        let items: StdResult<Vec<_>> = append_store.iter(&storage)?.collect();
+        assert_eq!(items?, vec![1, 3, 5, 7]);
+        // the positions past the kept elements are gone from storage
+        let element = |pos: u32| [b"test".as_slice(), &pos.to_be_bytes()].concat();
+        for pos in 4..7 {
+            assert!(storage.get(&element(pos)).is_none());
+        }
+
+        append_store.retain(&mut storage, |_| true)?;
+        assert_eq!(append_store.get_len(&storage)?, 4);
+
+        append_store.retain(&mut storage, |item| *item > 100)?;
+        assert!(append_store.is_empty(&storage)?);
+        assert!(storage.get(&element(0)).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_store_map() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let histories: AppendStoreMap<&Addr, i32> = AppendStoreMap::new("history");
+        let alice = Addr::unchecked("alice");
+        let bob = Addr::unchecked("bob");
+
+        histories.key(&alice).push(&mut storage, &1)?;
+        histories.key(&alice).push(&mut storage, &2)?;
+        histories.key(&bob).push(&mut storage, &3)?;
+
+        let items: StdResult<Vec<_>> = histories.key(&alice).iter(&storage)?.collect();
+        assert_eq!(items?, vec![1, 2]);
+        let items: StdResult<Vec<_>> = histories.key(&bob).iter(&storage)?.collect();
+        assert_eq!(items?, vec![3]);
+        assert!(histories
+            .key(&Addr::unchecked("carol"))
+            .is_empty(&storage)?);
+
+        // integer and composite keys
+        let by_id: AppendStoreMap<(u64, u8), i32> = AppendStoreMap::new("by_id");
+        by_id.key((1, 2)).push(&mut storage, &12)?;
+        by_id.key((12, 0)).push(&mut storage, &120)?;
+        assert_eq!(by_id.key((1, 2)).get_len(&storage)?, 1);
+        assert_eq!(by_id.key((12, 0)).get_at(&storage, 0)?, 120);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_store_map_has_no_suffix_collisions() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+
+        // plain suffixes are simply concatenated, so these end up in the same store
+        let suffixed = AppendStore::<i32>::new("x").add_suffix("ab");
+        suffixed.push(&mut storage, &1)?;
+        assert_eq!(
+            AppendStore::<i32>::new("xa")
+                .add_suffix("b")
+                .get_len(&storage)?,
+            1
+        );
+
+        let first: AppendStoreMap<&str, i32> = AppendStoreMap::new("y");
+        let second: AppendStoreMap<&str, i32> = AppendStoreMap::new("ya");
+        first.key("ab").push(&mut storage, &1)?;
+        assert!(second.key("b").is_empty(&storage)?);
+        second.key("b").push(&mut storage, &2)?;
+        assert_eq!(first.key("ab").get_len(&storage)?, 1);
+        assert_eq!(first.key("ab").get_at(&storage, 0)?, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reuse_across_storages() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let mut other = MockStorage::new();
+        let append_store: AppendStore<i32> = AppendStore::new("test");
+
+        append_store.push(&mut storage, &1)?;
+        append_store.push(&mut storage, &2)?;
+        assert_eq!(append_store.get_len(&other)?, 0);
+
+        append_store.push(&mut other, &3)?;
+        assert_eq!(append_store.get_len(&storage)?, 2);
+        assert_eq!(append_store.get_len(&other)?, 1);
+
+        let items: StdResult<Vec<_>> = append_store.iter(&storage)?.collect();
+        assert_eq!(items?, vec![1, 2]);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn test_rollback() -> StdResult<()> {
+        fn copy(storage: &dyn Storage) -> MockStorage {
+            let mut copy = MockStorage::new();
+            for (k, v) in storage.range(None, None, Order::Ascending) {
+                copy.set(&k, &v);
+            }
+            copy
+        }
+
+        let mut storage = MockStorage::new();
+        let append_store: AppendStore<i32> = AppendStore::new("test");
+        append_store.push(&mut storage, &1)?;
+
+        // writes made in a transaction that is rolled back are never seen again
+        let mut transaction = copy(&storage);
+        append_store.push(&mut transaction, &2)?;
+        append_store.push(&mut transaction, &3)?;
+        assert_eq!(append_store.get_len(&transaction)?, 3);
+        drop(transaction);
+
+        assert_eq!(append_store.get_len(&storage)?, 1);
+        append_store.push(&mut storage, &4)?;
+        let items: StdResult<Vec<_>> = append_store.iter(&storage)?.collect();
+        assert_eq!(items?, vec![1, 4]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_query() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let append_store: AppendStore<i32, Borsh> = AppendStore::new("test");
+        append_store.extend(&mut storage, &[1, 2, 3])?;
+        append_store.pop(&mut storage)?;
+
+        let querier = mock_remote_querier(storage);
+        let querier = QuerierWrapper::<Empty>::new(&querier);
+        let remote = || Addr::unchecked("remote");
+        let hash = || "hash".to_string();
+
+        assert_eq!(append_store.query_len(&querier, remote(), hash())?, 2);
+        assert_eq!(append_store.query_at(&querier, remote(), hash(), 1)?, 2);
+        // the popped element is still in storage, but out of bounds
+        assert!(append_store
+            .query_at(&querier, remote(), hash(), 2)
+            .is_err());
+
+        let other: AppendStore<i32> = AppendStore::new("other");
+        assert_eq!(other.query_len(&querier, remote(), hash())?, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_clear_full() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let append_store: AppendStore<i32> = AppendStore::new("test");
+        for i in 0..5 {
+            append_store.push(&mut storage, &i)?;
+        }
+        let element = |pos: u32| [b"test".as_slice(), &pos.to_be_bytes()].concat();
+
+        // removes from the back, a few elements at a time
+        assert!(!append_store.clear_full(&mut storage, Some(3))?);
+        assert_eq!(append_store.get_len(&storage)?, 2);
+        assert!(storage.get(&element(2)).is_none());
+        assert_eq!(append_store.get_at(&storage, 1)?, 1);
+
+        assert!(append_store.clear_full(&mut storage, Some(3))?);
+        assert!(append_store.is_empty(&storage)?);
+        assert!(storage.get(&element(0)).is_none());
+        assert!(storage.get(b"testlen").is_none());
+
+        // nothing left to do
+        assert!(append_store.clear_full(&mut storage, None)?);
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn test_no_orphaned_keys() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let append_store: AppendStore<i32> = AppendStore::new("test");
+        let count_keys = |storage: &MockStorage| {
+            storage
+                .range(None, None, cosmwasm_std::Order::Ascending)
+                .count()
+        };
+        append_store.extend(&mut storage, &[1, 2, 3, 4, 5, 6])?;
+        append_store.pop(&mut storage)?;
+        append_store.remove(&mut storage, 0)?;
+        // the 4 elements and the length
+        assert_eq!(count_keys(&storage), 5);
+
+        append_store.clear(&mut storage)?;
+        assert_eq!(count_keys(&storage), 0);
+        assert!(append_store.clear_full(&mut storage, None)?);
+        assert_eq!(count_keys(&storage), 0);
+
+        Ok(())
+    }
 }
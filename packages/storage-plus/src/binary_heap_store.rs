@@ -0,0 +1,432 @@
+//! A "binary heap store" is a priority queue in storage, which gives access to its greatest (or
+//! smallest) element at a logarithmic cost.
+//!
+//! It uses the same layout as an `AppendStore`: every element is stored in a separate storage
+//! entry indexed by its position, and a special key is reserved for the length of the collection.
+//! The elements are kept in heap order, so pushing and popping only read and write the elements
+//! along a single path from the root to a leaf.
+use std::cmp::Ordering;
+use std::marker::PhantomData;
+
+use cosmwasm_std::{Addr, CustomQuery, QuerierWrapper, StdError, StdResult, Storage};
+
+use crate::append_store::{AppendStore, AppendStoreIter};
+use crate::{Json, Serde};
+
+/// Selects which element a `BinaryHeapStore` gives priority to.
+pub trait HeapOrder {
+    /// Returns `Ordering::Greater` if `a` should be popped before `b`.
+    fn priority<T: Ord>(a: &T, b: &T) -> Ordering;
+}
+
+/// Pops the greatest element first.
+#[derive(Copy, Clone, Debug)]
+pub struct MaxHeap;
+
+impl HeapOrder for MaxHeap {
+    fn priority<T: Ord>(a: &T, b: &T) -> Ordering {
+        a.cmp(b)
+    }
+}
+
+/// Pops the smallest element first.
+#[derive(Copy, Clone, Debug)]
+pub struct MinHeap;
+
+impl HeapOrder for MinHeap {
+    fn priority<T: Ord>(a: &T, b: &T) -> Ordering {
+        b.cmp(a)
+    }
+}
+
+/// A priority queue in storage.
+///
+/// ```rust
+/// use cosmwasm_std::Addr;
+/// use secret_storage_plus::{BinaryHeapStore, MinHeap};
+///
+/// // claims ordered by expiration time, the next one to expire comes first
+/// const CLAIMS: BinaryHeapStore<(u64, Addr), secret_storage_plus::Json, MinHeap> =
+///     BinaryHeapStore::new("claims");
+/// ```
+pub struct BinaryHeapStore<'a, T, Ser = Json, Kind = MaxHeap>
+where
+    Ser: Serde<T>,
+{
+    store: AppendStore<'a, T, Ser>,
+    kind: PhantomData<Kind>,
+}
+
+impl<'a, T, Ser: Serde<T>, Kind> BinaryHeapStore<'a, T, Ser, Kind> {
+    /// constructor
+    pub const fn new(prefix: &'a str) -> Self {
+        Self {
+            store: AppendStore::new(prefix),
+            kind: PhantomData,
+        }
+    }
+    /// This is used to produce a new BinaryHeapStore. This can be used when you want to associate a BinaryHeapStore to each user
+    /// and you still get to define the BinaryHeapStore as a static constant
+    ///
+    /// Unlike `AppendStore::add_suffix`, the suffix is length-prefixed, so `"x"` with suffix
+    /// `"ab"` and `"xa"` with suffix `"b"` are different heaps.
+    pub fn add_suffix(&self, suffix: &str) -> Self {
+        Self {
+            store: self.store.add_length_prefixed_suffix(suffix),
+            kind: PhantomData,
+        }
+    }
+}
+
+impl<'a, T, Ser, Kind> BinaryHeapStore<'a, T, Ser, Kind>
+where
+    T: Ord,
+    Ser: Serde<T>,
+    Kind: HeapOrder,
+{
+    /// gets the length from storage, and otherwise returns 0
+    pub fn get_len(&self, storage: &dyn Storage) -> StdResult<u32> {
+        self.store.get_len(storage)
+    }
+    /// checks if the collection has any elements
+    pub fn is_empty(&self, storage: &dyn Storage) -> StdResult<bool> {
+        self.store.is_empty(storage)
+    }
+    /// Clear the collection and remove its elements from storage
+    pub fn clear(&self, storage: &mut dyn Storage) -> StdResult<()> {
+        self.store.clear(storage)
+    }
+    /// Clear the collection and remove its elements from storage, at most `limit` per call.
+    /// Returns true once it is empty.
+    pub fn clear_full(&self, storage: &mut dyn Storage, limit: Option<u32>) -> StdResult<bool> {
+        self.store.clear_full(storage, limit)
+    }
+    /// Returns the element that would be popped next, if any
+    pub fn peek(&self, storage: &dyn Storage) -> StdResult<Option<T>> {
+        if self.get_len(storage)? == 0 {
+            return Ok(None);
+        }
+        self.store.get_at_unchecked(storage, 0).map(Some)
+    }
+    /// Pushes an item to the BinaryHeapStore
+    pub fn push(&self, storage: &mut dyn Storage, item: &T) -> StdResult<()> {
+        let len = self.get_len(storage)?;
+        self.sift_up(storage, len, item)?;
+        self.store.set_len(storage, len + 1);
+        Ok(())
+    }
+    /// Pops the element with the highest priority from the BinaryHeapStore
+    pub fn pop(&self, storage: &mut dyn Storage) -> StdResult<T> {
+        if let Some(len) = self.get_len(storage)?.checked_sub(1) {
+            let last = self.store.get_at_unchecked(storage, len)?;
+            self.store.remove_at_unchecked(storage, len);
+            self.store.set_len(storage, len);
+            if len == 0 {
+                return Ok(last);
+            }
+            let top = self.store.get_at_unchecked(storage, 0)?;
+            self.sift_down(storage, len, &last)?;
+            Ok(top)
+        } else {
+            Err(StdError::generic_err(
+                "Can not pop from empty BinaryHeapStore",
+            ))
+        }
+    }
+    /// If you import the proper BinaryHeapStore from the remote contract, this will let you read
+    /// its length from a remote contract in a type-safe way using WasmQuery::RawQuery
+    pub fn query_len<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+    ) -> StdResult<u32> {
+        self.store
+            .query_len(querier, remote_contract, remote_contract_code_hash)
+    }
+    /// Reads the element that would be popped next from a remote contract, if any
+    pub fn query_peek<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+    ) -> StdResult<Option<T>> {
+        let len = self.query_len(
+            querier,
+            remote_contract.clone(),
+            remote_contract_code_hash.clone(),
+        )?;
+        if len == 0 {
+            return Ok(None);
+        }
+        self.store
+            .query_at_unchecked(querier, remote_contract, remote_contract_code_hash, 0)
+            .map(Some)
+    }
+    /// Returns a readonly iterator over the elements in storage order, which is not sorted
+    pub fn iter(&self, storage: &'a dyn Storage) -> StdResult<AppendStoreIter<'_, T, Ser>> {
+        self.store.iter(storage)
+    }
+    /// Returns every element, in the order they would be popped.
+    ///
+    /// This reads the whole collection without modifying it.
+    pub fn into_sorted_vec(&self, storage: &dyn Storage) -> StdResult<Vec<T>> {
+        let mut items = self.store.iter(storage)?.collect::<StdResult<Vec<T>>>()?;
+        items.sort_by(|a, b| Kind::priority(b, a));
+        Ok(items)
+    }
+    /// does paging over the elements in the order they would be popped
+    ///
+    /// Every call reads the whole collection, since the elements are only partially sorted in
+    /// storage.
+    pub fn sorted_paging(
+        &self,
+        storage: &dyn Storage,
+        start_page: u32,
+        size: u32,
+    ) -> StdResult<Vec<T>> {
+        Ok(self
+            .into_sorted_vec(storage)?
+            .into_iter()
+            .skip((start_page as usize) * (size as usize))
+            .take(size as usize)
+            .collect())
+    }
+
+    /// Moves `item` from the hole at `pos` towards the root until its parent has a higher
+    /// priority, shifting the parents it passes down into the hole.
+    fn sift_up(&self, storage: &mut dyn Storage, mut pos: u32, item: &T) -> StdResult<()> {
+        while pos > 0 {
+            let parent_pos = (pos - 1) / 2;
+            let parent = self.store.get_at_unchecked(storage, parent_pos)?;
+            if Kind::priority(item, &parent) != Ordering::Greater {
+                break;
+            }
+            self.store.set_at_unchecked(storage, pos, &parent)?;
+            pos = parent_pos;
+        }
+        self.store.set_at_unchecked(storage, pos, item)
+    }
+
+    /// Moves `item` from the hole at the root towards the leaves until neither of its children
+    /// has a higher priority, shifting the children it passes up into the hole.
+    fn sift_down(&self, storage: &mut dyn Storage, len: u32, item: &T) -> StdResult<()> {
+        let mut pos = 0;
+        loop {
+            let left = 2 * pos + 1;
+            if left >= len {
+                break;
+            }
+            let mut child_pos = left;
+            let mut child = self.store.get_at_unchecked(storage, left)?;
+            if left + 1 < len {
+                let right = self.store.get_at_unchecked(storage, left + 1)?;
+                if Kind::priority(&right, &child) == Ordering::Greater {
+                    child_pos = left + 1;
+                    child = right;
+                }
+            }
+            if Kind::priority(&child, item) != Ordering::Greater {
+                break;
+            }
+            self.store.set_at_unchecked(storage, pos, &child)?;
+            pos = child_pos;
+        }
+        self.store.set_at_unchecked(storage, pos, item)
+    }
+}
+
+impl<'a, T, Ser: Serde<T>, Kind> Clone for BinaryHeapStore<'a, T, Ser, Kind> {
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+            kind: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::MockStorage;
+    use cosmwasm_std::Addr;
+    use cosmwasm_std::Empty;
+
+    use super::*;
+    use crate::helpers::mock_remote_querier;
+    use crate::Bincode2;
+
+    #[test]
+    fn test_push_pop() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let heap: BinaryHeapStore<i32> = BinaryHeapStore::new("test");
+        for item in [5, 1, 8, 3, 9, 2, 8] {
+            heap.push(&mut storage, &item)?;
+        }
+        assert_eq!(heap.get_len(&storage)?, 7);
+        assert_eq!(heap.peek(&storage)?, Some(9));
+
+        let mut popped = vec![];
+        while !heap.is_empty(&storage)? {
+            popped.push(heap.pop(&mut storage)?);
+        }
+        assert_eq!(popped, vec![9, 8, 8, 5, 3, 2, 1]);
+        assert_eq!(heap.peek(&storage)?, None);
+        assert!(heap.pop(&mut storage).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_min_heap() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let claims: BinaryHeapStore<(u64, Addr), Bincode2, MinHeap> =
+            BinaryHeapStore::new("claims");
+        claims.push(&mut storage, &(300, Addr::unchecked("alice")))?;
+        claims.push(&mut storage, &(100, Addr::unchecked("bob")))?;
+        claims.push(&mut storage, &(200, Addr::unchecked("carol")))?;
+        claims.push(&mut storage, &(100, Addr::unchecked("alice")))?;
+
+        assert_eq!(
+            claims.peek(&storage)?,
+            Some((100, Addr::unchecked("alice")))
+        );
+        assert_eq!(claims.pop(&mut storage)?, (100, Addr::unchecked("alice")));
+        assert_eq!(claims.pop(&mut storage)?, (100, Addr::unchecked("bob")));
+
+        claims.push(&mut storage, &(50, Addr::unchecked("dave")))?;
+        assert_eq!(claims.pop(&mut storage)?, (50, Addr::unchecked("dave")));
+        assert_eq!(claims.pop(&mut storage)?, (200, Addr::unchecked("carol")));
+        assert_eq!(claims.pop(&mut storage)?, (300, Addr::unchecked("alice")));
+        assert!(claims.is_empty(&storage)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sorted_vec_and_paging() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let heap: BinaryHeapStore<u32> = BinaryHeapStore::new("test");
+        let items: Vec<u32> = (0..50).map(|i| (i * 37) % 50).collect();
+        for item in &items {
+            heap.push(&mut storage, item)?;
+        }
+
+        let sorted = heap.into_sorted_vec(&storage)?;
+        assert_eq!(sorted, (0..50).rev().collect::<Vec<u32>>());
+        // reading it does not modify the heap
+        assert_eq!(heap.get_len(&storage)?, 50);
+
+        assert_eq!(heap.sorted_paging(&storage, 0, 3)?, vec![49, 48, 47]);
+        assert_eq!(heap.sorted_paging(&storage, 2, 3)?, vec![43, 42, 41]);
+        assert_eq!(heap.sorted_paging(&storage, 16, 3)?, vec![1, 0]);
+        assert!(heap.sorted_paging(&storage, 17, 3)?.is_empty());
+
+        // iter returns every element in storage order, with the top one first
+        let mut stored = heap.iter(&storage)?.collect::<StdResult<Vec<u32>>>()?;
+        assert_eq!(stored[0], 49);
+        stored.sort_unstable();
+        assert_eq!(stored, (0..50).collect::<Vec<u32>>());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_shares_layout_with_append_store() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let heap: BinaryHeapStore<i32> = BinaryHeapStore::new("test");
+        heap.push(&mut storage, &1)?;
+        heap.push(&mut storage, &3)?;
+        heap.push(&mut storage, &2)?;
+
+        let append_store: AppendStore<i32> = AppendStore::new("test");
+        assert_eq!(append_store.get_len(&storage)?, 3);
+        assert_eq!(append_store.get_at(&storage, 0)?, 3);
+
+        // popping removes the last position from storage
+        assert_eq!(heap.pop(&mut storage)?, 3);
+        let last = [b"test".as_slice(), &2u32.to_be_bytes()].concat();
+        assert!(storage.get(&last).is_none());
+
+        heap.clear(&mut storage)?;
+        assert!(append_store.is_empty(&storage)?);
+        assert_eq!(heap.peek(&storage)?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_suffixed_heaps() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let heap: BinaryHeapStore<i32, Json, MinHeap> = BinaryHeapStore::new("test");
+        let alice = heap.add_suffix("alice");
+        let bob = heap.add_suffix("bob");
+
+        alice.push(&mut storage, &2)?;
+        alice.push(&mut storage, &1)?;
+        bob.push(&mut storage, &7)?;
+
+        assert_eq!(alice.pop(&mut storage)?, 1);
+        assert_eq!(bob.pop(&mut storage)?, 7);
+        assert_eq!(alice.pop(&mut storage)?, 2);
+        assert!(bob.is_empty(&storage)?);
+
+        // suffixes can't run into each other
+        let first: BinaryHeapStore<i32> = BinaryHeapStore::new("x");
+        let second: BinaryHeapStore<i32> = BinaryHeapStore::new("xa");
+        first.add_suffix("ab").push(&mut storage, &1)?;
+        assert!(second.add_suffix("b").is_empty(&storage)?);
+        assert!(first.add_suffix("a").add_suffix("b").is_empty(&storage)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_randomized_against_std() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let heap: BinaryHeapStore<u64, Bincode2> = BinaryHeapStore::new("test");
+        let mut expected = std::collections::BinaryHeap::new();
+
+        // simple LCG so the test is deterministic
+        let mut seed = 7u64;
+        for _ in 0..500 {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let value = seed >> 40;
+            if value % 3 == 1 {
+                assert_eq!(heap.pop(&mut storage).ok(), expected.pop());
+            } else {
+                heap.push(&mut storage, &value)?;
+                expected.push(value);
+            }
+            assert_eq!(heap.peek(&storage)?, expected.peek().copied());
+        }
+        assert_eq!(heap.into_sorted_vec(&storage)?, {
+            let mut sorted = expected.into_sorted_vec();
+            sorted.reverse();
+            sorted
+        });
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_query() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let heap: BinaryHeapStore<u32, Bincode2, MinHeap> = BinaryHeapStore::new("test");
+        let empty: BinaryHeapStore<u32, Bincode2, MinHeap> = BinaryHeapStore::new("empty");
+        heap.push(&mut storage, &5)?;
+        heap.push(&mut storage, &2)?;
+
+        let querier = mock_remote_querier(storage);
+        let querier = QuerierWrapper::<Empty>::new(&querier);
+        let remote = || Addr::unchecked("remote");
+        let hash = || "hash".to_string();
+
+        assert_eq!(heap.query_len(&querier, remote(), hash())?, 2);
+        assert_eq!(heap.query_peek(&querier, remote(), hash())?, Some(2));
+        assert_eq!(empty.query_peek(&querier, remote(), hash())?, None);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,138 @@
+use cosmwasm_std::{Addr, CustomQuery, QuerierWrapper, StdResult, Storage};
+
+use crate::keys::PrimaryKey;
+use crate::{Accumulator, Json, Serde};
+
+/// Counter keeps a `u64` count for every key, such as the number of tokens owned by every
+/// account.
+///
+/// Counts are stored like a `Map<K, u64>` under the given namespace. A key that was never counted
+/// reads as 0, and counts that drop back to 0 are removed from storage.
+pub struct Counter<'a, K, Ser = Json> {
+    counts: Accumulator<'a, K, u64, Ser>,
+}
+
+impl<'a, K, Ser> Counter<'a, K, Ser> {
+    pub const fn new(namespace: &'a str) -> Self {
+        Counter {
+            counts: Accumulator::new(namespace),
+        }
+    }
+
+    pub fn namespace(&self) -> &'a [u8] {
+        self.counts.namespace()
+    }
+}
+
+impl<'a, K, Ser> Counter<'a, K, Ser>
+where
+    K: PrimaryKey<'a>,
+    Ser: Serde<u64>,
+{
+    /// get returns the count of the key, or 0 if there is none
+    pub fn get(&self, store: &dyn Storage, k: K) -> StdResult<u64> {
+        self.counts.get(store, k)
+    }
+
+    /// increment adds 1 to the count of the key and returns the new count
+    pub fn increment(&self, store: &mut dyn Storage, k: K) -> StdResult<u64> {
+        self.counts.add(store, k, 1)
+    }
+
+    /// decrement subtracts 1 from the count of the key and returns the new count.
+    /// Returns an error if the count is already 0.
+    pub fn decrement(&self, store: &mut dyn Storage, k: K) -> StdResult<u64> {
+        self.counts.sub(store, k, 1)
+    }
+
+    /// add adds `amount` to the count of the key and returns the new count.
+    /// Returns an error if the count would overflow.
+    pub fn add(&self, store: &mut dyn Storage, k: K, amount: u64) -> StdResult<u64> {
+        self.counts.add(store, k, amount)
+    }
+
+    /// sub subtracts `amount` from the count of the key and returns the new count.
+    /// Returns an error if the count would drop below 0.
+    pub fn sub(&self, store: &mut dyn Storage, k: K, amount: u64) -> StdResult<u64> {
+        self.counts.sub(store, k, amount)
+    }
+
+    /// set replaces the count of the key
+    pub fn set(&self, store: &mut dyn Storage, k: K, value: u64) -> StdResult<()> {
+        self.counts.set(store, k, value)
+    }
+
+    /// remove resets the count of the key to 0
+    pub fn remove(&self, store: &mut dyn Storage, k: K) {
+        self.counts.remove(store, k);
+    }
+
+    /// If you import the proper Counter from the remote contract, this will let you read the
+    /// count of a key from a remote contract in a type-safe way.
+    pub fn query<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+        k: K,
+    ) -> StdResult<u64> {
+        self.counts
+            .query(querier, remote_contract, remote_contract_code_hash, k)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use cosmwasm_std::testing::MockStorage;
+    use cosmwasm_std::StdError;
+
+    use crate::{Bincode2, Map};
+
+    const VOTES: Counter<&str> = Counter::new("votes");
+
+    #[test]
+    fn counts_per_key() {
+        let mut store = MockStorage::new();
+
+        assert_eq!(1, VOTES.increment(&mut store, "yes").unwrap());
+        assert_eq!(2, VOTES.increment(&mut store, "yes").unwrap());
+        assert_eq!(1, VOTES.increment(&mut store, "no").unwrap());
+        assert_eq!(12, VOTES.add(&mut store, "yes", 10).unwrap());
+        assert_eq!(11, VOTES.decrement(&mut store, "yes").unwrap());
+        assert_eq!(0, VOTES.decrement(&mut store, "no").unwrap());
+
+        assert_eq!(11, VOTES.get(&store, "yes").unwrap());
+        assert_eq!(0, VOTES.get(&store, "no").unwrap());
+        assert_eq!(0, VOTES.get(&store, "abstain").unwrap());
+
+        let map: Map<&str, u64> = Map::new("votes");
+        assert_eq!(11, map.load(&store, "yes").unwrap());
+        assert!(!map.has(&store, "no"));
+    }
+
+    #[test]
+    fn checked_arithmetic() {
+        let mut store = MockStorage::new();
+        let counter: Counter<u32, Bincode2> = Counter::new("counter");
+
+        assert!(matches!(
+            counter.decrement(&mut store, 1),
+            Err(StdError::Overflow { .. })
+        ));
+
+        counter.set(&mut store, 1, u64::MAX - 1).unwrap();
+        assert_eq!(u64::MAX, counter.increment(&mut store, 1).unwrap());
+        assert!(matches!(
+            counter.add(&mut store, 1, 1),
+            Err(StdError::Overflow { .. })
+        ));
+        assert!(counter.sub(&mut store, 1, u64::MAX).is_ok());
+        assert_eq!(0, counter.get(&store, 1).unwrap());
+
+        counter.set(&mut store, 2, 5).unwrap();
+        counter.remove(&mut store, 2);
+        assert_eq!(0, counter.get(&store, 2).unwrap());
+    }
+}
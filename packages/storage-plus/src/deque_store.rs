@@ -7,41 +7,63 @@
 use std::any::type_name;
 use std::convert::TryInto;
 use std::marker::PhantomData;
-use std::sync::Mutex;
 
-use serde::{de::DeserializeOwned, Serialize};
-
-use cosmwasm_std::{StdError, StdResult, Storage};
+use cosmwasm_std::{Addr, CustomQuery, QuerierWrapper, StdError, StdResult, Storage};
 
+use crate::helpers::{nested_namespaces_with_key, query_deserialize, query_u32};
+use crate::keys::PrimaryKey;
+use crate::paging::{Order, Page, PositionedItems};
 use crate::{Json, Serde};
 
-const LEN_KEY: &[u8] = b"len";
-const OFFSET_KEY: &[u8] = b"off";
+pub(crate) const LEN_KEY: &[u8] = b"len";
+pub(crate) const OFFSET_KEY: &[u8] = b"off";
 
 pub struct DequeStore<'a, T, Ser = Json>
 where
-    T: Serialize + DeserializeOwned,
-    Ser: Serde,
+    Ser: Serde<T>,
 {
     /// prefix of the newly constructed Storage
     namespace: &'a [u8],
     /// needed if any suffixes were added to the original namespace.
     /// therefore it is not necessarily same as the namespace.
     prefix: Option<Vec<u8>>,
-    length: Mutex<Option<u32>>,
-    offset: Mutex<Option<u32>>,
+    /// maximum number of elements, if the store is used as a ring buffer
+    capacity: Option<u32>,
     item_type: PhantomData<T>,
     serialization_type: PhantomData<Ser>,
 }
 
-impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> DequeStore<'a, T, Ser> {
+impl<'a, T, Ser: Serde<T>> DequeStore<'a, T, Ser> {
     /// constructor
     pub const fn new(prefix: &'a str) -> Self {
         Self {
             namespace: prefix.as_bytes(),
             prefix: None,
-            length: Mutex::new(None),
-            offset: Mutex::new(None),
+            capacity: None,
+            item_type: PhantomData,
+            serialization_type: PhantomData,
+        }
+    }
+    /// constructor for a DequeStore that holds at most `capacity` elements.
+    ///
+    /// Once it is full, `push_back` evicts the element at the front, so the store keeps the
+    /// last `capacity` elements that were pushed. `push_back_evicting` also returns it.
+    pub const fn with_capacity(prefix: &'a str, capacity: u32) -> Self {
+        assert!(capacity > 0, "DequeStore capacity must be greater than 0");
+        Self {
+            namespace: prefix.as_bytes(),
+            prefix: None,
+            capacity: Some(capacity),
+            item_type: PhantomData,
+            serialization_type: PhantomData,
+        }
+    }
+    /// constructor for a store whose keys all start with `prefix`, which already includes the namespace
+    pub(crate) fn with_prefix(namespace: &'a [u8], prefix: Vec<u8>, capacity: Option<u32>) -> Self {
+        Self {
+            namespace,
+            prefix: Some(prefix),
+            capacity,
             item_type: PhantomData,
             serialization_type: PhantomData,
         }
@@ -57,44 +79,23 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> DequeStore<'a, T, Ser> {
         Self {
             namespace: self.namespace,
             prefix: Some(prefix),
-            length: Mutex::new(None),
-            offset: Mutex::new(None),
+            capacity: self.capacity,
             item_type: self.item_type,
             serialization_type: self.serialization_type,
         }
     }
 }
 
-impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> DequeStore<'a, T, Ser> {
-    /// gets the length from storage, and otherwise sets it to 0
+impl<'a, T, Ser: Serde<T>> DequeStore<'a, T, Ser> {
+    /// gets the length from storage, and otherwise returns 0
+    ///
+    /// The length is not cached, so the same store can safely be used with different storages.
     pub fn get_len(&self, storage: &dyn Storage) -> StdResult<u32> {
-        let mut may_len = self.length.lock().unwrap();
-        if let Some(len) = *may_len {
-            Ok(len)
-        } else {
-            match self._get_u32(storage, LEN_KEY) {
-                Ok(len) => {
-                    *may_len = Some(len);
-                    Ok(len)
-                }
-                Err(e) => Err(e),
-            }
-        }
+        self._get_u32(storage, LEN_KEY)
     }
-    /// gets the offset from storage, and otherwise sets it to 0
+    /// gets the offset from storage, and otherwise returns 0
     pub fn get_off(&self, storage: &dyn Storage) -> StdResult<u32> {
-        let mut may_off = self.offset.lock().unwrap();
-        if let Some(len) = *may_off {
-            Ok(len)
-        } else {
-            match self._get_u32(storage, OFFSET_KEY) {
-                Ok(len) => {
-                    *may_off = Some(len);
-                    Ok(len)
-                }
-                Err(e) => Err(e),
-            }
-        }
+        self._get_u32(storage, OFFSET_KEY)
     }
     /// gets offset or length
     fn _get_u32(&self, storage: &dyn Storage, key: &[u8]) -> StdResult<u32> {
@@ -110,6 +111,10 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> DequeStore<'a, T, Ser> {
             Ok(0)
         }
     }
+    /// gets the maximum number of elements, if the store was created with a capacity
+    pub fn capacity(&self) -> Option<u32> {
+        self.capacity
+    }
     /// checks if the collection has any elements
     pub fn is_empty(&self, storage: &dyn Storage) -> StdResult<bool> {
         Ok(self.get_len(storage)? == 0)
@@ -120,27 +125,19 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> DequeStore<'a, T, Ser> {
         if pos >= len {
             return Err(StdError::generic_err("DequeStore access out of bounds"));
         }
-        self.get_at_unchecked(storage, pos)
-    }
-    /// tries to get the element at pos
-    fn get_at_unchecked(&self, storage: &dyn Storage, pos: u32) -> StdResult<T> {
-        self.load_impl(storage, &self._get_offset_pos(storage, pos)?.to_be_bytes())
-    }
-    /// add the offset to the pos
-    fn _get_offset_pos(&self, storage: &dyn Storage, pos: u32) -> StdResult<u32> {
         let off = self.get_off(storage)?;
-        Ok(pos.overflowing_add(off).0)
+        self.get_at_unchecked(storage, off, pos)
+    }
+    /// tries to get the element at pos, given the offset the caller has read
+    fn get_at_unchecked(&self, storage: &dyn Storage, off: u32, pos: u32) -> StdResult<T> {
+        self.load_impl(storage, &pos.overflowing_add(off).0.to_be_bytes())
     }
     /// Set the length of the collection
     fn set_len(&self, storage: &mut dyn Storage, len: u32) {
-        let mut may_len = self.length.lock().unwrap();
-        *may_len = Some(len);
         self._set_u32(storage, LEN_KEY, len)
     }
     /// Set the offset of the collection
     fn set_off(&self, storage: &mut dyn Storage, off: u32) {
-        let mut may_off = self.offset.lock().unwrap();
-        *may_off = Some(off);
         self._set_u32(storage, OFFSET_KEY, off)
     }
     /// Set the length or offset of the collection
@@ -148,10 +145,33 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> DequeStore<'a, T, Ser> {
         let num_key = [self.as_slice(), key].concat();
         storage.set(&num_key, &num.to_be_bytes());
     }
-    /// Clear the collection
-    pub fn clear(&self, storage: &mut dyn Storage) {
-        self.set_len(storage, 0);
-        self.set_off(storage, 0);
+    /// Clear the collection and remove its elements from storage.
+    ///
+    /// The cost grows with the length of the collection, so use `clear_full` with a limit to
+    /// clear a large one over several transactions.
+    pub fn clear(&self, storage: &mut dyn Storage) -> StdResult<()> {
+        self.clear_full(storage, None)?;
+        Ok(())
+    }
+    /// Clear the collection and remove its elements from storage.
+    ///
+    /// At most `limit` elements are removed per call, starting from the back, so a large
+    /// collection can be cleared over several transactions. Returns true once it is empty,
+    /// at which point the length and offset are removed from storage as well.
+    pub fn clear_full(&self, storage: &mut dyn Storage, limit: Option<u32>) -> StdResult<bool> {
+        let len = self.get_len(storage)?;
+        let off = self.get_off(storage)?;
+        let new_len = len.saturating_sub(limit.unwrap_or(u32::MAX));
+        for pos in new_len..len {
+            self.remove_at_unchecked(storage, off, pos);
+        }
+        if new_len == 0 {
+            storage.remove(&[self.as_slice(), LEN_KEY].concat());
+            storage.remove(&[self.as_slice(), OFFSET_KEY].concat());
+        } else {
+            self.set_len(storage, new_len);
+        }
+        Ok(new_len == 0)
     }
     /// Replaces data at a position within bounds
     pub fn set_at(&self, storage: &mut dyn Storage, pos: u32, item: &T) -> StdResult<()> {
@@ -159,33 +179,90 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> DequeStore<'a, T, Ser> {
         if pos >= len {
             return Err(StdError::generic_err("DequeStore access out of bounds"));
         }
-        self.set_at_unchecked(storage, pos, item)
+        let off = self.get_off(storage)?;
+        self.set_at_unchecked(storage, off, pos, item)
+    }
+    /// Sets data at a given index, given the offset the caller has read
+    fn set_at_unchecked(
+        &self,
+        storage: &mut dyn Storage,
+        off: u32,
+        pos: u32,
+        item: &T,
+    ) -> StdResult<()> {
+        self.save_impl(storage, &pos.overflowing_add(off).0.to_be_bytes(), item)
     }
-    /// Sets data at a given index
-    fn set_at_unchecked(&self, storage: &mut dyn Storage, pos: u32, item: &T) -> StdResult<()> {
-        let off = self._get_offset_pos(storage, pos)?.to_be_bytes();
-        self.save_impl(storage, &off, item)
+    /// Removes the data at a given index from storage, given the offset the caller has read
+    fn remove_at_unchecked(&self, storage: &mut dyn Storage, off: u32, pos: u32) {
+        let key = pos.overflowing_add(off).0.to_be_bytes();
+        storage.remove(&[self.as_slice(), &key].concat());
     }
-    /// Pushes an item to the back
+    /// checks if the collection has reached its capacity
+    fn is_full(&self, len: u32) -> bool {
+        matches!(self.capacity, Some(capacity) if len >= capacity)
+    }
+    /// Pushes an item to the back.
+    ///
+    /// If the store was created with a capacity and is full, the element at the front is
+    /// evicted to make room.
     pub fn push_back(&self, storage: &mut dyn Storage, item: &T) -> StdResult<()> {
         let len = self.get_len(storage)?;
-        self.set_at_unchecked(storage, len, item)?;
+        let off = self.get_off(storage)?;
+        self.push_back_impl(storage, len, off, item)
+    }
+    /// Pushes an item to the back like `push_back`, and returns the element that was evicted
+    /// (and removed from storage) from the front if the store was created with a capacity and
+    /// is full.
+    pub fn push_back_evicting(&self, storage: &mut dyn Storage, item: &T) -> StdResult<Option<T>> {
+        let len = self.get_len(storage)?;
+        let off = self.get_off(storage)?;
+        let evicted = if self.is_full(len) {
+            Some(self.get_at_unchecked(storage, off, 0)?)
+        } else {
+            None
+        };
+        self.push_back_impl(storage, len, off, item)?;
+        Ok(evicted)
+    }
+    fn push_back_impl(
+        &self,
+        storage: &mut dyn Storage,
+        len: u32,
+        off: u32,
+        item: &T,
+    ) -> StdResult<()> {
+        if self.is_full(len) {
+            // drop the front element, so the length stays the same
+            self.remove_at_unchecked(storage, off, 0);
+            let off = off.overflowing_add(1).0;
+            self.set_off(storage, off);
+            return self.set_at_unchecked(storage, off, len - 1, item);
+        }
+        self.set_at_unchecked(storage, off, len, item)?;
         self.set_len(storage, len + 1);
         Ok(())
     }
-    /// Pushes an item to the front
+    /// Pushes an item to the front.
+    ///
+    /// Returns an error if the store was created with a capacity and is full.
     pub fn push_front(&self, storage: &mut dyn Storage, item: &T) -> StdResult<()> {
         let off = self.get_off(storage)?;
         let len = self.get_len(storage)?;
-        self.set_off(storage, off.overflowing_sub(1).0);
-        self.set_at_unchecked(storage, 0, item)?;
+        if self.is_full(len) {
+            return Err(StdError::generic_err("DequeStore is full"));
+        }
+        let off = off.overflowing_sub(1).0;
+        self.set_off(storage, off);
+        self.set_at_unchecked(storage, off, 0, item)?;
         self.set_len(storage, len + 1);
         Ok(())
     }
     /// Pops an item from the back
     pub fn pop_back(&self, storage: &mut dyn Storage) -> StdResult<T> {
         if let Some(len) = self.get_len(storage)?.checked_sub(1) {
-            let item = self.get_at_unchecked(storage, len);
+            let off = self.get_off(storage)?;
+            let item = self.get_at_unchecked(storage, off, len);
+            self.remove_at_unchecked(storage, off, len);
             self.set_len(storage, len);
             item
         } else {
@@ -196,7 +273,8 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> DequeStore<'a, T, Ser> {
     pub fn pop_front(&self, storage: &mut dyn Storage) -> StdResult<T> {
         if let Some(len) = self.get_len(storage)?.checked_sub(1) {
             let off = self.get_off(storage)?;
-            let item = self.get_at_unchecked(storage, 0);
+            let item = self.get_at_unchecked(storage, off, 0);
+            self.remove_at_unchecked(storage, off, 0);
             self.set_len(storage, len);
             self.set_off(storage, off.overflowing_add(1).0);
             item
@@ -204,6 +282,43 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> DequeStore<'a, T, Ser> {
             Err(StdError::generic_err("Can not pop from empty DequeStore"))
         }
     }
+    /// Inserts an element at the specified position, shifting the elements after it.
+    ///
+    /// Inserting at the head (first) or tail (last) has a constant cost.
+    /// Otherwise, all the elements between the closest tip of the collection (head or tail)
+    /// and the specified position will be shifted in storage.
+    ///
+    /// Returns an error if the store was created with a capacity and is full.
+    pub fn insert(&self, storage: &mut dyn Storage, pos: u32, item: &T) -> StdResult<()> {
+        let off = self.get_off(storage)?;
+        let len = self.get_len(storage)?;
+        if pos > len {
+            return Err(StdError::generic_err("DequeStore access out of bounds"));
+        }
+        if self.is_full(len) {
+            return Err(StdError::generic_err("DequeStore is full"));
+        }
+        let to_tail = len - pos;
+        if to_tail <= pos {
+            // closer to the tail
+            for i in (pos..len).rev() {
+                let element_to_shift = self.get_at_unchecked(storage, off, i)?;
+                self.set_at_unchecked(storage, off, i + 1, &element_to_shift)?;
+            }
+            self.set_at_unchecked(storage, off, pos, item)?;
+        } else {
+            // closer to the head
+            let off = off.overflowing_sub(1).0;
+            self.set_off(storage, off);
+            for i in 0..pos {
+                let element_to_shift = self.get_at_unchecked(storage, off, i + 1)?;
+                self.set_at_unchecked(storage, off, i, &element_to_shift)?;
+            }
+            self.set_at_unchecked(storage, off, pos, item)?;
+        }
+        self.set_len(storage, len + 1);
+        Ok(())
+    }
     /// Remove an element from the collection at the specified position.
     ///
     /// Removing an element from the head (first) or tail (last) has a constant cost.
@@ -219,20 +334,22 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> DequeStore<'a, T, Ser> {
         if pos >= len {
             return Err(StdError::generic_err("DequeStorage access out of bounds"));
         }
-        let item = self.get_at_unchecked(storage, pos);
+        let item = self.get_at_unchecked(storage, off, pos);
         let to_tail = len - pos;
         if to_tail < pos {
             // closer to the tail
             for i in pos..(len - 1) {
-                let element_to_shift = self.get_at_unchecked(storage, i + 1)?;
-                self.set_at_unchecked(storage, i, &element_to_shift)?;
+                let element_to_shift = self.get_at_unchecked(storage, off, i + 1)?;
+                self.set_at_unchecked(storage, off, i, &element_to_shift)?;
             }
+            self.remove_at_unchecked(storage, off, len - 1);
         } else {
             // closer to the head
             for i in (0..pos).rev() {
-                let element_to_shift = self.get_at_unchecked(storage, i)?;
-                self.set_at_unchecked(storage, i + 1, &element_to_shift)?;
+                let element_to_shift = self.get_at_unchecked(storage, off, i)?;
+                self.set_at_unchecked(storage, off, i + 1, &element_to_shift)?;
             }
+            self.remove_at_unchecked(storage, off, 0);
             self.set_off(storage, off.overflowing_add(1).0);
         }
         self.set_len(storage, len - 1);
@@ -251,10 +368,66 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> DequeStore<'a, T, Ser> {
             .take(size as usize)
             .collect()
     }
+    /// does paging with the given parameters, starting from the last element
+    pub fn paging_reverse(
+        &self,
+        storage: &dyn Storage,
+        start_page: u32,
+        size: u32,
+    ) -> StdResult<Vec<T>> {
+        self.iter(storage)?
+            .rev()
+            .skip((start_page as usize) * (size as usize))
+            .take(size as usize)
+            .collect()
+    }
+    /// does paging in the given order, and also returns the length of the collection
+    pub fn page(
+        &self,
+        storage: &dyn Storage,
+        start_page: u32,
+        size: u32,
+        order: Order,
+    ) -> StdResult<Page<T>> {
+        let items = match order {
+            Order::Ascending => self.paging(storage, start_page, size)?,
+            Order::Descending => self.paging_reverse(storage, start_page, size)?,
+        };
+        Ok(Page {
+            items,
+            total: self.get_len(storage)?,
+        })
+    }
+    /// Returns the elements at positions `start..end` along with their positions.
+    ///
+    /// Missing bounds default to the ends of the collection, and `end` is capped at its length,
+    /// so the position of the last element returned can be used as a cursor for the next call.
+    pub fn range<'c>(
+        &'c self,
+        storage: &'c dyn Storage,
+        start: Option<u32>,
+        end: Option<u32>,
+        order: Order,
+    ) -> StdResult<PositionedItems<'c, T>>
+    where
+        T: 'c,
+        Ser: 'c,
+    {
+        let len = self.get_len(storage)?;
+        let end = end.map_or(len, |end| end.min(len));
+        let start = start.unwrap_or(0).min(end);
+        let iter = DequeStoreIter::new(self, storage, start, end);
+        let with_pos = |(pos, item): (u32, StdResult<T>)| item.map(|item| (pos, item));
+        let range: PositionedItems<T> = match order {
+            Order::Ascending => Box::new((start..end).zip(iter).map(with_pos)),
+            Order::Descending => Box::new((start..end).rev().zip(iter.rev()).map(with_pos)),
+        };
+        Ok(range)
+    }
 }
 
-impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> DequeStore<'a, T, Ser> {
-    fn as_slice(&self) -> &[u8] {
+impl<'a, T, Ser: Serde<T>> DequeStore<'a, T, Ser> {
+    pub(crate) fn as_slice(&self) -> &[u8] {
         if let Some(prefix) = &self.prefix {
             prefix
         } else {
@@ -292,35 +465,151 @@ impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> DequeStore<'a, T, Ser> {
     }
 }
 
-impl<'a, T: Serialize + DeserializeOwned, Ser: Serde> Clone for DequeStore<'a, T, Ser> {
+impl<'a, T, Ser: Serde<T>> DequeStore<'a, T, Ser> {
+    /// If you import the proper DequeStore from the remote contract, this will let you read its
+    /// length from a remote contract in a type-safe way using WasmQuery::RawQuery
+    pub fn query_len<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+    ) -> StdResult<u32> {
+        let len_key = [self.as_slice(), LEN_KEY].concat();
+        query_u32(
+            querier,
+            remote_contract,
+            remote_contract_code_hash,
+            &len_key,
+        )
+    }
+    /// Reads the offset of a DequeStore in a remote contract
+    pub fn query_off<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+    ) -> StdResult<u32> {
+        let off_key = [self.as_slice(), OFFSET_KEY].concat();
+        query_u32(
+            querier,
+            remote_contract,
+            remote_contract_code_hash,
+            &off_key,
+        )
+    }
+    /// Reads the element at pos from a remote contract, decoding it with `Ser`.
+    ///
+    /// This sends three queries, since the length and the offset have to be read first.
+    pub fn query_at<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+        pos: u32,
+    ) -> StdResult<T> {
+        let len = self.query_len(
+            querier,
+            remote_contract.clone(),
+            remote_contract_code_hash.clone(),
+        )?;
+        if pos >= len {
+            return Err(StdError::generic_err("DequeStore access out of bounds"));
+        }
+        let off = self.query_off(
+            querier,
+            remote_contract.clone(),
+            remote_contract_code_hash.clone(),
+        )?;
+        let key = [self.as_slice(), &pos.overflowing_add(off).0.to_be_bytes()].concat();
+        query_deserialize::<T, Ser, Q>(querier, remote_contract, remote_contract_code_hash, &key)?
+            .ok_or_else(|| StdError::not_found(type_name::<T>()))
+    }
+}
+
+impl<'a, T, Ser: Serde<T>> Clone for DequeStore<'a, T, Ser> {
     fn clone(&self) -> Self {
         Self {
             namespace: self.namespace,
             prefix: self.prefix.clone(),
-            length: Mutex::new(None),
-            offset: Mutex::new(None),
+            capacity: self.capacity,
             item_type: self.item_type,
             serialization_type: self.serialization_type,
         }
     }
 }
 
+/// A collection of a deque stores, one for each key.
+///
+/// The prefix of every store is derived from the namespace and the key using the same
+/// length-prefixed scheme as `Map`, so stores for different keys can never overlap, and any
+/// `PrimaryKey` (addresses, integers, tuples...) can be used without converting it to a string.
+#[derive(Debug, Clone)]
+pub struct DequeStoreMap<'a, K, T, Ser = Json> {
+    namespace: &'a [u8],
+    capacity: Option<u32>,
+    // see https://doc.rust-lang.org/std/marker/struct.PhantomData.html#unused-type-parameters for why this is needed
+    key_type: PhantomData<K>,
+    data_type: PhantomData<T>,
+    serialization_type: PhantomData<*const Ser>,
+}
+
+impl<'a, K, T, Ser> DequeStoreMap<'a, K, T, Ser> {
+    pub const fn new(namespace: &'a str) -> Self {
+        DequeStoreMap {
+            namespace: namespace.as_bytes(),
+            capacity: None,
+            key_type: PhantomData,
+            data_type: PhantomData,
+            serialization_type: PhantomData,
+        }
+    }
+
+    /// constructor for a map whose stores each hold at most `capacity` elements,
+    /// see [`DequeStore::with_capacity`]
+    pub const fn with_capacity(namespace: &'a str, capacity: u32) -> Self {
+        assert!(capacity > 0, "DequeStore capacity must be greater than 0");
+        DequeStoreMap {
+            namespace: namespace.as_bytes(),
+            capacity: Some(capacity),
+            key_type: PhantomData,
+            data_type: PhantomData,
+            serialization_type: PhantomData,
+        }
+    }
+
+    pub fn namespace(&self) -> &'a [u8] {
+        self.namespace
+    }
+}
+
+impl<'a, K, T, Ser> DequeStoreMap<'a, K, T, Ser>
+where
+    K: PrimaryKey<'a>,
+    Ser: Serde<T>,
+{
+    /// Returns the store that belongs to the given key
+    pub fn key(&self, k: K) -> DequeStore<'a, T, Ser> {
+        let prefix = nested_namespaces_with_key(&[self.namespace], &k.key(), b"");
+        DequeStore::with_prefix(self.namespace, prefix, self.capacity)
+    }
+}
+
 /// An iterator over the contents of the deque store.
 pub struct DequeStoreIter<'a, T, Ser>
 where
-    T: Serialize + DeserializeOwned,
-    Ser: Serde,
+    Ser: Serde<T>,
 {
     deque_store: &'a DequeStore<'a, T, Ser>,
     storage: &'a dyn Storage,
     start: u32,
     end: u32,
+    /// the offset, read from storage with the first element
+    off: Option<u32>,
 }
 
 impl<'a, T, Ser> DequeStoreIter<'a, T, Ser>
 where
-    T: Serialize + DeserializeOwned,
-    Ser: Serde,
+    Ser: Serde<T>,
 {
     /// constructor
     pub fn new(
@@ -334,14 +623,22 @@ where
             storage,
             start,
             end,
+            off: None,
         }
     }
+    /// gets the element at pos, which the iterator bounds already keep in range
+    fn get_at(&mut self, pos: u32) -> StdResult<T> {
+        let off = match self.off {
+            Some(off) => off,
+            None => *self.off.insert(self.deque_store.get_off(self.storage)?),
+        };
+        self.deque_store.get_at_unchecked(self.storage, off, pos)
+    }
 }
 
 impl<'a, T, Ser> Iterator for DequeStoreIter<'a, T, Ser>
 where
-    T: Serialize + DeserializeOwned,
-    Ser: Serde,
+    Ser: Serde<T>,
 {
     type Item = StdResult<T>;
 
@@ -349,7 +646,7 @@ where
         if self.start >= self.end {
             return None;
         }
-        let item = self.deque_store.get_at(self.storage, self.start);
+        let item = self.get_at(self.start);
         self.start += 1;
         Some(item)
     }
@@ -374,15 +671,14 @@ where
 
 impl<'a, T, Ser> DoubleEndedIterator for DequeStoreIter<'a, T, Ser>
 where
-    T: Serialize + DeserializeOwned,
-    Ser: Serde,
+    Ser: Serde<T>,
 {
     fn next_back(&mut self) -> Option<Self::Item> {
         if self.start >= self.end {
             return None;
         }
         self.end -= 1;
-        let item = self.deque_store.get_at(self.storage, self.end);
+        let item = self.get_at(self.end);
         Some(item)
     }
 
@@ -399,18 +695,17 @@ where
 }
 
 // This enables writing `deque_store.iter().skip(n).rev()`
-impl<'a, T, Ser> ExactSizeIterator for DequeStoreIter<'a, T, Ser>
-where
-    T: Serialize + DeserializeOwned,
-    Ser: Serde,
-{
-}
+impl<'a, T, Ser> ExactSizeIterator for DequeStoreIter<'a, T, Ser> where Ser: Serde<T> {}
 
 #[cfg(test)]
 mod tests {
     use cosmwasm_std::testing::MockStorage;
+    use cosmwasm_std::Addr;
+    use cosmwasm_std::Empty;
+
+    use crate::helpers::mock_remote_querier;
 
-    use crate::Json;
+    use crate::{Borsh, Json};
 
     use super::*;
 
@@ -603,6 +898,16 @@ mod tests {
         let bytes = storage.get(&key);
         assert_eq!(bytes, Some(b"1234".to_vec()));
 
+        // Check that overriding the serializer with Borsh works
+        let mut storage = MockStorage::new();
+        let borsh_deque_store: DequeStore<i32, Borsh> = DequeStore::new("test3");
+        borsh_deque_store.push_back(&mut storage, &1234)?;
+
+        let key = [borsh_deque_store.as_slice(), &0_u32.to_be_bytes()].concat();
+        let bytes = storage.get(&key);
+        assert_eq!(bytes, Some(1234_i32.to_le_bytes().to_vec()));
+        assert_eq!(borsh_deque_store.get_at(&storage, 0)?, 1234);
+
         Ok(())
     }
 
@@ -631,4 +936,395 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_paging_reverse() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let deque_store: DequeStore<u32> = DequeStore::new("test");
+
+        let page_size: u32 = 5;
+        let total_items: u32 = 50;
+
+        for i in 0..total_items {
+            deque_store.push_back(&mut storage, &i)?;
+        }
+
+        for start_page in 0..(total_items / page_size) {
+            let values = deque_store.paging_reverse(&storage, start_page, page_size)?;
+            assert_eq!(values.len(), page_size as usize);
+
+            for (index, value) in values.iter().enumerate() {
+                assert_eq!(
+                    value,
+                    &(total_items - 1 - page_size * start_page - index as u32)
+                )
+            }
+        }
+
+        // pages past the end are empty
+        assert!(deque_store
+            .paging_reverse(&storage, total_items / page_size, page_size)?
+            .is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_page() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let deque_store: DequeStore<u32> = DequeStore::new("test");
+
+        for i in 0..7 {
+            deque_store.push_back(&mut storage, &i)?;
+        }
+
+        let page = deque_store.page(&storage, 1, 3, Order::Ascending)?;
+        assert_eq!(
+            page,
+            Page {
+                items: vec![3, 4, 5],
+                total: 7
+            }
+        );
+
+        let page = deque_store.page(&storage, 2, 3, Order::Descending)?;
+        assert_eq!(
+            page,
+            Page {
+                items: vec![0],
+                total: 7
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_range() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let deque_store: DequeStore<u32> = DequeStore::new("test");
+
+        assert_eq!(
+            deque_store
+                .range(&storage, None, None, Order::Ascending)?
+                .count(),
+            0
+        );
+
+        for i in 0..6 {
+            deque_store.push_back(&mut storage, &(i * 10))?;
+        }
+
+        let all: Vec<_> = deque_store
+            .range(&storage, None, None, Order::Ascending)?
+            .collect::<StdResult<_>>()?;
+        assert_eq!(
+            all,
+            vec![(0, 0), (1, 10), (2, 20), (3, 30), (4, 40), (5, 50)]
+        );
+
+        let some: Vec<_> = deque_store
+            .range(&storage, Some(2), Some(4), Order::Ascending)?
+            .collect::<StdResult<_>>()?;
+        assert_eq!(some, vec![(2, 20), (3, 30)]);
+
+        let some: Vec<_> = deque_store
+            .range(&storage, Some(2), Some(4), Order::Descending)?
+            .collect::<StdResult<_>>()?;
+        assert_eq!(some, vec![(3, 30), (2, 20)]);
+
+        // use the last position as a cursor, newest first
+        let first: Vec<_> = deque_store
+            .range(&storage, None, None, Order::Descending)?
+            .take(4)
+            .collect::<StdResult<_>>()?;
+        assert_eq!(first, vec![(5, 50), (4, 40), (3, 30), (2, 20)]);
+        let next: Vec<_> = deque_store
+            .range(&storage, None, Some(first[3].0), Order::Descending)?
+            .take(4)
+            .collect::<StdResult<_>>()?;
+        assert_eq!(next, vec![(1, 10), (0, 0)]);
+
+        // bounds are capped at the length
+        let capped: Vec<_> = deque_store
+            .range(&storage, Some(4), Some(100), Order::Ascending)?
+            .collect::<StdResult<_>>()?;
+        assert_eq!(capped, vec![(4, 40), (5, 50)]);
+        assert_eq!(
+            deque_store
+                .range(&storage, Some(8), None, Order::Ascending)?
+                .count(),
+            0
+        );
+        assert_eq!(
+            deque_store
+                .range(&storage, Some(3), Some(1), Order::Descending)?
+                .count(),
+            0
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_insert() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let deque_store: DequeStore<i32> = DequeStore::new("test");
+
+        // insert into empty store, at the tail and at the head
+        deque_store.insert(&mut storage, 0, &3)?;
+        deque_store.insert(&mut storage, 1, &5)?;
+        deque_store.insert(&mut storage, 0, &1)?;
+        // closer to the head
+        deque_store.insert(&mut storage, 1, &2)?;
+        // closer to the tail
+        deque_store.insert(&mut storage, 3, &4)?;
+
+        let items: StdResult<Vec<_>> = deque_store.iter(&storage)?.collect();
+        assert_eq!(items?, vec![1, 2, 3, 4, 5]);
+        assert!(deque_store.insert(&mut storage, 6, &6).is_err());
+
+        // still works as a deque afterwards
+        assert_eq!(deque_store.pop_front(&mut storage), Ok(1));
+        assert_eq!(deque_store.pop_back(&mut storage), Ok(5));
+        deque_store.push_front(&mut storage, &0)?;
+        deque_store.insert(&mut storage, 2, &9)?;
+        let items: StdResult<Vec<_>> = deque_store.iter(&storage)?.collect();
+        assert_eq!(items?, vec![0, 2, 9, 3, 4]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_with_capacity() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let deque_store: DequeStore<i32> = DequeStore::with_capacity("test", 3);
+        assert_eq!(deque_store.capacity(), Some(3));
+        assert_eq!(DequeStore::<i32>::new("other").capacity(), None);
+
+        deque_store.push_back(&mut storage, &1)?;
+        assert_eq!(deque_store.push_back_evicting(&mut storage, &2)?, None);
+        assert_eq!(deque_store.push_back_evicting(&mut storage, &3)?, None);
+        assert_eq!(deque_store.push_back_evicting(&mut storage, &4)?, Some(1));
+        deque_store.push_back(&mut storage, &5)?;
+
+        assert_eq!(deque_store.get_len(&storage)?, 3);
+        let items: StdResult<Vec<_>> = deque_store.iter(&storage)?.collect();
+        assert_eq!(items?, vec![3, 4, 5]);
+
+        // only push_back evicts
+        assert!(deque_store.push_front(&mut storage, &0).is_err());
+        assert!(deque_store.insert(&mut storage, 1, &0).is_err());
+
+        assert_eq!(deque_store.pop_front(&mut storage), Ok(3));
+        deque_store.push_front(&mut storage, &0)?;
+        let items: StdResult<Vec<_>> = deque_store.iter(&storage)?.collect();
+        assert_eq!(items?, vec![0, 4, 5]);
+
+        // the capacity carries over to suffixed stores
+        let suffixed = deque_store.add_suffix("user");
+        assert_eq!(suffixed.capacity(), Some(3));
+        for i in 0..10 {
+            suffixed.push_back(&mut storage, &i)?;
+        }
+        let items: StdResult<Vec<_>> = suffixed.iter(&storage)?.collect();
+        assert_eq!(items?, vec![7, 8, 9]);
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn test_with_capacity_removes_evicted() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let deque_store: DequeStore<i32> = DequeStore::with_capacity("test", 3);
+        for i in 0..100 {
+            deque_store.push_back(&mut storage, &i)?;
+        }
+        assert_eq!(
+            deque_store.push_back_evicting(&mut storage, &100)?,
+            Some(97)
+        );
+
+        // only the 3 elements, the length and the offset are left
+        let keys = storage
+            .range(None, None, cosmwasm_std::Order::Ascending)
+            .count();
+        assert_eq!(keys, 5);
+        let items: StdResult<Vec<_>> = deque_store.iter(&storage)?.collect();
+        assert_eq!(items?, vec![98, 99, 100]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_store_map() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let events: DequeStoreMap<&Addr, i32> = DequeStoreMap::new("events");
+        let alice = Addr::unchecked("alice");
+        let bob = Addr::unchecked("bob");
+
+        events.key(&alice).push_back(&mut storage, &2)?;
+        events.key(&alice).push_front(&mut storage, &1)?;
+        events.key(&bob).push_back(&mut storage, &3)?;
+
+        let items: StdResult<Vec<_>> = events.key(&alice).iter(&storage)?.collect();
+        assert_eq!(items?, vec![1, 2]);
+        let items: StdResult<Vec<_>> = events.key(&bob).iter(&storage)?.collect();
+        assert_eq!(items?, vec![3]);
+
+        let first: DequeStoreMap<&str, i32> = DequeStoreMap::new("x");
+        let second: DequeStoreMap<&str, i32> = DequeStoreMap::new("xa");
+        first.key("ab").push_back(&mut storage, &1)?;
+        assert!(second.key("b").is_empty(&storage)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_store_map_with_capacity() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let recent: DequeStoreMap<u64, i32> = DequeStoreMap::with_capacity("recent", 2);
+
+        for i in 0..5 {
+            recent.key(1).push_back(&mut storage, &i)?;
+        }
+        recent.key(2).push_back(&mut storage, &10)?;
+
+        assert_eq!(recent.key(1).capacity(), Some(2));
+        let items: StdResult<Vec<_>> = recent.key(1).iter(&storage)?.collect();
+        assert_eq!(items?, vec![3, 4]);
+        let items: StdResult<Vec<_>> = recent.key(2).iter(&storage)?.collect();
+        assert_eq!(items?, vec![10]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reuse_across_storages() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let mut other = MockStorage::new();
+        let deque_store: DequeStore<i32> = DequeStore::new("test");
+
+        deque_store.push_back(&mut storage, &1)?;
+        deque_store.push_back(&mut storage, &2)?;
+        assert_eq!(deque_store.get_len(&other)?, 0);
+
+        deque_store.push_back(&mut other, &3)?;
+        assert_eq!(deque_store.get_len(&storage)?, 2);
+        assert_eq!(deque_store.get_len(&other)?, 1);
+        deque_store.push_front(&mut other, &0)?;
+        let items: StdResult<Vec<_>> = deque_store.iter(&other)?.collect();
+        assert_eq!(items?, vec![0, 3]);
+
+        let items: StdResult<Vec<_>> = deque_store.iter(&storage)?.collect();
+        assert_eq!(items?, vec![1, 2]);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn test_rollback() -> StdResult<()> {
+        fn copy(storage: &dyn Storage) -> MockStorage {
+            let mut copy = MockStorage::new();
+            for (k, v) in storage.range(None, None, Order::Ascending) {
+                copy.set(&k, &v);
+            }
+            copy
+        }
+
+        let mut storage = MockStorage::new();
+        let deque_store: DequeStore<i32> = DequeStore::new("test");
+        deque_store.push_back(&mut storage, &1)?;
+
+        // writes made in a transaction that is rolled back are never seen again
+        let mut transaction = copy(&storage);
+        deque_store.push_back(&mut transaction, &2)?;
+        deque_store.push_back(&mut transaction, &3)?;
+        assert_eq!(deque_store.get_len(&transaction)?, 3);
+        drop(transaction);
+
+        assert_eq!(deque_store.get_len(&storage)?, 1);
+        deque_store.push_back(&mut storage, &4)?;
+        let items: StdResult<Vec<_>> = deque_store.iter(&storage)?.collect();
+        assert_eq!(items?, vec![1, 4]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_query() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let deque_store: DequeStore<i32, Borsh> = DequeStore::new("test");
+        deque_store.push_back(&mut storage, &2)?;
+        deque_store.push_back(&mut storage, &3)?;
+        deque_store.push_front(&mut storage, &1)?;
+
+        let querier = mock_remote_querier(storage);
+        let querier = QuerierWrapper::<Empty>::new(&querier);
+        let remote = || Addr::unchecked("remote");
+        let hash = || "hash".to_string();
+
+        assert_eq!(deque_store.query_len(&querier, remote(), hash())?, 3);
+        assert_eq!(deque_store.query_off(&querier, remote(), hash())?, u32::MAX);
+        assert_eq!(deque_store.query_at(&querier, remote(), hash(), 0)?, 1);
+        assert_eq!(deque_store.query_at(&querier, remote(), hash(), 2)?, 3);
+        assert!(deque_store.query_at(&querier, remote(), hash(), 3).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_clear_full() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let deque_store: DequeStore<i32> = DequeStore::new("test");
+        deque_store.push_back(&mut storage, &2)?;
+        deque_store.push_back(&mut storage, &3)?;
+        deque_store.push_front(&mut storage, &1)?;
+        let element = |pos: u32| [b"test".as_slice(), &pos.to_be_bytes()].concat();
+
+        // the front element wrapped around to the end of the index space
+        assert!(!deque_store.clear_full(&mut storage, Some(1))?);
+        assert!(storage.get(&element(1)).is_none());
+        let items: StdResult<Vec<_>> = deque_store.iter(&storage)?.collect();
+        assert_eq!(items?, vec![1, 2]);
+
+        assert!(deque_store.clear_full(&mut storage, None)?);
+        assert!(storage.get(&element(0)).is_none());
+        assert!(storage.get(&element(u32::MAX)).is_none());
+        assert_eq!(deque_store.get_len(&storage)?, 0);
+        assert_eq!(deque_store.get_off(&storage)?, 0);
+        assert!(storage.get(b"testlen").is_none());
+        assert!(storage.get(b"testoff").is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn test_no_orphaned_keys() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let deque_store: DequeStore<i32> = DequeStore::new("test");
+        let count_keys = |storage: &MockStorage| {
+            storage
+                .range(None, None, cosmwasm_std::Order::Ascending)
+                .count()
+        };
+        for i in 0..8 {
+            deque_store.push_back(&mut storage, &i)?;
+        }
+        deque_store.pop_back(&mut storage)?;
+        deque_store.pop_front(&mut storage)?;
+        // from the tail side and from the head side
+        deque_store.remove(&mut storage, 4)?;
+        deque_store.remove(&mut storage, 1)?;
+        // the 4 elements, the length and the offset
+        assert_eq!(count_keys(&storage), 6);
+
+        deque_store.clear(&mut storage)?;
+        assert_eq!(count_keys(&storage), 0);
+        assert!(deque_store.clear_full(&mut storage, None)?);
+        assert_eq!(count_keys(&storage), 0);
+
+        Ok(())
+    }
 }
@@ -4,8 +4,8 @@
 //! Everything in this file is only responsible for building such keys
 //! and is in no way specific to any kind of storage.
 
-use serde::de::DeserializeOwned;
 use std::any::type_name;
+use std::convert::TryInto;
 
 use crate::{keys::Key, Serde};
 
@@ -18,7 +18,7 @@ use cosmwasm_std::{
 ///
 /// value is an odd type, but this is meant to be easy to use with output from storage.get (Option<Vec<u8>>)
 /// and value.map(|s| s.as_slice()) seems trickier than &value
-pub(crate) fn may_deserialize<T: DeserializeOwned, Ser: Serde>(
+pub(crate) fn may_deserialize<T, Ser: Serde<T>>(
     value: &Option<Vec<u8>>,
 ) -> StdResult<Option<T>> {
     match value {
@@ -28,7 +28,7 @@ pub(crate) fn may_deserialize<T: DeserializeOwned, Ser: Serde>(
 }
 
 /// must_deserialize parses json bytes from storage (Option), returning NotFound error if no data present
-pub(crate) fn must_deserialize<T: DeserializeOwned, Ser: Serde>(value: &Option<Vec<u8>>) -> StdResult<T> {
+pub(crate) fn must_deserialize<T, Ser: Serde<T>>(value: &Option<Vec<u8>>) -> StdResult<T> {
     match value {
         Some(vec) => Ok(Ser::deserialize(vec)?),
         None => Err(StdError::not_found(type_name::<T>())),
@@ -122,6 +122,59 @@ pub(crate) fn query_raw<Q: CustomQuery>(
     }
 }
 
+/// query_deserialize reads the data stored under `key` in a remote contract and parses it with
+/// `Ser`, returning Ok(None) if no data present
+pub(crate) fn query_deserialize<T, Ser: Serde<T>, Q: CustomQuery>(
+    querier: &QuerierWrapper<Q>,
+    contract_addr: Addr,
+    code_hash: String,
+    key: &[u8],
+) -> StdResult<Option<T>> {
+    let result = query_raw(querier, contract_addr, code_hash, key.into())?;
+    if result.is_empty() {
+        Ok(None)
+    } else {
+        Ser::deserialize(&result).map(Some)
+    }
+}
+
+/// query_u32 reads a big endian u32 (such as a length or an offset) stored under `key` in a
+/// remote contract, returning 0 if no data present
+pub(crate) fn query_u32<Q: CustomQuery>(
+    querier: &QuerierWrapper<Q>,
+    contract_addr: Addr,
+    code_hash: String,
+    key: &[u8],
+) -> StdResult<u32> {
+    let result = query_raw(querier, contract_addr, code_hash, key.into())?;
+    if result.is_empty() {
+        return Ok(0);
+    }
+    let bytes = result
+        .as_slice()
+        .try_into()
+        .map_err(|err| StdError::parse_err("u32", err))?;
+    Ok(u32::from_be_bytes(bytes))
+}
+
+/// Builds a querier that answers the queries sent by `query_raw` with the data found in
+/// `storage`, as if it was the storage of the remote contract.
+#[cfg(test)]
+pub(crate) fn mock_remote_querier(
+    storage: cosmwasm_std::testing::MockStorage,
+) -> cosmwasm_std::testing::MockQuerier {
+    use cosmwasm_std::Storage;
+
+    let mut querier = cosmwasm_std::testing::MockQuerier::new(&[]);
+    querier.update_wasm(move |query| match query {
+        WasmQuery::Smart { msg, .. } => {
+            SystemResult::Ok(ContractResult::Ok(storage.get(msg).unwrap_or_default().into()))
+        }
+        _ => panic!("unexpected query {:?}", query),
+    });
+    querier
+}
+
 #[cfg(test)]
 mod test {
     use crate::Json;
@@ -2,9 +2,7 @@
 #![cfg(feature = "iterator")]
 
 use crate::PrefixBound;
-use cosmwasm_std::{StdError, StdResult, Storage};
-use serde::de::DeserializeOwned;
-use serde::Serialize;
+use cosmwasm_std::{Addr, CustomQuery, QuerierWrapper, StdError, StdResult, Storage};
 
 use crate::de::KeyDeserialize;
 use crate::indexes::Index;
@@ -12,32 +10,60 @@ use crate::iter_helpers::{deserialize_kv, deserialize_v};
 use crate::keys::{Prefixer, PrimaryKey};
 use crate::map::Map;
 use crate::prefix::{namespaced_prefix_range, Prefix};
-use crate::{Bound, Path};
+use crate::{Bound, Json, Path, Serde};
 
-pub trait IndexList<T> {
-    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<T>> + '_>;
+/// The indexes of an `IndexedMap`, which must be declared with the same `Ser` as the map,
+/// as they read (or copy) the primary values
+pub trait IndexList<T, Ser = Json> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<T, Ser>> + '_>;
 }
 
 // TODO: remove traits here and make this const fn new
 /// `IndexedMap` works like a `Map` but has a secondary index
-pub struct IndexedMap<'a, K, T, I>
+///
+/// Primary values are encoded with `Ser`, and `I` must list indexes declared with the same
+/// `Ser`, so an index that would decode the values with another backend doesn't compile:
+///
+/// ```compile_fail
+/// use secret_storage_plus::{Bincode2, Index, IndexList, IndexedMap, MultiIndex};
+///
+/// struct Indexes<'a> {
+///     // decodes the primary values with the default `Json`
+///     parity: MultiIndex<'a, u64, u64, &'a str>,
+/// }
+///
+/// impl<'a> IndexList<u64> for Indexes<'a> {
+///     fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<u64>> + '_> {
+///         let v: Vec<&dyn Index<u64>> = vec![&self.parity];
+///         Box::new(v.into_iter())
+///     }
+/// }
+///
+/// let indexes = Indexes {
+///     parity: MultiIndex::new(|v| *v % 2, "numbers", "numbers__parity"),
+/// };
+/// IndexedMap::<&str, u64, Indexes, Bincode2>::new("numbers", indexes);
+/// ```
+pub struct IndexedMap<'a, K, T, I, Ser = Json>
 where
     K: PrimaryKey<'a>,
-    T: Serialize + DeserializeOwned + Clone,
-    I: IndexList<T>,
+    T: Clone,
+    I: IndexList<T, Ser>,
+    Ser: Serde<T>,
 {
     pk_namespace: &'a [u8],
-    primary: Map<'a, K, T>,
+    primary: Map<'a, K, T, Ser>,
     /// This is meant to be read directly to get the proper types, like:
     /// map.idx.owner.items(...)
     pub idx: I,
 }
 
-impl<'a, K, T, I> IndexedMap<'a, K, T, I>
+impl<'a, K, T, I, Ser> IndexedMap<'a, K, T, I, Ser>
 where
     K: PrimaryKey<'a>,
-    T: Serialize + DeserializeOwned + Clone,
-    I: IndexList<T>,
+    T: Clone,
+    I: IndexList<T, Ser>,
+    Ser: Serde<T>,
 {
     // TODO: remove traits here and make this const fn new
     pub fn new(pk_namespace: &'a str, indexes: I) -> Self {
@@ -48,16 +74,22 @@ where
         }
     }
 
-    pub fn key(&self, k: K) -> Path<T> {
+    pub fn key(&self, k: K) -> Path<T, Ser> {
         self.primary.key(k)
     }
+
+    /// The namespace of the primary map
+    pub fn namespace(&self) -> &'a [u8] {
+        self.pk_namespace
+    }
 }
 
-impl<'a, K, T, I> IndexedMap<'a, K, T, I>
+impl<'a, K, T, I, Ser> IndexedMap<'a, K, T, I, Ser>
 where
     K: PrimaryKey<'a>,
-    T: Serialize + DeserializeOwned + Clone,
-    I: IndexList<T>,
+    T: Clone,
+    I: IndexList<T, Ser>,
+    Ser: Serde<T>,
 {
     /// save will serialize the model and store, returns an error on serialization issues.
     /// this must load the old value to update the indexes properly
@@ -135,18 +167,32 @@ where
         self.primary.key(k).has(store)
     }
 
+    /// If you import the proper IndexedMap from the remote contract, this will let you read the
+    /// primary data from a remote contract in a type-safe way using WasmQuery::RawQuery.
+    pub fn query<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+        k: K,
+    ) -> StdResult<Option<T>> {
+        self.primary
+            .query(querier, remote_contract, remote_contract_code_hash, k)
+    }
+
     // use no_prefix to scan -> range
-    fn no_prefix_raw(&self) -> Prefix<Vec<u8>, T, K> {
+    fn no_prefix_raw(&self) -> Prefix<Vec<u8>, T, K, Ser> {
         Prefix::new(self.pk_namespace, &[])
     }
 }
 
 #[cfg(feature = "iterator")]
-impl<'a, K, T, I> IndexedMap<'a, K, T, I>
+impl<'a, K, T, I, Ser> IndexedMap<'a, K, T, I, Ser>
 where
     K: PrimaryKey<'a>,
-    T: Serialize + DeserializeOwned + Clone,
-    I: IndexList<T>,
+    T: Clone,
+    I: IndexList<T, Ser>,
+    Ser: Serde<T>,
 {
     /// While `range_raw` over a `prefix` fixes the prefix to one element and iterates over the
     /// remaining, `prefix_range_raw` accepts bounds for the lowest and highest elements of the `Prefix`
@@ -163,35 +209,38 @@ where
     where
         T: 'c,
         'a: 'c,
+        Ser: 'c,
     {
-        let mapped =
-            namespaced_prefix_range(store, self.pk_namespace, min, max, order).map(deserialize_v);
+        let mapped = namespaced_prefix_range(store, self.pk_namespace, min, max, order)
+            .map(deserialize_v::<T, Ser>);
         Box::new(mapped)
     }
 }
 
 #[cfg(feature = "iterator")]
-impl<'a, K, T, I> IndexedMap<'a, K, T, I>
+impl<'a, K, T, I, Ser> IndexedMap<'a, K, T, I, Ser>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Clone,
     K: PrimaryKey<'a>,
-    I: IndexList<T>,
+    I: IndexList<T, Ser>,
+    Ser: Serde<T>,
 {
-    pub fn sub_prefix(&self, p: K::SubPrefix) -> Prefix<K::SuperSuffix, T, K::SuperSuffix> {
+    pub fn sub_prefix(&self, p: K::SubPrefix) -> Prefix<K::SuperSuffix, T, K::SuperSuffix, Ser> {
         Prefix::new(self.pk_namespace, &p.prefix())
     }
 
-    pub fn prefix(&self, p: K::Prefix) -> Prefix<K::Suffix, T, K::Suffix> {
+    pub fn prefix(&self, p: K::Prefix) -> Prefix<K::Suffix, T, K::Suffix, Ser> {
         Prefix::new(self.pk_namespace, &p.prefix())
     }
 }
 
 #[cfg(feature = "iterator")]
-impl<'a, K, T, I> IndexedMap<'a, K, T, I>
+impl<'a, K, T, I, Ser> IndexedMap<'a, K, T, I, Ser>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Clone,
     K: PrimaryKey<'a> + KeyDeserialize,
-    I: IndexList<T>,
+    I: IndexList<T, Ser>,
+    Ser: Serde<T>,
 {
     /// While `range` over a `prefix` fixes the prefix to one element and iterates over the
     /// remaining, `prefix_range` accepts bounds for the lowest and highest elements of the
@@ -211,9 +260,10 @@ where
         'a: 'c,
         K: 'c,
         K::Output: 'static,
+        Ser: 'c,
     {
         let mapped = namespaced_prefix_range(store, self.pk_namespace, min, max, order)
-            .map(deserialize_kv::<K, T>);
+            .map(deserialize_kv::<K, T, Ser>);
         Box::new(mapped)
     }
 
@@ -268,7 +318,7 @@ where
         self.no_prefix().keys(store, min, max, order)
     }
 
-    fn no_prefix(&self) -> Prefix<K, T, K> {
+    fn no_prefix(&self) -> Prefix<K, T, K, Ser> {
         Prefix::new(self.pk_namespace, &[])
     }
 }
@@ -278,29 +328,37 @@ mod test {
     use super::*;
 
     use crate::indexes::test::{index_string_tuple, index_tuple};
-    use crate::{MultiIndex, UniqueIndex};
+    use crate::{Bincode2, Borsh, MultiIndex, UniqueIndex, UniqueRef};
+    use borsh::{BorshDeserialize, BorshSerialize};
     use cosmwasm_std::testing::MockStorage;
     use cosmwasm_std::{MemoryStorage, Order};
+    use rstest::*;
+    use rstest_reuse::{self, *};
     use serde::{Deserialize, Serialize};
 
-    #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+    #[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
     struct Data {
         pub name: String,
         pub last_name: String,
         pub age: u32,
     }
 
-    struct DataIndexes<'a> {
+    /// Everything a `Data` map with its indexes stores through its backend
+    trait DataSerde: Serde<Data> + Serde<UniqueRef<Data>> {}
+
+    impl<S> DataSerde for S where S: Serde<Data> + Serde<UniqueRef<Data>> {}
+
+    struct DataIndexes<'a, Ser = Json> {
         // Last type parameters are for signaling pk deserialization
-        pub name: MultiIndex<'a, String, Data, String>,
-        pub age: UniqueIndex<'a, u32, Data, String>,
-        pub name_lastname: UniqueIndex<'a, (Vec<u8>, Vec<u8>), Data, String>,
+        pub name: MultiIndex<'a, String, Data, String, Ser>,
+        pub age: UniqueIndex<'a, u32, Data, String, Ser>,
+        pub name_lastname: UniqueIndex<'a, (Vec<u8>, Vec<u8>), Data, String, Ser>,
     }
 
     // Future Note: this can likely be macro-derived
-    impl<'a> IndexList<Data> for DataIndexes<'a> {
-        fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Data>> + '_> {
-            let v: Vec<&dyn Index<Data>> = vec![&self.name, &self.age, &self.name_lastname];
+    impl<'a, Ser: DataSerde> IndexList<Data, Ser> for DataIndexes<'a, Ser> {
+        fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Data, Ser>> + '_> {
+            let v: Vec<&dyn Index<Data, Ser>> = vec![&self.name, &self.age, &self.name_lastname];
             Box::new(v.into_iter())
         }
     }
@@ -319,8 +377,10 @@ mod test {
         }
     }
 
+    type DataMap<'a, Ser> = IndexedMap<'a, &'a str, Data, DataIndexes<'a, Ser>, Ser>;
+
     // Can we make it easier to define this? (less wordy generic)
-    fn build_map<'a>() -> IndexedMap<'a, &'a str, Data, DataIndexes<'a>> {
+    fn build_map<'a, Ser: DataSerde>() -> DataMap<'a, Ser> {
         let indexes = DataIndexes {
             name: MultiIndex::new(|d| d.name.clone(), "data", "data__name"),
             age: UniqueIndex::new(|d| d.age, "data__age"),
@@ -332,9 +392,16 @@ mod test {
         IndexedMap::new("data", indexes)
     }
 
-    fn save_data<'a>(
+    #[template]
+    #[rstest]
+    #[case(build_map::<Json>())]
+    #[case(build_map::<Bincode2>())]
+    #[case(build_map::<Borsh>())]
+    fn serialization(#[case] map: DataMap<impl DataSerde>) {}
+
+    fn save_data<'a, Ser: DataSerde>(
         store: &mut MockStorage,
-        map: &IndexedMap<'a, &'a str, Data, DataIndexes<'a>>,
+        map: &DataMap<'a, Ser>,
     ) -> (Vec<&'a str>, Vec<Data>) {
         let mut pks = vec![];
         let mut datas = vec![];
@@ -393,10 +460,9 @@ mod test {
         (pks, datas)
     }
 
-    #[test]
-    fn store_and_load_by_index() {
+    #[apply(serialization)]
+    fn store_and_load_by_index(#[case] map: DataMap<impl DataSerde>) {
         let mut store = MockStorage::new();
-        let map = build_map();
 
         // save data
         let (pks, datas) = save_data(&mut store, &map);
@@ -524,20 +590,18 @@ mod test {
         assert_eq!(None, aged);
     }
 
-    #[test]
-    fn existence() {
+    #[apply(serialization)]
+    fn existence(#[case] map: DataMap<impl DataSerde>) {
         let mut store = MockStorage::new();
-        let map = build_map();
         let (pks, _) = save_data(&mut store, &map);
 
         assert!(map.has(&store, pks[0]));
         assert!(!map.has(&store, "6"));
     }
 
-    #[test]
-    fn range_raw_simple_key_by_multi_index() {
+    #[apply(serialization)]
+    fn range_raw_simple_key_by_multi_index(#[case] map: DataMap<impl DataSerde>) {
         let mut store = MockStorage::new();
-        let map = build_map();
 
         // save data
         let data1 = Data {
@@ -590,10 +654,9 @@ mod test {
         assert_eq!(marias[1].1, data1);
     }
 
-    #[test]
-    fn range_simple_key_by_multi_index() {
+    #[apply(serialization)]
+    fn range_simple_key_by_multi_index(#[case] map: DataMap<impl DataSerde>) {
         let mut store = MockStorage::new();
-        let map = build_map();
 
         // save data
         let data1 = Data {
@@ -653,7 +716,7 @@ mod test {
         let indexes = DataCompositeMultiIndex {
             name_age: MultiIndex::new(|d| index_tuple(&d.name, d.age), "data", "data__name_age"),
         };
-        let map = IndexedMap::new("data", indexes);
+        let map: IndexedMap<_, _, _> = IndexedMap::new("data", indexes);
 
         // save data
         let data1 = Data {
@@ -714,7 +777,7 @@ mod test {
         let indexes = DataCompositeMultiIndex {
             name_age: MultiIndex::new(|d| index_tuple(&d.name, d.age), "data", "data__name_age"),
         };
-        let map = IndexedMap::new("data", indexes);
+        let map: IndexedMap<_, _, _> = IndexedMap::new("data", indexes);
 
         // save data
         let data1 = Data {
@@ -768,10 +831,9 @@ mod test {
         assert_eq!(data3, marias[1].1);
     }
 
-    #[test]
-    fn unique_index_enforced() {
+    #[apply(serialization)]
+    fn unique_index_enforced(#[case] map: DataMap<impl DataSerde>) {
         let mut store = MockStorage::new();
-        let map = build_map();
 
         // save data
         let (pks, datas) = save_data(&mut store, &map);
@@ -812,10 +874,9 @@ mod test {
         assert_eq!(v.age, data5.age);
     }
 
-    #[test]
-    fn unique_index_enforced_composite_key() {
+    #[apply(serialization)]
+    fn unique_index_enforced_composite_key(#[case] map: DataMap<impl DataSerde>) {
         let mut store = MockStorage::new();
-        let map = build_map();
 
         // save data
         save_data(&mut store, &map);
@@ -831,15 +892,11 @@ mod test {
         map.save(&mut store, pk5, &data5).unwrap_err();
     }
 
-    #[test]
-    fn remove_and_update_reflected_on_indexes() {
+    #[apply(serialization)]
+    fn remove_and_update_reflected_on_indexes(#[case] map: DataMap<impl DataSerde>) {
         let mut store = MockStorage::new();
-        let map = build_map();
 
-        let name_count = |map: &IndexedMap<&str, Data, DataIndexes>,
-                          store: &MemoryStorage,
-                          name: &str|
-         -> usize {
+        let name_count = |map: &DataMap<_>, store: &MemoryStorage, name: &str| -> usize {
             map.idx
                 .name
                 .prefix(name.to_string())
@@ -875,10 +932,9 @@ mod test {
         assert_eq!(name_count(&map, &store, "Mary"), 1);
     }
 
-    #[test]
-    fn range_raw_simple_key_by_unique_index() {
+    #[apply(serialization)]
+    fn range_raw_simple_key_by_unique_index(#[case] map: DataMap<impl DataSerde>) {
         let mut store = MockStorage::new();
-        let map = build_map();
 
         // save data
         let (pks, datas) = save_data(&mut store, &map);
@@ -908,10 +964,9 @@ mod test {
         assert_eq!(datas[4], ages[4].1);
     }
 
-    #[test]
-    fn range_simple_key_by_unique_index() {
+    #[apply(serialization)]
+    fn range_simple_key_by_unique_index(#[case] map: DataMap<impl DataSerde>) {
         let mut store = MockStorage::new();
-        let map = build_map();
 
         // save data
         let (pks, datas) = save_data(&mut store, &map);
@@ -941,10 +996,9 @@ mod test {
         assert_eq!(datas[4], ages[4].1);
     }
 
-    #[test]
-    fn range_raw_composite_key_by_unique_index() {
+    #[apply(serialization)]
+    fn range_raw_composite_key_by_unique_index(#[case] map: DataMap<impl DataSerde>) {
         let mut store = MockStorage::new();
-        let map = build_map();
 
         // save data
         let (pks, datas) = save_data(&mut store, &map);
@@ -970,10 +1024,9 @@ mod test {
         assert_eq!(datas[1], marias[1].1);
     }
 
-    #[test]
-    fn range_composite_key_by_unique_index() {
+    #[apply(serialization)]
+    fn range_composite_key_by_unique_index(#[case] map: DataMap<impl DataSerde>) {
         let mut store = MockStorage::new();
-        let map = build_map();
 
         // save data
         let (pks, datas) = save_data(&mut store, &map);
@@ -999,11 +1052,10 @@ mod test {
         assert_eq!(datas[1], marias[1].1);
     }
 
-    #[test]
+    #[apply(serialization)]
     #[cfg(feature = "iterator")]
-    fn range_simple_string_key() {
+    fn range_simple_string_key(#[case] map: DataMap<impl DataSerde>) {
         let mut store = MockStorage::new();
-        let map = build_map();
 
         // save data
         let (pks, datas) = save_data(&mut store, &map);
@@ -1037,11 +1089,10 @@ mod test {
         );
     }
 
-    #[test]
+    #[apply(serialization)]
     #[cfg(feature = "iterator")]
-    fn prefix_simple_string_key() {
+    fn prefix_simple_string_key(#[case] map: DataMap<impl DataSerde>) {
         let mut store = MockStorage::new();
-        let map = build_map();
 
         // save data
         let (pks, datas) = save_data(&mut store, &map);
@@ -1072,7 +1123,7 @@ mod test {
         let indexes = DataCompositeMultiIndex {
             name_age: MultiIndex::new(|d| index_tuple(&d.name, d.age), "data", "data__name_age"),
         };
-        let map = IndexedMap::new("data", indexes);
+        let map: IndexedMap<_, _, _> = IndexedMap::new("data", indexes);
 
         // save data
         let data1 = Data {
@@ -1127,7 +1178,7 @@ mod test {
         let indexes = DataCompositeMultiIndex {
             name_age: MultiIndex::new(|d| index_tuple(&d.name, d.age), "data", "data__name_age"),
         };
-        let map = IndexedMap::new("data", indexes);
+        let map: IndexedMap<_, _, _> = IndexedMap::new("data", indexes);
 
         // save data
         let data1 = Data {
@@ -1179,7 +1230,7 @@ mod test {
         let indexes = DataCompositeMultiIndex {
             name_age: MultiIndex::new(|d| index_tuple(&d.name, d.age), "data", "data__name_age"),
         };
-        let map = IndexedMap::new("data", indexes);
+        let map: IndexedMap<_, _, _> = IndexedMap::new("data", indexes);
 
         // save data
         let data1 = Data {
@@ -1237,7 +1288,7 @@ mod test {
         let indexes = DataCompositeMultiIndex {
             name_age: MultiIndex::new(|d| index_tuple(&d.name, d.age), "data", "data__name_age"),
         };
-        let map = IndexedMap::new("data", indexes);
+        let map: IndexedMap<_, _, _> = IndexedMap::new("data", indexes);
 
         // save data
         let data1 = Data {
@@ -1318,7 +1369,7 @@ mod test {
         let indexes = DataCompositeMultiIndex {
             name_age: MultiIndex::new(|d| index_tuple(&d.name, d.age), "data", "data__name_age"),
         };
-        let map = IndexedMap::new("data", indexes);
+        let map: IndexedMap<_, _, _> = IndexedMap::new("data", indexes);
 
         // save data
         let data1 = Data {
@@ -1529,4 +1580,53 @@ mod test {
             );
         }
     }
+
+    mod bincode2_indexes {
+        use super::*;
+
+        struct Indexes<'a> {
+            // Declaring these with the default `Json` would not implement `IndexList<u64, Bincode2>`
+            secondary: MultiIndex<'a, u64, u64, &'a str, Bincode2>,
+            unique: UniqueIndex<'a, u64, u64, &'a str, Bincode2>,
+        }
+
+        impl<'a> IndexList<u64, Bincode2> for Indexes<'a> {
+            fn get_indexes(
+                &'_ self,
+            ) -> Box<dyn Iterator<Item = &'_ dyn Index<u64, Bincode2>> + '_> {
+                let v: Vec<&dyn Index<u64, Bincode2>> = vec![&self.secondary, &self.unique];
+                Box::new(v.into_iter())
+            }
+        }
+
+        #[test]
+        fn indexes_decode_with_map_serialization() {
+            let indexes = Indexes {
+                secondary: MultiIndex::new(|v| *v % 2, "test_map", "test_map__secondary"),
+                unique: UniqueIndex::new(|v| *v, "test_map__unique"),
+            };
+            let map = IndexedMap::<&str, u64, Indexes, Bincode2>::new("test_map", indexes);
+            let mut store = MockStorage::new();
+
+            map.save(&mut store, "one", &1).unwrap();
+            map.save(&mut store, "two", &2).unwrap();
+            map.save(&mut store, "three", &3).unwrap();
+
+            // the primary values are stored with bincode, not json
+            let raw = store.get(&map.key("three")).unwrap();
+            assert_eq!(<Bincode2 as Serde<u64>>::serialize(&3).unwrap(), raw);
+
+            let odd: Vec<_> = map
+                .idx
+                .secondary
+                .prefix(1)
+                .range(&store, None, None, Order::Ascending)
+                .collect::<Result<_, _>>()
+                .unwrap();
+            assert_eq!(odd, vec![("one".to_string(), 1), ("three".to_string(), 3)]);
+
+            let (pk, value) = map.idx.unique.item(&store, 2).unwrap().unwrap();
+            assert_eq!((b"two".to_vec(), 2), (pk, value));
+        }
+    }
 }
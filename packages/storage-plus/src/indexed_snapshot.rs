@@ -1,9 +1,7 @@
 // this module requires iterator to be useful at all
 #![cfg(feature = "iterator")]
 
-use cosmwasm_std::{StdError, StdResult, Storage};
-use serde::de::DeserializeOwned;
-use serde::Serialize;
+use cosmwasm_std::{Addr, CustomQuery, QuerierWrapper, StdError, StdResult, Storage};
 
 use crate::de::KeyDeserialize;
 use crate::iter_helpers::deserialize_kv;
@@ -11,18 +9,21 @@ use crate::keys::{Prefixer, PrimaryKey};
 use crate::prefix::{namespaced_prefix_range, Prefix};
 use crate::snapshot::{ChangeSet, SnapshotMap};
 use crate::PrefixBound;
-use crate::{Bound, IndexList, Map, Path, Strategy};
+use crate::{Bound, IndexList, Json, Map, Path, Serde, Strategy};
 
 /// `IndexedSnapshotMap` works like a `SnapshotMap` but has a secondary index
-pub struct IndexedSnapshotMap<'a, K, T, I> {
+///
+/// Primary values, changelog and checkpoints are encoded with `Ser`, and `I` must list indexes
+/// declared with the same `Ser`.
+pub struct IndexedSnapshotMap<'a, K, T, I, Ser = Json> {
     pk_namespace: &'a [u8],
-    primary: SnapshotMap<'a, K, T>,
+    primary: SnapshotMap<'a, K, T, Ser>,
     /// This is meant to be read directly to get the proper types, like:
     /// map.idx.owner.items(...)
     pub idx: I,
 }
 
-impl<'a, K, T, I> IndexedSnapshotMap<'a, K, T, I> {
+impl<'a, K, T, I, Ser> IndexedSnapshotMap<'a, K, T, I, Ser> {
     /// Examples:
     ///
     /// ```rust
@@ -58,16 +59,17 @@ impl<'a, K, T, I> IndexedSnapshotMap<'a, K, T, I> {
         }
     }
 
-    pub fn changelog(&self) -> &Map<'a, (K, u64), ChangeSet<T>> {
+    pub fn changelog(&self) -> &Map<'a, (K, u64), ChangeSet<T>, Ser> {
         self.primary.changelog()
     }
 }
 
-impl<'a, K, T, I> IndexedSnapshotMap<'a, K, T, I>
+impl<'a, K, T, I, Ser> IndexedSnapshotMap<'a, K, T, I, Ser>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Clone,
     K: PrimaryKey<'a> + Prefixer<'a> + KeyDeserialize,
-    I: IndexList<T>,
+    I: IndexList<T, Ser>,
+    Ser: Serde<T> + Serde<u32> + Serde<ChangeSet<T>>,
 {
     pub fn add_checkpoint(&self, store: &mut dyn Storage, height: u64) -> StdResult<()> {
         self.primary.add_checkpoint(store, height)
@@ -90,16 +92,17 @@ where
         self.primary.assert_checkpointed(store, height)
     }
 
-    pub fn key(&self, k: K) -> Path<T> {
+    pub fn key(&self, k: K) -> Path<T, Ser> {
         self.primary.key(k)
     }
 }
 
-impl<'a, K, T, I> IndexedSnapshotMap<'a, K, T, I>
+impl<'a, K, T, I, Ser> IndexedSnapshotMap<'a, K, T, I, Ser>
 where
     K: PrimaryKey<'a> + Prefixer<'a> + KeyDeserialize,
-    T: Serialize + DeserializeOwned + Clone,
-    I: IndexList<T>,
+    T: Clone,
+    I: IndexList<T, Ser>,
+    Ser: Serde<T> + Serde<u32> + Serde<ChangeSet<T>>,
 {
     /// save will serialize the model and store, returns an error on serialization issues.
     /// this must load the old value to update the indexes properly
@@ -179,18 +182,36 @@ where
         self.primary.may_load(store, key)
     }
 
+    /// If you import the proper IndexedSnapshotMap from the remote contract, this will let you read the
+    /// current data from a remote contract in a type-safe way using WasmQuery::RawQuery.
+    ///
+    /// Historical values cannot be read this way. Finding them takes a range over the changelog,
+    /// and raw queries can only read single keys, so the remote contract has to expose a smart
+    /// query that calls `may_load_at_height` instead.
+    pub fn query<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+        k: K,
+    ) -> StdResult<Option<T>> {
+        self.primary
+            .query(querier, remote_contract, remote_contract_code_hash, k)
+    }
+
     // use no_prefix to scan -> range
-    pub fn no_prefix_raw(&self) -> Prefix<Vec<u8>, T, K> {
+    pub fn no_prefix_raw(&self) -> Prefix<Vec<u8>, T, K, Ser> {
         Prefix::new(self.pk_namespace, &[])
     }
 }
 
 // short-cut for simple keys, rather than .prefix(()).range_raw(...)
-impl<'a, K, T, I> IndexedSnapshotMap<'a, K, T, I>
+impl<'a, K, T, I, Ser> IndexedSnapshotMap<'a, K, T, I, Ser>
 where
     K: PrimaryKey<'a> + Prefixer<'a> + KeyDeserialize,
-    T: Serialize + DeserializeOwned + Clone,
-    I: IndexList<T>,
+    T: Clone,
+    I: IndexList<T, Ser>,
+    Ser: Serde<T> + Serde<u32> + Serde<ChangeSet<T>>,
 {
     // I would prefer not to copy code from Prefix, but no other way
     // with lifetimes (create Prefix inside function and return ref = no no)
@@ -219,27 +240,29 @@ where
 }
 
 #[cfg(feature = "iterator")]
-impl<'a, K, T, I> IndexedSnapshotMap<'a, K, T, I>
+impl<'a, K, T, I, Ser> IndexedSnapshotMap<'a, K, T, I, Ser>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Clone,
     K: PrimaryKey<'a>,
-    I: IndexList<T>,
+    I: IndexList<T, Ser>,
+    Ser: Serde<T>,
 {
-    pub fn sub_prefix(&self, p: K::SubPrefix) -> Prefix<K::SuperSuffix, T, K::SuperSuffix> {
+    pub fn sub_prefix(&self, p: K::SubPrefix) -> Prefix<K::SuperSuffix, T, K::SuperSuffix, Ser> {
         Prefix::new(self.pk_namespace, &p.prefix())
     }
 
-    pub fn prefix(&self, p: K::Prefix) -> Prefix<K::Suffix, T, K::Suffix> {
+    pub fn prefix(&self, p: K::Prefix) -> Prefix<K::Suffix, T, K::Suffix, Ser> {
         Prefix::new(self.pk_namespace, &p.prefix())
     }
 }
 
 #[cfg(feature = "iterator")]
-impl<'a, K, T, I> IndexedSnapshotMap<'a, K, T, I>
+impl<'a, K, T, I, Ser> IndexedSnapshotMap<'a, K, T, I, Ser>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Clone,
     K: PrimaryKey<'a> + KeyDeserialize,
-    I: IndexList<T>,
+    I: IndexList<T, Ser>,
+    Ser: Serde<T>,
 {
     /// While `range` over a `prefix` fixes the prefix to one element and iterates over the
     /// remaining, `prefix_range` accepts bounds for the lowest and highest elements of the
@@ -258,10 +281,11 @@ where
         T: 'c,
         'a: 'c,
         K: 'c,
+        Ser: 'c,
         K::Output: 'static,
     {
         let mapped = namespaced_prefix_range(store, self.pk_namespace, min, max, order)
-            .map(deserialize_kv::<K, T>);
+            .map(deserialize_kv::<K, T, Ser>);
         Box::new(mapped)
     }
 
@@ -293,7 +317,7 @@ where
         self.no_prefix().keys(store, min, max, order)
     }
 
-    fn no_prefix(&self) -> Prefix<K, T, K> {
+    fn no_prefix(&self) -> Prefix<K, T, K, Ser> {
         Prefix::new(self.pk_namespace, &[])
     }
 }
@@ -303,29 +327,43 @@ mod test {
     use super::*;
 
     use crate::indexes::test::{index_string_tuple, index_tuple};
-    use crate::{Index, MultiIndex, UniqueIndex};
+    use crate::{Bincode2, Borsh, Index, MultiIndex, UniqueIndex, UniqueRef};
+    use borsh::{BorshDeserialize, BorshSerialize};
     use cosmwasm_std::testing::MockStorage;
     use cosmwasm_std::{MemoryStorage, Order};
+    use rstest::*;
+    use rstest_reuse::{self, *};
     use serde::{Deserialize, Serialize};
 
-    #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+    #[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
     struct Data {
         pub name: String,
         pub last_name: String,
         pub age: u32,
     }
 
-    struct DataIndexes<'a> {
+    /// Everything a `Data` map with its indexes stores through its backend
+    trait DataSerde:
+        Serde<Data> + Serde<UniqueRef<Data>> + Serde<u32> + Serde<ChangeSet<Data>>
+    {
+    }
+
+    impl<S> DataSerde for S where
+        S: Serde<Data> + Serde<UniqueRef<Data>> + Serde<u32> + Serde<ChangeSet<Data>>
+    {
+    }
+
+    struct DataIndexes<'a, Ser = Json> {
         // Last type parameters are for signaling pk deserialization
-        pub name: MultiIndex<'a, Vec<u8>, Data, String>,
-        pub age: UniqueIndex<'a, u32, Data, String>,
-        pub name_lastname: UniqueIndex<'a, (Vec<u8>, Vec<u8>), Data, String>,
+        pub name: MultiIndex<'a, Vec<u8>, Data, String, Ser>,
+        pub age: UniqueIndex<'a, u32, Data, String, Ser>,
+        pub name_lastname: UniqueIndex<'a, (Vec<u8>, Vec<u8>), Data, String, Ser>,
     }
 
     // Future Note: this can likely be macro-derived
-    impl<'a> IndexList<Data> for DataIndexes<'a> {
-        fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Data>> + '_> {
-            let v: Vec<&dyn Index<Data>> = vec![&self.name, &self.age, &self.name_lastname];
+    impl<'a, Ser: DataSerde> IndexList<Data, Ser> for DataIndexes<'a, Ser> {
+        fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Data, Ser>> + '_> {
+            let v: Vec<&dyn Index<Data, Ser>> = vec![&self.name, &self.age, &self.name_lastname];
             Box::new(v.into_iter())
         }
     }
@@ -344,8 +382,11 @@ mod test {
         }
     }
 
+    type DataSnapshotMap<'a, Ser> =
+        IndexedSnapshotMap<'a, &'a str, Data, DataIndexes<'a, Ser>, Ser>;
+
     // Can we make it easier to define this? (less wordy generic)
-    fn build_snapshot_map<'a>() -> IndexedSnapshotMap<'a, &'a str, Data, DataIndexes<'a>> {
+    fn build_snapshot_map<'a, Ser: DataSerde>() -> DataSnapshotMap<'a, Ser> {
         let indexes = DataIndexes {
             name: MultiIndex::new(|d| d.name.as_bytes().to_vec(), "data", "data__name"),
             age: UniqueIndex::new(|d| d.age, "data__age"),
@@ -363,9 +404,16 @@ mod test {
         )
     }
 
-    fn save_data<'a>(
+    #[template]
+    #[rstest]
+    #[case(build_snapshot_map::<Json>())]
+    #[case(build_snapshot_map::<Bincode2>())]
+    #[case(build_snapshot_map::<Borsh>())]
+    fn serialization(#[case] map: DataSnapshotMap<impl DataSerde>) {}
+
+    fn save_data<'a, Ser: DataSerde>(
         store: &mut MockStorage,
-        map: &IndexedSnapshotMap<'a, &'a str, Data, DataIndexes<'a>>,
+        map: &DataSnapshotMap<'a, Ser>,
     ) -> (Vec<&'a str>, Vec<Data>) {
         let mut pks = vec![];
         let mut datas = vec![];
@@ -418,10 +466,9 @@ mod test {
         (pks, datas)
     }
 
-    #[test]
-    fn store_and_load_by_index() {
+    #[apply(serialization)]
+    fn store_and_load_by_index(#[case] map: DataSnapshotMap<impl DataSerde>) {
         let mut store = MockStorage::new();
-        let map = build_snapshot_map();
 
         // save data
         let (pks, datas) = save_data(&mut store, &map);
@@ -492,10 +539,9 @@ mod test {
         assert_eq!(None, aged);
     }
 
-    #[test]
-    fn range_raw_simple_key_by_multi_index() {
+    #[apply(serialization)]
+    fn range_raw_simple_key_by_multi_index(#[case] map: DataSnapshotMap<impl DataSerde>) {
         let mut store = MockStorage::new();
-        let map = build_snapshot_map();
         let mut height = 1;
 
         // save data
@@ -552,10 +598,9 @@ mod test {
         assert_eq!(marias[1].1, data1);
     }
 
-    #[test]
-    fn range_simple_key_by_multi_index() {
+    #[apply(serialization)]
+    fn range_simple_key_by_multi_index(#[case] map: DataSnapshotMap<impl DataSerde>) {
         let mut store = MockStorage::new();
-        let map = build_snapshot_map();
         let mut height = 1;
 
         // save data
@@ -612,10 +657,9 @@ mod test {
         assert_eq!(marias[1].1, data1);
     }
 
-    #[test]
-    fn changelog_range_works() {
+    #[apply(serialization)]
+    fn changelog_range_works(#[case] map: DataSnapshotMap<impl DataSerde>) {
         let mut store = MockStorage::new();
-        let map = build_snapshot_map();
         let mut height = 1;
 
         // simple data for testing
@@ -679,7 +723,7 @@ mod test {
         let indexes = DataCompositeMultiIndex {
             name_age: MultiIndex::new(|d| index_tuple(&d.name, d.age), "data", "data__name_age"),
         };
-        let map =
+        let map: IndexedSnapshotMap<_, _, _> =
             IndexedSnapshotMap::new("data", "checks", "changes", Strategy::EveryBlock, indexes);
 
         // save data
@@ -745,7 +789,7 @@ mod test {
         let indexes = DataCompositeMultiIndex {
             name_age: MultiIndex::new(|d| index_tuple(&d.name, d.age), "data", "data__name_age"),
         };
-        let map =
+        let map: IndexedSnapshotMap<_, _, _> =
             IndexedSnapshotMap::new("data", "checks", "changes", Strategy::EveryBlock, indexes);
 
         // save data
@@ -803,10 +847,9 @@ mod test {
         assert_eq!(data3, marias[1].1);
     }
 
-    #[test]
-    fn unique_index_enforced() {
+    #[apply(serialization)]
+    fn unique_index_enforced(#[case] map: DataSnapshotMap<impl DataSerde>) {
         let mut store = MockStorage::new();
-        let map = build_snapshot_map();
         let mut height = 3;
 
         // save data
@@ -850,10 +893,9 @@ mod test {
         assert_eq!(v.age, data5.age);
     }
 
-    #[test]
-    fn unique_index_enforced_composite_key() {
+    #[apply(serialization)]
+    fn unique_index_enforced_composite_key(#[case] map: DataSnapshotMap<impl DataSerde>) {
         let mut store = MockStorage::new();
-        let map = build_snapshot_map();
         let height = 4;
 
         // save data
@@ -870,16 +912,12 @@ mod test {
         map.save(&mut store, pk5, &data5, height).unwrap_err();
     }
 
-    #[test]
-    fn remove_and_update_reflected_on_indexes() {
+    #[apply(serialization)]
+    fn remove_and_update_reflected_on_indexes(#[case] map: DataSnapshotMap<impl DataSerde>) {
         let mut store = MockStorage::new();
-        let map = build_snapshot_map();
         let mut height = 5;
 
-        let name_count = |map: &IndexedSnapshotMap<&str, Data, DataIndexes>,
-                          store: &MemoryStorage,
-                          name: &str|
-         -> usize {
+        let name_count = |map: &DataSnapshotMap<_>, store: &MemoryStorage, name: &str| -> usize {
             map.idx
                 .name
                 .prefix(name.as_bytes().to_vec())
@@ -916,10 +954,9 @@ mod test {
         assert_eq!(name_count(&map, &store, "Mary"), 1);
     }
 
-    #[test]
-    fn range_raw_simple_key_by_unique_index() {
+    #[apply(serialization)]
+    fn range_raw_simple_key_by_unique_index(#[case] map: DataSnapshotMap<impl DataSerde>) {
         let mut store = MockStorage::new();
-        let map = build_snapshot_map();
 
         // save data
         let (pks, datas) = save_data(&mut store, &map);
@@ -947,10 +984,9 @@ mod test {
         assert_eq!(datas[0], ages[3].1);
     }
 
-    #[test]
-    fn range_simple_key_by_unique_index() {
+    #[apply(serialization)]
+    fn range_simple_key_by_unique_index(#[case] map: DataSnapshotMap<impl DataSerde>) {
         let mut store = MockStorage::new();
-        let map = build_snapshot_map();
 
         // save data
         let (pks, datas) = save_data(&mut store, &map);
@@ -978,10 +1014,9 @@ mod test {
         assert_eq!(datas[0], ages[3].1);
     }
 
-    #[test]
-    fn range_raw_composite_key_by_unique_index() {
+    #[apply(serialization)]
+    fn range_raw_composite_key_by_unique_index(#[case] map: DataSnapshotMap<impl DataSerde>) {
         let mut store = MockStorage::new();
-        let map = build_snapshot_map();
 
         // save data
         let (pks, datas) = save_data(&mut store, &map);
@@ -1007,10 +1042,9 @@ mod test {
         assert_eq!(datas[1], marias[1].1);
     }
 
-    #[test]
-    fn range_composite_key_by_unique_index() {
+    #[apply(serialization)]
+    fn range_composite_key_by_unique_index(#[case] map: DataSnapshotMap<impl DataSerde>) {
         let mut store = MockStorage::new();
-        let map = build_snapshot_map();
 
         // save data
         let (pks, datas) = save_data(&mut store, &map);
@@ -1036,11 +1070,10 @@ mod test {
         assert_eq!(datas[1], marias[1].1);
     }
 
-    #[test]
+    #[apply(serialization)]
     #[cfg(feature = "iterator")]
-    fn range_simple_string_key() {
+    fn range_simple_string_key(#[case] map: DataSnapshotMap<impl DataSerde>) {
         let mut store = MockStorage::new();
-        let map = build_snapshot_map();
 
         // save data
         let (pks, datas) = save_data(&mut store, &map);
@@ -1074,11 +1107,10 @@ mod test {
         );
     }
 
-    #[test]
+    #[apply(serialization)]
     #[cfg(feature = "iterator")]
-    fn prefix_simple_string_key() {
+    fn prefix_simple_string_key(#[case] map: DataSnapshotMap<impl DataSerde>) {
         let mut store = MockStorage::new();
-        let map = build_snapshot_map();
 
         // save data
         let (pks, datas) = save_data(&mut store, &map);
@@ -1101,11 +1133,10 @@ mod test {
         );
     }
 
-    #[test]
+    #[apply(serialization)]
     #[cfg(feature = "iterator")]
-    fn sub_prefix_simple_string_key() {
+    fn sub_prefix_simple_string_key(#[case] map: DataSnapshotMap<impl DataSerde>) {
         let mut store = MockStorage::new();
-        let map = build_snapshot_map();
 
         // save data
         let (pks, datas) = save_data(&mut store, &map);
@@ -1136,7 +1167,7 @@ mod test {
         let indexes = DataCompositeMultiIndex {
             name_age: MultiIndex::new(|d| index_tuple(&d.name, d.age), "data", "data__name_age"),
         };
-        let map =
+        let map: IndexedSnapshotMap<_, _, _> =
             IndexedSnapshotMap::new("data", "checks", "changes", Strategy::EveryBlock, indexes);
 
         // save data
@@ -4,18 +4,19 @@ mod multi;
 mod unique;
 
 pub use multi::MultiIndex;
-pub use unique::UniqueIndex;
-
-use serde::de::DeserializeOwned;
-use serde::Serialize;
+pub use unique::{UniqueIndex, UniqueRef};
 
 use cosmwasm_std::{StdResult, Storage};
 
+use crate::Json;
+
 // Note: we cannot store traits with generic functions inside `Box<dyn Index>`,
 // so I pull S: Storage to a top-level
-pub trait Index<T>
+/// `Ser` is the serialization of the values the index reads from (or copies out of) the
+/// primary map, so an `IndexList` can only hold indexes that agree with its map.
+pub trait Index<T, Ser = Json>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Clone,
 {
     fn save(&self, store: &mut dyn Storage, pk: &[u8], data: &T) -> StdResult<()>;
     fn remove(&self, store: &mut dyn Storage, pk: &[u8], old_data: &T) -> StdResult<()>;
@@ -1,18 +1,14 @@
 // this module requires iterator to be useful at all
 #![cfg(feature = "iterator")]
 
-use serde::de::DeserializeOwned;
-use serde::Serialize;
-
 use cosmwasm_std::{from_slice, Order, Record, StdError, StdResult, Storage};
 
 use crate::bound::PrefixBound;
 use crate::de::KeyDeserialize;
 use crate::helpers::namespaces_with_key;
-use crate::iter_helpers::deserialize_kv;
 use crate::map::Map;
 use crate::prefix::namespaced_prefix_range;
-use crate::{Bound, Index, Prefix, Prefixer, PrimaryKey};
+use crate::{Bound, Index, Json, Prefix, Prefixer, PrimaryKey, Serde};
 use std::marker::PhantomData;
 
 /// MultiIndex stores (namespace, index_name, idx_value, pk) -> b"pk_len".
@@ -27,18 +23,22 @@ use std::marker::PhantomData;
 /// more important, type-safe bound key type.
 /// This type must match the encompassing `IndexedMap` primary key type,
 /// or its owned variant.
-pub struct MultiIndex<'a, IK, T, PK> {
+///
+/// The Ser type is used to decode the values loaded from the main map, so it must match
+/// the encompassing `IndexedMap` serialization. The stored pk_len is always json encoded.
+pub struct MultiIndex<'a, IK, T, PK, Ser = Json> {
     index: fn(&T) -> IK,
     idx_namespace: &'a [u8],
     // note, we collapse the ik - combining everything under the namespace - and concatenating the pk
     idx_map: Map<'a, Vec<u8>, u32>,
     pk_namespace: &'a [u8],
-    phantom: PhantomData<PK>,
+    phantom: PhantomData<(PK, Ser)>,
 }
 
-impl<'a, IK, T, PK> MultiIndex<'a, IK, T, PK>
+impl<'a, IK, T, PK, Ser> MultiIndex<'a, IK, T, PK, Ser>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Clone,
+    Ser: Serde<T>,
 {
     // TODO: make this a const fn
     /// Create a new MultiIndex
@@ -74,9 +74,13 @@ where
             phantom: PhantomData,
         }
     }
+
+    pub fn namespace(&self) -> &'a [u8] {
+        self.idx_namespace
+    }
 }
 
-fn deserialize_multi_v<T: DeserializeOwned>(
+fn deserialize_multi_v<T, Ser: Serde<T>>(
     store: &dyn Storage,
     pk_namespace: &[u8],
     kv: Record,
@@ -95,12 +99,12 @@ fn deserialize_multi_v<T: DeserializeOwned>(
     let v = store
         .get(&full_key)
         .ok_or_else(|| StdError::generic_err("pk not found"))?;
-    let v = from_slice::<T>(&v)?;
+    let v = Ser::deserialize(&v)?;
 
     Ok((pk.to_vec(), v))
 }
 
-fn deserialize_multi_kv<K: KeyDeserialize, T: DeserializeOwned>(
+fn deserialize_multi_kv<K: KeyDeserialize, T, Ser: Serde<T>>(
     store: &dyn Storage,
     pk_namespace: &[u8],
     kv: Record,
@@ -119,16 +123,17 @@ fn deserialize_multi_kv<K: KeyDeserialize, T: DeserializeOwned>(
     let v = store
         .get(&full_key)
         .ok_or_else(|| StdError::generic_err("pk not found"))?;
-    let v = from_slice::<T>(&v)?;
+    let v = Ser::deserialize(&v)?;
 
     // We return deserialized `pk` here for consistency
     Ok((K::from_slice(pk)?, v))
 }
 
-impl<'a, IK, T, PK> Index<T> for MultiIndex<'a, IK, T, PK>
+impl<'a, IK, T, PK, Ser> Index<T, Ser> for MultiIndex<'a, IK, T, PK, Ser>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Clone,
     IK: PrimaryKey<'a>,
+    Ser: Serde<T>,
 {
     fn save(&self, store: &mut dyn Storage, pk: &[u8], data: &T) -> StdResult<()> {
         let idx = (self.index)(data).joined_extra_key(pk);
@@ -142,27 +147,29 @@ where
     }
 }
 
-impl<'a, IK, T, PK> MultiIndex<'a, IK, T, PK>
+impl<'a, IK, T, PK, Ser> MultiIndex<'a, IK, T, PK, Ser>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Clone,
     IK: PrimaryKey<'a> + Prefixer<'a>,
+    Ser: Serde<T>,
 {
-    fn no_prefix_raw(&self) -> Prefix<Vec<u8>, T, (IK, PK)> {
+    fn no_prefix_raw(&self) -> Prefix<Vec<u8>, T, (IK, PK), Ser> {
         Prefix::with_deserialization_functions(
             self.idx_namespace,
             &[],
             self.pk_namespace,
-            deserialize_multi_v,
-            deserialize_multi_v,
+            deserialize_multi_v::<T, Ser>,
+            deserialize_multi_v::<T, Ser>,
         )
     }
 }
 
-impl<'a, IK, T, PK> MultiIndex<'a, IK, T, PK>
+impl<'a, IK, T, PK, Ser> MultiIndex<'a, IK, T, PK, Ser>
 where
     PK: PrimaryKey<'a> + KeyDeserialize,
-    T: Serialize + DeserializeOwned + Clone,
+    T: Clone,
     IK: PrimaryKey<'a> + Prefixer<'a>,
+    Ser: Serde<T>,
 {
     pub fn index_key(&self, k: IK) -> Vec<u8> {
         k.joined_extra_key(b"")
@@ -192,11 +199,12 @@ where
 }
 
 // short-cut for simple keys, rather than .prefix(()).range_raw(...)
-impl<'a, IK, T, PK> MultiIndex<'a, IK, T, PK>
+impl<'a, IK, T, PK, Ser> MultiIndex<'a, IK, T, PK, Ser>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Clone,
     IK: PrimaryKey<'a> + Prefixer<'a> + KeyDeserialize,
     PK: PrimaryKey<'a> + KeyDeserialize,
+    Ser: Serde<T>,
 {
     // I would prefer not to copy code from Prefix, but no other way
     // with lifetimes (create Prefix inside function and return ref = no no)
@@ -239,47 +247,50 @@ where
     where
         T: 'c,
         'a: 'c,
+        Ser: 'c,
     {
         let mapped = namespaced_prefix_range(store, self.idx_namespace, min, max, order)
-            .map(move |kv| (deserialize_multi_v)(store, self.pk_namespace, kv));
+            .map(move |kv| (deserialize_multi_v::<T, Ser>)(store, self.pk_namespace, kv));
         Box::new(mapped)
     }
 }
 
 #[cfg(feature = "iterator")]
-impl<'a, IK, T, PK> MultiIndex<'a, IK, T, PK>
+impl<'a, IK, T, PK, Ser> MultiIndex<'a, IK, T, PK, Ser>
 where
     PK: PrimaryKey<'a> + KeyDeserialize,
-    T: Serialize + DeserializeOwned + Clone,
+    T: Clone,
     IK: PrimaryKey<'a> + Prefixer<'a>,
+    Ser: Serde<T>,
 {
-    pub fn prefix(&self, p: IK) -> Prefix<PK, T, PK> {
+    pub fn prefix(&self, p: IK) -> Prefix<PK, T, PK, Ser> {
         Prefix::with_deserialization_functions(
             self.idx_namespace,
             &p.prefix(),
             self.pk_namespace,
-            deserialize_multi_kv::<PK, T>,
-            deserialize_multi_v,
+            deserialize_multi_kv::<PK, T, Ser>,
+            deserialize_multi_v::<T, Ser>,
         )
     }
 
-    pub fn sub_prefix(&self, p: IK::Prefix) -> Prefix<PK, T, (IK::Suffix, PK)> {
+    pub fn sub_prefix(&self, p: IK::Prefix) -> Prefix<PK, T, (IK::Suffix, PK), Ser> {
         Prefix::with_deserialization_functions(
             self.idx_namespace,
             &p.prefix(),
             self.pk_namespace,
-            deserialize_multi_kv::<PK, T>,
-            deserialize_multi_v,
+            deserialize_multi_kv::<PK, T, Ser>,
+            deserialize_multi_v::<T, Ser>,
         )
     }
 }
 
 #[cfg(feature = "iterator")]
-impl<'a, IK, T, PK> MultiIndex<'a, IK, T, PK>
+impl<'a, IK, T, PK, Ser> MultiIndex<'a, IK, T, PK, Ser>
 where
     PK: PrimaryKey<'a> + KeyDeserialize,
-    T: Serialize + DeserializeOwned + Clone,
+    T: Clone,
     IK: PrimaryKey<'a> + KeyDeserialize + Prefixer<'a>,
+    Ser: Serde<T>,
 {
     /// While `range` over a `prefix` fixes the prefix to one element and iterates over the
     /// remaining, `prefix_range` accepts bounds for the lowest and highest elements of the
@@ -300,9 +311,11 @@ where
         IK: 'c,
         PK: 'c,
         PK::Output: 'static,
+        Ser: 'c,
     {
+        let pk_namespace = self.pk_namespace;
         let mapped = namespaced_prefix_range(store, self.idx_namespace, min, max, order)
-            .map(deserialize_kv::<PK, T>);
+            .map(move |kv| (deserialize_multi_kv::<PK, T, Ser>)(store, pk_namespace, kv));
         Box::new(mapped)
     }
 
@@ -334,13 +347,13 @@ where
         self.no_prefix().keys(store, min, max, order)
     }
 
-    fn no_prefix(&self) -> Prefix<PK, T, (IK, PK)> {
+    fn no_prefix(&self) -> Prefix<PK, T, (IK, PK), Ser> {
         Prefix::with_deserialization_functions(
             self.idx_namespace,
             &[],
             self.pk_namespace,
-            deserialize_multi_kv::<PK, T>,
-            deserialize_multi_v,
+            deserialize_multi_kv::<PK, T, Ser>,
+            deserialize_multi_v::<T, Ser>,
         )
     }
 }
@@ -3,37 +3,54 @@
 
 use std::marker::PhantomData;
 
-use serde::de::DeserializeOwned;
+use borsh::{BorshDeserialize, BorshSerialize};
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{from_slice, Binary, Order, Record, StdError, StdResult, Storage};
+use cosmwasm_std::{Binary, Order, Record, StdError, StdResult, Storage};
 
 use crate::bound::PrefixBound;
 use crate::de::KeyDeserialize;
-use crate::iter_helpers::deserialize_kv;
 use crate::map::Map;
 use crate::prefix::namespaced_prefix_range;
-use crate::{Bound, Index, Prefix, Prefixer, PrimaryKey};
+use crate::{Bound, Index, Json, Prefix, Prefixer, PrimaryKey, Serde};
 
 /// UniqueRef stores Binary(Vec[u8]) representation of private key and index value
-#[derive(Deserialize, Serialize)]
-pub(crate) struct UniqueRef<T> {
+#[derive(Deserialize, Serialize, Debug)]
+pub struct UniqueRef<T> {
     // note, we collapse the pk - combining everything under the namespace - even if it is composite
     pk: Binary,
     value: T,
 }
 
+// Binary has no borsh support, so the pk is written as plain bytes
+impl<T: BorshSerialize> BorshSerialize for UniqueRef<T> {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        BorshSerialize::serialize(&self.pk.0, writer)?;
+        BorshSerialize::serialize(&self.value, writer)
+    }
+}
+
+impl<T: BorshDeserialize> BorshDeserialize for UniqueRef<T> {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        Ok(UniqueRef {
+            pk: Binary(Vec::<u8>::deserialize_reader(reader)?),
+            value: T::deserialize_reader(reader)?,
+        })
+    }
+}
+
 /// UniqueIndex stores (namespace, index_name, idx_value) -> {key, value}
 /// Allows one value per index (i.e. unique) and copies pk and data
 /// The optional PK type defines the type of Primary Key deserialization.
-pub struct UniqueIndex<'a, IK, T, PK = ()> {
+/// The stored copies are encoded with `Ser`.
+pub struct UniqueIndex<'a, IK, T, PK = (), Ser = Json> {
     index: fn(&T) -> IK,
-    idx_map: Map<'a, IK, UniqueRef<T>>,
+    idx_map: Map<'a, IK, UniqueRef<T>, Ser>,
     idx_namespace: &'a [u8],
     phantom: PhantomData<PK>,
 }
 
-impl<'a, IK, T, PK> UniqueIndex<'a, IK, T, PK> {
+impl<'a, IK, T, PK, Ser> UniqueIndex<'a, IK, T, PK, Ser> {
     // TODO: make this a const fn
     /// Create a new UniqueIndex
     ///
@@ -60,12 +77,17 @@ impl<'a, IK, T, PK> UniqueIndex<'a, IK, T, PK> {
             phantom: PhantomData,
         }
     }
+
+    pub fn namespace(&self) -> &'a [u8] {
+        self.idx_namespace
+    }
 }
 
-impl<'a, IK, T, PK> Index<T> for UniqueIndex<'a, IK, T, PK>
+impl<'a, IK, T, PK, Ser> Index<T, Ser> for UniqueIndex<'a, IK, T, PK, Ser>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Clone,
     IK: PrimaryKey<'a>,
+    Ser: Serde<UniqueRef<T>>,
 {
     fn save(&self, store: &mut dyn Storage, pk: &[u8], data: &T) -> StdResult<()> {
         let idx = (self.index)(data);
@@ -90,36 +112,37 @@ where
     }
 }
 
-fn deserialize_unique_v<T: DeserializeOwned>(kv: Record) -> StdResult<Record<T>> {
+fn deserialize_unique_v<T, Ser: Serde<UniqueRef<T>>>(kv: Record) -> StdResult<Record<T>> {
     let (_, v) = kv;
-    let t = from_slice::<UniqueRef<T>>(&v)?;
+    let t = Ser::deserialize(&v)?;
     Ok((t.pk.0, t.value))
 }
 
-fn deserialize_unique_kv<K: KeyDeserialize, T: DeserializeOwned>(
+fn deserialize_unique_kv<K: KeyDeserialize, T, Ser: Serde<UniqueRef<T>>>(
     kv: Record,
 ) -> StdResult<(K::Output, T)> {
     let (_, v) = kv;
-    let t = from_slice::<UniqueRef<T>>(&v)?;
+    let t = Ser::deserialize(&v)?;
     Ok((K::from_vec(t.pk.0)?, t.value))
 }
 
-impl<'a, IK, T, PK> UniqueIndex<'a, IK, T, PK>
+impl<'a, IK, T, PK, Ser> UniqueIndex<'a, IK, T, PK, Ser>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Clone,
     IK: PrimaryKey<'a>,
+    Ser: Serde<T> + Serde<UniqueRef<T>>,
 {
     pub fn index_key(&self, k: IK) -> Vec<u8> {
         k.joined_key()
     }
 
-    fn no_prefix_raw(&self) -> Prefix<Vec<u8>, T, IK> {
+    fn no_prefix_raw(&self) -> Prefix<Vec<u8>, T, IK, Ser> {
         Prefix::with_deserialization_functions(
             self.idx_namespace,
             &[],
             &[],
-            |_, _, kv| deserialize_unique_v(kv),
-            |_, _, kv| deserialize_unique_v(kv),
+            |_, _, kv| deserialize_unique_v::<_, Ser>(kv),
+            |_, _, kv| deserialize_unique_v::<_, Ser>(kv),
         )
     }
 
@@ -134,10 +157,11 @@ where
 }
 
 // short-cut for simple keys, rather than .prefix(()).range_raw(...)
-impl<'a, IK, T, PK> UniqueIndex<'a, IK, T, PK>
+impl<'a, IK, T, PK, Ser> UniqueIndex<'a, IK, T, PK, Ser>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Clone,
     IK: PrimaryKey<'a>,
+    Ser: Serde<T> + Serde<UniqueRef<T>>,
 {
     // I would prefer not to copy code from Prefix, but no other way
     // with lifetimes (create Prefix inside function and return ref = no no)
@@ -166,11 +190,12 @@ where
 }
 
 #[cfg(feature = "iterator")]
-impl<'a, IK, T, PK> UniqueIndex<'a, IK, T, PK>
+impl<'a, IK, T, PK, Ser> UniqueIndex<'a, IK, T, PK, Ser>
 where
     PK: PrimaryKey<'a> + KeyDeserialize,
-    T: Serialize + DeserializeOwned + Clone,
+    T: Clone,
     IK: PrimaryKey<'a>,
+    Ser: Serde<T> + Serde<UniqueRef<T>>,
 {
     /// While `range` over a `prefix` fixes the prefix to one element and iterates over the
     /// remaining, `prefix_range` accepts bounds for the lowest and highest elements of the
@@ -191,9 +216,10 @@ where
         IK: 'c,
         PK: 'c,
         PK::Output: 'static,
+        Ser: 'c,
     {
         let mapped = namespaced_prefix_range(store, self.idx_namespace, min, max, order)
-            .map(deserialize_kv::<PK, T>);
+            .map(deserialize_unique_kv::<PK, T, Ser>);
         Box::new(mapped)
     }
 
@@ -225,33 +251,33 @@ where
         self.no_prefix().keys(store, min, max, order)
     }
 
-    pub fn prefix(&self, p: IK::Prefix) -> Prefix<PK, T, IK::Suffix> {
+    pub fn prefix(&self, p: IK::Prefix) -> Prefix<PK, T, IK::Suffix, Ser> {
         Prefix::with_deserialization_functions(
             self.idx_namespace,
             &p.prefix(),
             &[],
-            |_, _, kv| deserialize_unique_kv::<PK, _>(kv),
-            |_, _, kv| deserialize_unique_v(kv),
+            |_, _, kv| deserialize_unique_kv::<PK, _, Ser>(kv),
+            |_, _, kv| deserialize_unique_v::<_, Ser>(kv),
         )
     }
 
-    pub fn sub_prefix(&self, p: IK::SubPrefix) -> Prefix<PK, T, IK::SuperSuffix> {
+    pub fn sub_prefix(&self, p: IK::SubPrefix) -> Prefix<PK, T, IK::SuperSuffix, Ser> {
         Prefix::with_deserialization_functions(
             self.idx_namespace,
             &p.prefix(),
             &[],
-            |_, _, kv| deserialize_unique_kv::<PK, _>(kv),
-            |_, _, kv| deserialize_unique_v(kv),
+            |_, _, kv| deserialize_unique_kv::<PK, _, Ser>(kv),
+            |_, _, kv| deserialize_unique_v::<_, Ser>(kv),
         )
     }
 
-    fn no_prefix(&self) -> Prefix<PK, T, IK> {
+    fn no_prefix(&self) -> Prefix<PK, T, IK, Ser> {
         Prefix::with_deserialization_functions(
             self.idx_namespace,
             &[],
             &[],
-            |_, _, kv| deserialize_unique_kv::<PK, _>(kv),
-            |_, _, kv| deserialize_unique_v(kv),
+            |_, _, kv| deserialize_unique_kv::<PK, _, Ser>(kv),
+            |_, _, kv| deserialize_unique_v::<_, Ser>(kv),
         )
     }
 }
@@ -0,0 +1,730 @@
+//! Helpers to make sense of the raw keys and values of a contract's storage while debugging.
+//!
+//! Any raw key can be split into its length-prefixed namespaces with [`decode_key`]. This is a
+//! best guess, since keys do not record which collection wrote them. A [`Layout`] knows the
+//! collections of a contract, so it can tell which one every key belongs to, and decode the
+//! values through the `Serde` they were stored with.
+//!
+//! A `Layout` can hold `Item`, `Map`, `AppendStore`, `DequeStore`, `AppendStoreMap` and
+//! `DequeStoreMap` collections, and with the `iterator` feature `IndexedMap`, `MultiIndex` and
+//! `UniqueIndex` as well. The other collections (the snapshot collections, `LinkedMap`,
+//! `LinkedSet`, `BinaryHeapStore`, `Sequence`, `Counter` and `Accumulator`) can't be registered
+//! yet, so their keys are decoded with the best guess and their values are shown as raw bytes.
+//!
+//! ```rust
+//! # use cosmwasm_std::testing::MockStorage;
+//! use secret_storage_plus::inspect::Layout;
+//! use secret_storage_plus::{AppendStore, Item, Map};
+//!
+//! const CONFIG: Item<String> = Item::new("config");
+//! const BALANCES: Map<&str, u64> = Map::new("balances");
+//! const HISTORY: AppendStore<u64> = AppendStore::new("history");
+//!
+//! let layout = Layout::new().item(&CONFIG).map(&BALANCES).append_store(&HISTORY);
+//!
+//! # let mut store = MockStorage::new();
+//! BALANCES.save(&mut store, "alice", &5).unwrap();
+//! let key = BALANCES.key("alice");
+//! assert_eq!(
+//!     layout.decode_key(&key).to_string(),
+//!     r#"map "balances" ["alice"]"#,
+//! );
+//! ```
+use std::convert::TryInto;
+use std::fmt;
+
+#[cfg(feature = "iterator")]
+use cosmwasm_std::{Order, Storage};
+
+use crate::helpers::encode_length;
+use crate::keys::PrimaryKey;
+use crate::{
+    append_store, deque_store, AppendStore, AppendStoreMap, DequeStore, DequeStoreMap, Item, Map,
+    Serde,
+};
+#[cfg(feature = "iterator")]
+use crate::{IndexList, IndexedMap, Json, MultiIndex, UniqueIndex, UniqueRef};
+
+/// The kind of collection a key belongs to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CollectionKind {
+    Item,
+    Map,
+    AppendStore,
+    DequeStore,
+    AppendStoreMap,
+    DequeStoreMap,
+    MultiIndex,
+    UniqueIndex,
+    Unknown,
+}
+
+impl fmt::Display for CollectionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            CollectionKind::Item => "item",
+            CollectionKind::Map => "map",
+            CollectionKind::AppendStore => "append_store",
+            CollectionKind::DequeStore => "deque_store",
+            CollectionKind::AppendStoreMap => "append_store_map",
+            CollectionKind::DequeStoreMap => "deque_store_map",
+            CollectionKind::MultiIndex => "multi_index",
+            CollectionKind::UniqueIndex => "unique_index",
+            CollectionKind::Unknown => "unknown",
+        };
+        f.write_str(name)
+    }
+}
+
+/// One part of a key, after its namespace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Segment {
+    /// A key segment, as written by `PrimaryKey`
+    Key(Vec<u8>),
+    /// The position of an element in an `AppendStore` or `DequeStore`, or one of a store map
+    Index(u32),
+    /// The length of an `AppendStore` or `DequeStore`, or of one of a store map
+    Len,
+    /// The offset of a `DequeStore`
+    Offset,
+}
+
+impl fmt::Display for Segment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Segment::Key(key) => write_bytes(f, key),
+            Segment::Index(pos) => write!(f, "{}", pos),
+            Segment::Len => f.write_str("len"),
+            Segment::Offset => f.write_str("off"),
+        }
+    }
+}
+
+/// A raw storage key, split into the namespace of its collection and the segments after it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodedKey {
+    pub kind: CollectionKind,
+    pub namespace: Vec<u8>,
+    pub segments: Vec<Segment>,
+}
+
+impl fmt::Display for DecodedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ", self.kind)?;
+        write_bytes(f, &self.namespace)?;
+        if !self.segments.is_empty() {
+            f.write_str(" [")?;
+            for (i, segment) in self.segments.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+                write!(f, "{}", segment)?;
+            }
+            f.write_str("]")?;
+        }
+        Ok(())
+    }
+}
+
+/// A decoded key and its value, formatted with `Debug`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub key: DecodedKey,
+    pub value: String,
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} = {}", self.key, self.value)
+    }
+}
+
+/// Splits a raw key into its length-prefixed namespaces, as written by `Map` and the other
+/// collections built on `namespaces_with_key`.
+///
+/// The first namespace becomes the namespace of the decoded key, and the rest, including the
+/// bytes left after the last namespace, become its segments. Keys that do not start with a
+/// namespace, such as those of an `Item` or an `AppendStore`, are returned whole, with an
+/// unknown kind.
+///
+/// Since keys do not record their own layout, this is only a best guess: for example a key
+/// segment made of fixed-width integers may also look like a namespace.
+pub fn decode_key(key: &[u8]) -> DecodedKey {
+    let (mut namespaces, rest) = split_namespaces(key);
+    if namespaces.is_empty() {
+        return DecodedKey {
+            kind: CollectionKind::Unknown,
+            namespace: key.to_vec(),
+            segments: vec![],
+        };
+    }
+    let namespace = namespaces.remove(0);
+    DecodedKey {
+        kind: CollectionKind::Map,
+        namespace,
+        segments: key_segments(namespaces, rest),
+    }
+}
+
+/// The collections of a contract, used to decode its keys and values.
+#[derive(Clone, Debug, Default)]
+pub struct Layout {
+    collections: Vec<Collection>,
+}
+
+#[derive(Clone, Debug)]
+struct Collection {
+    kind: CollectionKind,
+    /// the storage key of an item, or the prefix of every key of the other collections
+    prefix: Vec<u8>,
+    decode: fn(&[u8]) -> String,
+}
+
+impl Layout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers an `Item`
+    pub fn item<T, Ser>(mut self, item: &Item<T, Ser>) -> Self
+    where
+        T: fmt::Debug,
+        Ser: Serde<T>,
+    {
+        self.collections.push(Collection {
+            kind: CollectionKind::Item,
+            prefix: item.as_slice().to_vec(),
+            decode: decode_value::<T, Ser>,
+        });
+        self
+    }
+
+    /// Registers a `Map`
+    pub fn map<'a, K, T, Ser>(self, map: &Map<'a, K, T, Ser>) -> Self
+    where
+        K: PrimaryKey<'a>,
+        T: fmt::Debug,
+        Ser: Serde<T>,
+    {
+        self.namespaced(CollectionKind::Map, map.namespace(), decode_value::<T, Ser>)
+    }
+
+    /// Registers the primary map of an `IndexedMap`. Its indexes are registered on their own,
+    /// with `multi_index` and `unique_index`.
+    #[cfg(feature = "iterator")]
+    pub fn indexed_map<'a, K, T, I, Ser>(self, map: &IndexedMap<'a, K, T, I, Ser>) -> Self
+    where
+        K: PrimaryKey<'a>,
+        T: Clone + fmt::Debug,
+        I: IndexList<T, Ser>,
+        Ser: Serde<T>,
+    {
+        self.namespaced(CollectionKind::Map, map.namespace(), decode_value::<T, Ser>)
+    }
+
+    /// Registers a `MultiIndex`, whose values are the length of the primary key
+    #[cfg(feature = "iterator")]
+    pub fn multi_index<IK, T, PK, Ser>(self, index: &MultiIndex<IK, T, PK, Ser>) -> Self
+    where
+        T: Clone,
+        Ser: Serde<T>,
+    {
+        self.namespaced(
+            CollectionKind::MultiIndex,
+            index.namespace(),
+            decode_value::<u32, Json>,
+        )
+    }
+
+    /// Registers a `UniqueIndex`, whose values are the primary key and a copy of the value
+    #[cfg(feature = "iterator")]
+    pub fn unique_index<IK, T, PK, Ser>(self, index: &UniqueIndex<IK, T, PK, Ser>) -> Self
+    where
+        T: fmt::Debug,
+        Ser: Serde<UniqueRef<T>>,
+    {
+        self.namespaced(
+            CollectionKind::UniqueIndex,
+            index.namespace(),
+            decode_value::<UniqueRef<T>, Ser>,
+        )
+    }
+
+    /// Registers an `AppendStore`, including any suffix it was created with
+    pub fn append_store<T, Ser>(mut self, store: &AppendStore<T, Ser>) -> Self
+    where
+        T: fmt::Debug,
+        Ser: Serde<T>,
+    {
+        self.collections.push(Collection {
+            kind: CollectionKind::AppendStore,
+            prefix: store.as_slice().to_vec(),
+            decode: decode_value::<T, Ser>,
+        });
+        self
+    }
+
+    /// Registers a `DequeStore`, including any suffix it was created with
+    pub fn deque_store<T, Ser>(mut self, store: &DequeStore<T, Ser>) -> Self
+    where
+        T: fmt::Debug,
+        Ser: Serde<T>,
+    {
+        self.collections.push(Collection {
+            kind: CollectionKind::DequeStore,
+            prefix: store.as_slice().to_vec(),
+            decode: decode_value::<T, Ser>,
+        });
+        self
+    }
+
+    /// Registers an `AppendStoreMap`, with the stores of all its keys
+    pub fn append_store_map<K, T, Ser>(self, map: &AppendStoreMap<K, T, Ser>) -> Self
+    where
+        T: fmt::Debug,
+        Ser: Serde<T>,
+    {
+        self.namespaced(
+            CollectionKind::AppendStoreMap,
+            map.namespace(),
+            decode_value::<T, Ser>,
+        )
+    }
+
+    /// Registers a `DequeStoreMap`, with the stores of all its keys
+    pub fn deque_store_map<K, T, Ser>(self, map: &DequeStoreMap<K, T, Ser>) -> Self
+    where
+        T: fmt::Debug,
+        Ser: Serde<T>,
+    {
+        self.namespaced(
+            CollectionKind::DequeStoreMap,
+            map.namespace(),
+            decode_value::<T, Ser>,
+        )
+    }
+
+    /// Registers a collection whose keys all start with its length-prefixed namespace
+    fn namespaced(
+        mut self,
+        kind: CollectionKind,
+        namespace: &[u8],
+        decode: fn(&[u8]) -> String,
+    ) -> Self {
+        self.collections.push(Collection {
+            kind,
+            prefix: [&encode_length(namespace)[..], namespace].concat(),
+            decode,
+        });
+        self
+    }
+
+    /// Decodes a raw key, using the registered collection it belongs to if there is one, and
+    /// falling back to [`decode_key`] otherwise.
+    pub fn decode_key(&self, key: &[u8]) -> DecodedKey {
+        self.find(key)
+            .map(|(collection, segments)| DecodedKey {
+                kind: collection.kind,
+                namespace: collection.namespace(),
+                segments,
+            })
+            .unwrap_or_else(|| decode_key(key))
+    }
+
+    /// Decodes a raw key and its value. Values of unregistered collections are shown as raw
+    /// bytes.
+    pub fn decode(&self, key: &[u8], value: &[u8]) -> Entry {
+        match self.find(key) {
+            Some((collection, segments)) => {
+                let value = match segments.last() {
+                    Some(Segment::Len) | Some(Segment::Offset) => decode_u32(value),
+                    _ => (collection.decode)(value),
+                };
+                Entry {
+                    key: DecodedKey {
+                        kind: collection.kind,
+                        namespace: collection.namespace(),
+                        segments,
+                    },
+                    value,
+                }
+            }
+            None => Entry {
+                key: decode_key(key),
+                value: Bytes(value).to_string(),
+            },
+        }
+    }
+
+    /// Decodes every key and value in `storage`, in ascending order of their raw keys
+    #[cfg(feature = "iterator")]
+    pub fn dump(&self, storage: &dyn Storage) -> Vec<Entry> {
+        storage
+            .range(None, None, Order::Ascending)
+            .map(|(key, value)| self.decode(&key, &value))
+            .collect()
+    }
+
+    /// Pretty-prints every key and value in `storage`, one per line
+    #[cfg(feature = "iterator")]
+    pub fn pretty_print(&self, storage: &dyn Storage) -> String {
+        self.dump(storage)
+            .iter()
+            .map(|entry| format!("{}\n", entry))
+            .collect()
+    }
+
+    /// Finds the collection `key` belongs to, preferring the most specific one, and returns it
+    /// with the segments of the key after its prefix.
+    fn find(&self, key: &[u8]) -> Option<(&Collection, Vec<Segment>)> {
+        self.collections
+            .iter()
+            .filter_map(|collection| Some((collection, collection.segments(key)?)))
+            .max_by_key(|(collection, _)| {
+                (
+                    collection.kind == CollectionKind::Item,
+                    collection.prefix.len(),
+                )
+            })
+    }
+}
+
+impl Collection {
+    fn namespace(&self) -> Vec<u8> {
+        match self.kind {
+            CollectionKind::Item | CollectionKind::AppendStore | CollectionKind::DequeStore => {
+                self.prefix.clone()
+            }
+            _ => self.prefix[2..].to_vec(),
+        }
+    }
+
+    /// Returns the segments of `key` if it belongs to this collection
+    fn segments(&self, key: &[u8]) -> Option<Vec<Segment>> {
+        if self.kind == CollectionKind::Item {
+            return (key == self.prefix.as_slice()).then(Vec::new);
+        }
+        let rest = key.strip_prefix(self.prefix.as_slice())?;
+        match self.kind {
+            CollectionKind::AppendStore | CollectionKind::DequeStore => {
+                Some(vec![self.store_segment(rest)?])
+            }
+            CollectionKind::AppendStoreMap | CollectionKind::DequeStoreMap => {
+                self.store_map_segments(rest)
+            }
+            _ => {
+                let (namespaces, rest) = split_namespaces(rest);
+                Some(key_segments(namespaces, rest))
+            }
+        }
+    }
+
+    /// Decodes what an `AppendStore` or `DequeStore` appends to its prefix
+    fn store_segment(&self, rest: &[u8]) -> Option<Segment> {
+        let is_deque = matches!(
+            self.kind,
+            CollectionKind::DequeStore | CollectionKind::DequeStoreMap
+        );
+        if rest == append_store::LEN_KEY {
+            Some(Segment::Len)
+        } else if is_deque && rest == deque_store::OFFSET_KEY {
+            Some(Segment::Offset)
+        } else {
+            Some(Segment::Index(u32::from_be_bytes(rest.try_into().ok()?)))
+        }
+    }
+
+    /// Splits the key of a store map into its length-prefixed key segments, and what the store
+    /// of that key appends to them
+    fn store_map_segments(&self, rest: &[u8]) -> Option<Vec<Segment>> {
+        // the length and offset keys are shorter than a position, so they are tried first
+        let ends = [append_store::LEN_KEY.len(), 4];
+        ends.iter().find_map(|&end| {
+            let split = rest.len().checked_sub(end)?;
+            let (namespaces, left) = split_namespaces(&rest[..split]);
+            if namespaces.is_empty() || !left.is_empty() {
+                return None;
+            }
+            let segment = self.store_segment(&rest[split..])?;
+            let mut segments: Vec<Segment> = namespaces.into_iter().map(Segment::Key).collect();
+            segments.push(segment);
+            Some(segments)
+        })
+    }
+}
+
+/// Splits the length-prefixed namespaces at the start of `data` from the bytes after them
+fn split_namespaces(mut data: &[u8]) -> (Vec<Vec<u8>>, &[u8]) {
+    let mut namespaces = vec![];
+    while data.len() >= 2 {
+        let len = u16::from_be_bytes([data[0], data[1]]) as usize;
+        match data.get(2..2 + len) {
+            Some(namespace) => {
+                namespaces.push(namespace.to_vec());
+                data = &data[2 + len..];
+            }
+            None => break,
+        }
+    }
+    (namespaces, data)
+}
+
+fn key_segments(namespaces: Vec<Vec<u8>>, rest: &[u8]) -> Vec<Segment> {
+    let mut segments: Vec<Segment> = namespaces.into_iter().map(Segment::Key).collect();
+    if !rest.is_empty() || segments.is_empty() {
+        segments.push(Segment::Key(rest.to_vec()));
+    }
+    segments
+}
+
+fn decode_value<T: fmt::Debug, Ser: Serde<T>>(data: &[u8]) -> String {
+    match Ser::deserialize(data) {
+        Ok(value) => format!("{:?}", value),
+        Err(err) => format!("<{}> {}", err, Bytes(data)),
+    }
+}
+
+fn decode_u32(data: &[u8]) -> String {
+    match data.try_into() {
+        Ok(bytes) => u32::from_be_bytes(bytes).to_string(),
+        Err(_) => Bytes(data).to_string(),
+    }
+}
+
+/// Displays bytes as a quoted string if they are printable, and in hex otherwise
+struct Bytes<'b>(&'b [u8]);
+
+impl fmt::Display for Bytes<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_bytes(f, self.0)
+    }
+}
+
+fn write_bytes(f: &mut fmt::Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+    match std::str::from_utf8(bytes) {
+        Ok(s) if !s.chars().any(char::is_control) => write!(f, "{:?}", s),
+        _ => {
+            f.write_str("0x")?;
+            bytes.iter().try_for_each(|b| write!(f, "{:02x}", b))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::Bincode2;
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    struct Config {
+        owner: String,
+    }
+
+    const CONFIG: Item<Config, Bincode2> = Item::new("config");
+    const ALLOWANCES: Map<(&str, &str), u64> = Map::new("allow");
+    const HISTORY: AppendStore<u32> = AppendStore::new("history");
+    const QUEUE: DequeStore<u32> = DequeStore::new("queue");
+
+    fn layout() -> Layout {
+        Layout::new()
+            .item(&CONFIG)
+            .map(&ALLOWANCES)
+            .append_store(&HISTORY)
+            .deque_store(&QUEUE)
+    }
+
+    #[test]
+    fn decode_key_without_layout() {
+        let key = ALLOWANCES.key(("alice", "bob"));
+        assert_eq!(
+            decode_key(&key),
+            DecodedKey {
+                kind: CollectionKind::Map,
+                namespace: b"allow".to_vec(),
+                segments: vec![
+                    Segment::Key(b"alice".to_vec()),
+                    Segment::Key(b"bob".to_vec())
+                ],
+            }
+        );
+
+        let decoded = decode_key(b"config");
+        assert_eq!(decoded.kind, CollectionKind::Unknown);
+        assert_eq!(decoded.namespace, b"config");
+        assert_eq!(decoded.to_string(), r#"unknown "config""#);
+    }
+
+    #[test]
+    fn decode_key_with_layout() {
+        let layout = layout();
+        assert_eq!(layout.decode_key(b"config").to_string(), r#"item "config""#);
+        assert_eq!(
+            layout
+                .decode_key(&ALLOWANCES.key(("alice", "bob")))
+                .to_string(),
+            r#"map "allow" ["alice", "bob"]"#
+        );
+        assert_eq!(
+            layout.decode_key(b"historylen").to_string(),
+            r#"append_store "history" [len]"#
+        );
+        assert_eq!(
+            layout.decode_key(b"history\x00\x00\x01\x00").to_string(),
+            r#"append_store "history" [256]"#
+        );
+        assert_eq!(
+            layout.decode_key(b"queueoff").to_string(),
+            r#"deque_store "queue" [off]"#
+        );
+        // unregistered keys fall back to the best guess
+        assert_eq!(
+            layout.decode_key(b"\x00\x04misc\x01\x02").to_string(),
+            r#"map "misc" [0x0102]"#
+        );
+    }
+
+    #[test]
+    fn items_win_over_stores_with_the_same_prefix() {
+        let layout = Layout::new()
+            .append_store(&HISTORY)
+            .item(&Item::<u32>::new("historylen"));
+        assert_eq!(layout.decode_key(b"historylen").kind, CollectionKind::Item);
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn dump_state() {
+        use cosmwasm_std::testing::MockStorage;
+
+        let mut store = MockStorage::new();
+        CONFIG
+            .save(
+                &mut store,
+                &Config {
+                    owner: "admin".to_string(),
+                },
+            )
+            .unwrap();
+        ALLOWANCES.save(&mut store, ("alice", "bob"), &10).unwrap();
+        HISTORY.push(&mut store, &7).unwrap();
+        QUEUE.push_front(&mut store, &1).unwrap();
+        cosmwasm_std::Storage::set(&mut store, b"raw", b"\x01\x02");
+
+        let dump = layout().pretty_print(&store);
+        assert_eq!(
+            dump,
+            [
+                "map \"allow\" [\"alice\", \"bob\"] = 10",
+                "item \"config\" = Config { owner: \"admin\" }",
+                "append_store \"history\" [0] = 7",
+                "append_store \"history\" [len] = 1",
+                "deque_store \"queue\" [len] = 1",
+                "deque_store \"queue\" [off] = 4294967295",
+                "deque_store \"queue\" [4294967295] = 1",
+                "unknown \"raw\" = 0x0102",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn decode_store_map_keys() {
+        let events: AppendStoreMap<(&str, u8), u32> = AppendStoreMap::new("events");
+        let queues: DequeStoreMap<&str, u32> = DequeStoreMap::new("queues");
+        let layout = Layout::new()
+            .append_store_map(&events)
+            .deque_store_map(&queues);
+
+        let store = events.key(("alice", 1));
+        assert_eq!(
+            layout
+                .decode_key(&[store.as_slice(), b"len"].concat())
+                .to_string(),
+            r#"append_store_map "events" ["alice", 0x01, len]"#
+        );
+        assert_eq!(
+            layout
+                .decode_key(&[store.as_slice(), &2u32.to_be_bytes()].concat())
+                .to_string(),
+            r#"append_store_map "events" ["alice", 0x01, 2]"#
+        );
+
+        let store = queues.key("bob");
+        assert_eq!(
+            layout
+                .decode_key(&[store.as_slice(), b"off"].concat())
+                .to_string(),
+            r#"deque_store_map "queues" ["bob", off]"#
+        );
+        let entry = layout.decode(&[store.as_slice(), &7u32.to_be_bytes()].concat(), b"5");
+        assert_eq!(
+            entry.to_string(),
+            r#"deque_store_map "queues" ["bob", 7] = 5"#
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn dump_indexed_map() {
+        use cosmwasm_std::testing::MockStorage;
+
+        use crate::Index;
+
+        struct Indexes<'a> {
+            owner: MultiIndex<'a, String, Config, &'a str, Bincode2>,
+            unique_owner: UniqueIndex<'a, String, Config, &'a str, Bincode2>,
+        }
+
+        impl<'a> IndexList<Config, Bincode2> for Indexes<'a> {
+            fn get_indexes(
+                &'_ self,
+            ) -> Box<dyn Iterator<Item = &'_ dyn Index<Config, Bincode2>> + '_> {
+                let v: Vec<&dyn Index<Config, Bincode2>> = vec![&self.owner, &self.unique_owner];
+                Box::new(v.into_iter())
+            }
+        }
+
+        let indexes = Indexes {
+            owner: MultiIndex::new(|c| c.owner.clone(), "cfg", "cfg__owner"),
+            unique_owner: UniqueIndex::new(|c| c.owner.clone(), "cfg__unique"),
+        };
+        let map = IndexedMap::<&str, Config, Indexes, Bincode2>::new("cfg", indexes);
+        let layout = Layout::new()
+            .indexed_map(&map)
+            .multi_index(&map.idx.owner)
+            .unique_index(&map.idx.unique_owner);
+
+        let mut store = MockStorage::new();
+        map.save(
+            &mut store,
+            "a",
+            &Config {
+                owner: "admin".to_string(),
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            layout.pretty_print(&store),
+            [
+                "map \"cfg\" [\"a\"] = Config { owner: \"admin\" }",
+                "multi_index \"cfg__owner\" [\"admin\", \"a\"] = 1",
+                "unique_index \"cfg__unique\" [\"admin\"] = \
+                 UniqueRef { pk: Binary(61), value: Config { owner: \"admin\" } }",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn undecodable_values_are_shown_raw() {
+        let entry = layout().decode(b"config", b"\xff");
+        assert_eq!(entry.key.kind, CollectionKind::Item);
+        assert!(entry.value.ends_with("0xff"), "{}", entry.value);
+    }
+}
@@ -1,19 +1,17 @@
-use serde::de::DeserializeOwned;
-use serde::Serialize;
+use std::any::type_name;
 use std::marker::PhantomData;
 
 use cosmwasm_std::{
-    Addr, CustomQuery, QuerierWrapper, StdError, StdResult, Storage, WasmQuery,
+    Addr, CustomQuery, QuerierWrapper, StdError, StdResult, Storage,
 };
 
-use crate::{helpers::{may_deserialize, must_deserialize}, Serde, Json};
+use crate::{helpers::{may_deserialize, must_deserialize, query_deserialize}, Serde, Json};
+use crate::serialization::{may_load_and_upgrade, Upgrade, Versioned};
 
 /// Item stores one typed item at the given key.
 /// This is an analog of Singleton.
 /// It functions the same way as Path does but doesn't use a Vec and thus has a const fn constructor.
-pub struct Item<'a, T, Ser = Json>
-    where Ser: Serde,
-{
+pub struct Item<'a, T, Ser = Json> {
     // this is full key - no need to length-prefix it, we only store one item
     storage_key: &'a [u8],
     // see https://doc.rust-lang.org/std/marker/struct.PhantomData.html#unused-type-parameters for why this is needed
@@ -21,10 +19,7 @@ pub struct Item<'a, T, Ser = Json>
     serialization_type: PhantomData<*const Ser>,
 }
 
-impl<'a, T, Ser> Item<'a, T, Ser>
-where
-    Ser: Serde,
-{
+impl<'a, T, Ser> Item<'a, T, Ser> {
     pub const fn new(storage_key: &'a str) -> Self {
         Item {
             storage_key: storage_key.as_bytes(),
@@ -36,8 +31,7 @@ where
 
 impl<'a, T, Ser> Item<'a, T, Ser>
 where
-    T: Serialize + DeserializeOwned,
-    Ser: Serde,
+    Ser: Serde<T>,
 {
     // this gets the path of the data to use elsewhere
     pub fn as_slice(&self) -> &[u8] {
@@ -85,6 +79,7 @@ where
 
     /// If you import the proper Item from the remote contract, this will let you read the data
     /// from a remote contract in a type-safe way using WasmQuery::RawQuery.
+    /// The data is decoded with the same `Ser` it was stored with.
     ///
     /// Note that we expect an Item to be set, and error if there is no data there
     pub fn query<Q: CustomQuery>(
@@ -93,31 +88,47 @@ where
         remote_contract: Addr,
         remote_contract_code_hash: String,
     ) -> StdResult<T> {
-        let request = WasmQuery::Smart {
-            contract_addr: remote_contract.into(),
-            code_hash: remote_contract_code_hash,
-            msg: self.storage_key.into(),
-        };
-        querier.query(&request.into())
+        query_deserialize::<T, Ser, Q>(
+            querier,
+            remote_contract,
+            remote_contract_code_hash,
+            self.storage_key,
+        )?
+        .ok_or_else(|| StdError::not_found(type_name::<T>()))
+    }
+}
+
+impl<'a, T, Inner> Item<'a, T, Versioned<Inner>>
+where
+    T: Upgrade<Inner>,
+    Inner: Serde<T>,
+{
+    /// may_load_and_upgrade works like may_load, but if the data was stored under an older
+    /// version it is written back in the current one, so the upgrade only runs once.
+    pub fn may_load_and_upgrade(&self, store: &mut dyn Storage) -> StdResult<Option<T>> {
+        may_load_and_upgrade::<T, Inner>(store, self.storage_key)
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::Bincode2;
+    use crate::{Bincode2, Borsh};
+    use crate::helpers::mock_remote_querier;
 
     use super::*;
     use cosmwasm_std::{
         to_vec,
-        testing::MockStorage
+        testing::MockStorage,
+        Empty,
     };
     use rstest::*;
     use rstest_reuse::{self, *};
+    use borsh::{BorshDeserialize, BorshSerialize};
     use serde::{Deserialize, Serialize};
 
     use cosmwasm_std::{OverflowError, OverflowOperation, StdError};
 
-    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    #[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
     struct Config {
         pub owner: String,
         pub max_tokens: i32,
@@ -126,16 +137,18 @@ mod test {
     // note const constructor rather than 2 funcs with Singleton
     const CONFIG: Item<Config> = Item::new("config");
     const B_CONFIG: Item<Config, Bincode2> = Item::new("config");
+    const BR_CONFIG: Item<Config, Borsh> = Item::new("config");
 
     #[template]
     #[rstest]
     #[case(CONFIG)]
     #[case(B_CONFIG)]
-    fn serialization(#[case] config: Item<Config, impl Serde>) { }
+    #[case(BR_CONFIG)]
+    fn serialization(#[case] config: Item<Config, impl Serde<Config>>) { }
 
     #[apply(serialization)]
     fn save_and_load(
-        #[case] config: Item<Config, impl Serde>,
+        #[case] config: Item<Config, impl Serde<Config>>,
     ) {
         let mut store = MockStorage::new();
 
@@ -153,7 +166,7 @@ mod test {
 
     #[apply(serialization)]
     fn remove_works(
-        #[case] config: Item<Config, impl Serde>,
+        #[case] config: Item<Config, impl Serde<Config>>,
     ) {
         let mut store = MockStorage::new();
 
@@ -174,6 +187,29 @@ mod test {
         assert_eq!(None, config.may_load(&store).unwrap());
     }
 
+    #[apply(serialization)]
+    fn query_works(
+        #[case] config: Item<Config, impl Serde<Config>>,
+    ) {
+        let mut store = MockStorage::new();
+        let cfg = Config {
+            owner: "admin".to_string(),
+            max_tokens: 1234,
+        };
+        config.save(&mut store, &cfg).unwrap();
+
+        let querier = mock_remote_querier(store);
+        let querier = QuerierWrapper::<Empty>::new(&querier);
+        let loaded = config
+            .query(&querier, Addr::unchecked("remote"), "hash".to_string())
+            .unwrap();
+        assert_eq!(cfg, loaded);
+
+        let missing = Item::<Config>::new("missing")
+            .query(&querier, Addr::unchecked("remote"), "hash".to_string());
+        assert!(matches!(missing, Err(StdError::NotFound { .. })));
+    }
+
     #[test]
     fn isolated_reads() {
         let mut store = MockStorage::new();
@@ -209,9 +245,165 @@ mod test {
         assert_eq!(other_reader.may_load(&store).unwrap(), None);
     }
 
+    #[test]
+    fn isolated_reads_borsh() {
+        let mut store = MockStorage::new();
+
+        let config = BR_CONFIG;
+        let cfg = Config {
+            owner: "admin".to_string(),
+            max_tokens: 1234,
+        };
+        config.save(&mut store, &cfg).unwrap();
+
+        let reader = Item::<Config, Borsh>::new("config");
+        assert_eq!(cfg, reader.load(&store).unwrap());
+
+        let other_reader = Item::<Config, Borsh>::new("config2");
+        assert_eq!(other_reader.may_load(&store).unwrap(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "msgpack")]
+    fn isolated_reads_msgpack() {
+        use crate::MsgPack;
+
+        let mut store = MockStorage::new();
+
+        let config = Item::<Config, MsgPack>::new("config");
+        let cfg = Config {
+            owner: "admin".to_string(),
+            max_tokens: 1234,
+        };
+        config.save(&mut store, &cfg).unwrap();
+
+        let reader = Item::<Config, MsgPack>::new("config");
+        assert_eq!(cfg, reader.load(&store).unwrap());
+
+        let other_reader = Item::<Config, MsgPack>::new("config2");
+        assert_eq!(other_reader.may_load(&store).unwrap(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "cbor")]
+    fn isolated_reads_cbor() {
+        use crate::Cbor;
+
+        let mut store = MockStorage::new();
+
+        let config = Item::<Config, Cbor>::new("config");
+        let cfg = Config {
+            owner: "admin".to_string(),
+            max_tokens: 1234,
+        };
+        config.save(&mut store, &cfg).unwrap();
+
+        let reader = Item::<Config, Cbor>::new("config");
+        assert_eq!(cfg, reader.load(&store).unwrap());
+
+        let other_reader = Item::<Config, Cbor>::new("config2");
+        assert_eq!(other_reader.may_load(&store).unwrap(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "postcard")]
+    fn isolated_reads_postcard() {
+        use crate::Postcard;
+
+        let mut store = MockStorage::new();
+
+        let config = Item::<Config, Postcard>::new("config");
+        let cfg = Config {
+            owner: "admin".to_string(),
+            max_tokens: 1234,
+        };
+        config.save(&mut store, &cfg).unwrap();
+
+        let reader = Item::<Config, Postcard>::new("config");
+        assert_eq!(cfg, reader.load(&store).unwrap());
+
+        let other_reader = Item::<Config, Postcard>::new("config2");
+        assert_eq!(other_reader.may_load(&store).unwrap(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "msgpack")]
+    fn msgpack_tolerates_added_fields() {
+        use crate::MsgPack;
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct ConfigV2 {
+            pub owner: String,
+            pub max_tokens: i32,
+            #[serde(default)]
+            pub paused: bool,
+        }
+
+        let mut store = MockStorage::new();
+        let cfg = Config {
+            owner: "admin".to_string(),
+            max_tokens: 1234,
+        };
+        Item::<Config, MsgPack>::new("config").save(&mut store, &cfg).unwrap();
+
+        let upgraded = Item::<ConfigV2, MsgPack>::new("config");
+        assert_eq!(
+            ConfigV2 {
+                owner: "admin".to_string(),
+                max_tokens: 1234,
+                paused: false,
+            },
+            upgraded.load(&store).unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "cbor")]
+    fn cbor_tolerates_added_fields() {
+        use crate::Cbor;
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct ConfigV2 {
+            pub owner: String,
+            pub max_tokens: i32,
+            #[serde(default)]
+            pub paused: bool,
+        }
+
+        let mut store = MockStorage::new();
+        let cfg = Config {
+            owner: "admin".to_string(),
+            max_tokens: 1234,
+        };
+        Item::<Config, Cbor>::new("config").save(&mut store, &cfg).unwrap();
+
+        let upgraded = Item::<ConfigV2, Cbor>::new("config");
+        assert_eq!(
+            ConfigV2 {
+                owner: "admin".to_string(),
+                max_tokens: 1234,
+                paused: false,
+            },
+            upgraded.load(&store).unwrap()
+        );
+    }
+
+    #[test]
+    fn borsh_is_compatible_with_borsh_storage() {
+        let mut store = MockStorage::new();
+        let cfg = Config {
+            owner: "admin".to_string(),
+            max_tokens: 1234,
+        };
+        BR_CONFIG.save(&mut store, &cfg).unwrap();
+
+        let reader = secret_borsh_storage::BorshItem::<Config>::new("config");
+        assert_eq!(cfg, reader.load(&store).unwrap());
+    }
+
     #[apply(serialization)]
     fn update_success(
-        #[case] config: Item<Config, impl Serde>,
+        #[case] config: Item<Config, impl Serde<Config>>,
     ) {
         let mut store = MockStorage::new();
 
@@ -235,7 +427,7 @@ mod test {
 
     #[apply(serialization)]
     fn update_can_change_variable_from_outer_scope(
-        #[case] config: Item<Config, impl Serde>,
+        #[case] config: Item<Config, impl Serde<Config>>,
     ) {
         let mut store = MockStorage::new();
         let cfg = Config {
@@ -257,7 +449,7 @@ mod test {
 
     #[apply(serialization)]
     fn update_does_not_change_data_on_error(
-        #[case] config: Item<Config, impl Serde>,
+        #[case] config: Item<Config, impl Serde<Config>>,
     ) {
         let mut store = MockStorage::new();
 
@@ -283,7 +475,7 @@ mod test {
 
     #[apply(serialization)]
     fn update_supports_custom_errors(
-        #[case] config: Item<Config, impl Serde>
+        #[case] config: Item<Config, impl Serde<Config>>
     ) {
         #[derive(Debug)]
         enum MyError {
@@ -327,7 +519,7 @@ mod test {
 
     #[apply(serialization)]
     fn readme_works(
-        #[case] config: Item<Config, impl Serde>
+        #[case] config: Item<Config, impl Serde<Config>>
     ) -> StdResult<()> {
         let mut store = MockStorage::new();
 
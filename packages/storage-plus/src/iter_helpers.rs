@@ -1,26 +1,25 @@
 #![cfg(feature = "iterator")]
 
-use serde::de::DeserializeOwned;
-
 use cosmwasm_std::Record;
-use cosmwasm_std::{from_slice, StdResult};
+use cosmwasm_std::StdResult;
 
 use crate::de::KeyDeserialize;
 use crate::helpers::encode_length;
+use crate::Serde;
 
 #[allow(dead_code)]
-pub(crate) fn deserialize_v<T: DeserializeOwned>(kv: Record) -> StdResult<Record<T>> {
+pub(crate) fn deserialize_v<T, Ser: Serde<T>>(kv: Record) -> StdResult<Record<T>> {
     let (k, v) = kv;
-    let t = from_slice::<T>(&v)?;
+    let t = Ser::deserialize(&v)?;
     Ok((k, t))
 }
 
-pub(crate) fn deserialize_kv<K: KeyDeserialize, T: DeserializeOwned>(
+pub(crate) fn deserialize_kv<K: KeyDeserialize, T, Ser: Serde<T>>(
     kv: Record,
 ) -> StdResult<(K::Output, T)> {
     let (k, v) = kv;
     let kt = K::from_vec(k)?;
-    let vt = from_slice::<T>(&v)?;
+    let vt = Ser::deserialize(&v)?;
     Ok((kt, vt))
 }
 
@@ -1,5 +1,8 @@
+mod accumulator;
 mod append_store;
+mod binary_heap_store;
 mod bound;
+mod counter;
 mod de;
 mod de_old;
 mod deque_store;
@@ -8,23 +11,30 @@ mod helpers;
 mod indexed_map;
 mod indexed_snapshot;
 mod indexes;
+pub mod inspect;
 mod int_key;
 mod item;
 mod iter_helpers;
 mod keys;
 mod keys_old;
+mod linked_map;
 mod map;
+mod paging;
 mod path;
 mod prefix;
+mod sequence;
 mod serialization;
 mod snapshot;
 mod traits;
 
-pub use append_store::AppendStore;
+pub use accumulator::{Accumulator, Summable};
+pub use append_store::{AppendStore, AppendStoreMap};
+pub use binary_heap_store::{BinaryHeapStore, HeapOrder, MaxHeap, MinHeap};
 #[cfg(feature = "iterator")]
 pub use bound::{Bound, Bounder, PrefixBound, RawBound};
+pub use counter::Counter;
 pub use de::KeyDeserialize;
-pub use deque_store::DequeStore;
+pub use deque_store::{DequeStore, DequeStoreMap};
 pub use endian::Endian;
 #[cfg(feature = "iterator")]
 pub use indexed_map::{IndexList, IndexedMap};
@@ -35,18 +45,29 @@ pub use indexes::Index;
 #[cfg(feature = "iterator")]
 pub use indexes::MultiIndex;
 #[cfg(feature = "iterator")]
-pub use indexes::UniqueIndex;
+pub use indexes::{UniqueIndex, UniqueRef};
 pub use int_key::CwIntKey;
 pub use item::Item;
 pub use keys::{Key, Prefixer, PrimaryKey};
 pub use keys_old::IntKeyOld;
+pub use linked_map::{LinkedMap, LinkedSet};
 pub use map::Map;
+pub use paging::{Order, Page};
 pub use path::Path;
 #[cfg(feature = "iterator")]
 pub use prefix::{range_with_prefix, Prefix};
-pub use serialization::{Bincode2, Json, Serde};
+pub use sequence::Sequence;
+pub use serialization::{Bincode2, Borsh, Json, Serde, Upgrade, Version, Versioned};
+#[cfg(feature = "cbor")]
+pub use serialization::Cbor;
+#[cfg(feature = "compression")]
+pub use serialization::{Compressed, DEFAULT_COMPRESSION_THRESHOLD};
+#[cfg(feature = "msgpack")]
+pub use serialization::MsgPack;
+#[cfg(feature = "postcard")]
+pub use serialization::Postcard;
 #[cfg(feature = "iterator")]
-pub use snapshot::{SnapshotItem, SnapshotMap, Strategy};
+pub use snapshot::{ChangeSet, SnapshotItem, SnapshotMap, Strategy};
 pub use traits::{
     GenericItemStorage, GenericMapStorage, ItemStorage, MapStorage, NaiveItemStorage,
     NaiveMapStorage,
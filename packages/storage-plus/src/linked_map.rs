@@ -0,0 +1,817 @@
+//! Insertion-ordered collections backed by a doubly linked list.
+//!
+//! Every member stores the keys of its neighbours, so members can be appended and removed by
+//! key at a constant cost, without shifting any other member in storage. A few special keys are
+//! reserved for the head, the tail and the length of the list.
+use std::any::type_name;
+use std::convert::TryInto;
+use std::marker::PhantomData;
+
+use cosmwasm_std::{Addr, CustomQuery, QuerierWrapper, StdError, StdResult, Storage};
+
+use crate::de::KeyDeserialize;
+use crate::helpers::{encode_length, namespaces_with_key, query_deserialize, query_raw};
+use crate::keys::PrimaryKey;
+use crate::{Json, Serde};
+
+const HEAD_KEY: &[u8] = b"head";
+const TAIL_KEY: &[u8] = b"tail";
+const LEN_KEY: &[u8] = b"len";
+// these are length-prefixed under the namespace, so they never collide with the keys above
+const LINKS_NAMESPACE: &[u8] = b"l";
+const VALUES_NAMESPACE: &[u8] = b"v";
+
+type Entries<'c, K, T> = Box<dyn Iterator<Item = StdResult<(K, T)>> + 'c>;
+
+/// The neighbours of a member, by raw key.
+#[derive(Debug, Default, PartialEq)]
+struct Link {
+    prev: Option<Vec<u8>>,
+    next: Option<Vec<u8>>,
+}
+
+impl Link {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut out = vec![];
+        for key in [&self.prev, &self.next] {
+            match key {
+                Some(key) => {
+                    out.push(1);
+                    out.extend_from_slice(&encode_length(key));
+                    out.extend_from_slice(key);
+                }
+                None => out.push(0),
+            }
+        }
+        out
+    }
+
+    fn from_bytes(mut data: &[u8]) -> StdResult<Self> {
+        let mut read = || -> StdResult<Option<Vec<u8>>> {
+            let invalid = || StdError::parse_err(type_name::<Self>(), "invalid link");
+            let (&flag, rest) = data.split_first().ok_or_else(invalid)?;
+            if flag == 0 {
+                data = rest;
+                return Ok(None);
+            }
+            if rest.len() < 2 {
+                return Err(invalid());
+            }
+            let len = u16::from_be_bytes([rest[0], rest[1]]) as usize;
+            let key = rest.get(2..2 + len).ok_or_else(invalid)?.to_vec();
+            data = &rest[2 + len..];
+            Ok(Some(key))
+        };
+        let prev = read()?;
+        let next = read()?;
+        Ok(Link { prev, next })
+    }
+}
+
+/// The list of raw keys shared by `LinkedMap` and `LinkedSet`.
+#[derive(Debug, Clone, Copy)]
+struct LinkedKeys<'a> {
+    namespace: &'a [u8],
+}
+
+impl<'a> LinkedKeys<'a> {
+    const fn new(namespace: &'a str) -> Self {
+        LinkedKeys {
+            namespace: namespace.as_bytes(),
+        }
+    }
+
+    fn meta_key(&self, name: &[u8]) -> Vec<u8> {
+        namespaces_with_key(&[self.namespace], name)
+    }
+
+    fn link_key(&self, key: &[u8]) -> Vec<u8> {
+        namespaces_with_key(&[self.namespace, LINKS_NAMESPACE], key)
+    }
+
+    fn value_key(&self, key: &[u8]) -> Vec<u8> {
+        namespaces_with_key(&[self.namespace, VALUES_NAMESPACE], key)
+    }
+
+    fn len(&self, storage: &dyn Storage) -> StdResult<u32> {
+        match storage.get(&self.meta_key(LEN_KEY)) {
+            Some(len_vec) => {
+                let len_bytes = len_vec
+                    .as_slice()
+                    .try_into()
+                    .map_err(|err| StdError::parse_err("u32", err))?;
+                Ok(u32::from_be_bytes(len_bytes))
+            }
+            None => Ok(0),
+        }
+    }
+
+    fn set_len(&self, storage: &mut dyn Storage, len: u32) {
+        storage.set(&self.meta_key(LEN_KEY), &len.to_be_bytes());
+    }
+
+    fn end(&self, storage: &dyn Storage, reverse: bool) -> Option<Vec<u8>> {
+        storage.get(&self.meta_key(if reverse { TAIL_KEY } else { HEAD_KEY }))
+    }
+
+    fn set_end(&self, storage: &mut dyn Storage, tail: bool, key: Option<&[u8]>) {
+        let end_key = self.meta_key(if tail { TAIL_KEY } else { HEAD_KEY });
+        match key {
+            Some(key) => storage.set(&end_key, key),
+            None => storage.remove(&end_key),
+        }
+    }
+
+    fn contains(&self, storage: &dyn Storage, key: &[u8]) -> bool {
+        storage.get(&self.link_key(key)).is_some()
+    }
+
+    fn query_contains<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+        key: &[u8],
+    ) -> StdResult<bool> {
+        let link = query_raw(
+            querier,
+            remote_contract,
+            remote_contract_code_hash,
+            self.link_key(key).into(),
+        )?;
+        Ok(!link.is_empty())
+    }
+
+    fn may_load_link(&self, storage: &dyn Storage, key: &[u8]) -> StdResult<Option<Link>> {
+        storage
+            .get(&self.link_key(key))
+            .map(|data| Link::from_bytes(&data))
+            .transpose()
+    }
+
+    fn load_link(&self, storage: &dyn Storage, key: &[u8]) -> StdResult<Link> {
+        self.may_load_link(storage, key)?
+            .ok_or_else(|| StdError::not_found(type_name::<Link>()))
+    }
+
+    fn save_link(&self, storage: &mut dyn Storage, key: &[u8], link: &Link) {
+        storage.set(&self.link_key(key), &link.to_bytes());
+    }
+
+    /// Appends `key` to the list, returning false if it was already a member
+    fn push_back(&self, storage: &mut dyn Storage, key: &[u8]) -> StdResult<bool> {
+        if self.contains(storage, key) {
+            return Ok(false);
+        }
+        let tail = self.end(storage, true);
+        if let Some(tail) = &tail {
+            let mut link = self.load_link(storage, tail)?;
+            link.next = Some(key.to_vec());
+            self.save_link(storage, tail, &link);
+        } else {
+            self.set_end(storage, false, Some(key));
+        }
+        self.save_link(
+            storage,
+            key,
+            &Link {
+                prev: tail,
+                next: None,
+            },
+        );
+        self.set_end(storage, true, Some(key));
+        let len = self.len(storage)?;
+        self.set_len(storage, len + 1);
+        Ok(true)
+    }
+
+    /// Unlinks `key` from the list, returning false if it was not a member
+    fn remove(&self, storage: &mut dyn Storage, key: &[u8]) -> StdResult<bool> {
+        let link = match self.may_load_link(storage, key)? {
+            Some(link) => link,
+            None => return Ok(false),
+        };
+        match &link.prev {
+            Some(prev) => {
+                let mut prev_link = self.load_link(storage, prev)?;
+                prev_link.next = link.next.clone();
+                self.save_link(storage, prev, &prev_link);
+            }
+            None => self.set_end(storage, false, link.next.as_deref()),
+        }
+        match &link.next {
+            Some(next) => {
+                let mut next_link = self.load_link(storage, next)?;
+                next_link.prev = link.prev.clone();
+                self.save_link(storage, next, &next_link);
+            }
+            None => self.set_end(storage, true, link.prev.as_deref()),
+        }
+        storage.remove(&self.link_key(key));
+        let len = self.len(storage)?;
+        self.set_len(storage, len - 1);
+        Ok(true)
+    }
+
+    /// Iterates over the raw keys, starting after `cursor` if given
+    fn keys<'c>(
+        &self,
+        storage: &'c dyn Storage,
+        cursor: Option<&[u8]>,
+        reverse: bool,
+    ) -> StdResult<RawKeys<'c>>
+    where
+        'a: 'c,
+    {
+        let next = match cursor {
+            Some(cursor) => {
+                let link = self.load_link(storage, cursor)?;
+                if reverse {
+                    link.prev
+                } else {
+                    link.next
+                }
+            }
+            None => self.end(storage, reverse),
+        };
+        Ok(RawKeys {
+            list: *self,
+            storage,
+            next,
+            reverse,
+        })
+    }
+}
+
+struct RawKeys<'c> {
+    list: LinkedKeys<'c>,
+    storage: &'c dyn Storage,
+    next: Option<Vec<u8>>,
+    reverse: bool,
+}
+
+impl<'c> Iterator for RawKeys<'c> {
+    type Item = StdResult<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = self.next.take()?;
+        match self.list.load_link(self.storage, &key) {
+            Ok(link) => {
+                self.next = if self.reverse { link.prev } else { link.next };
+                Some(Ok(key))
+            }
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+/// LinkedMap stores values by key like a `Map`, but remembers the order in which the keys were
+/// inserted and iterates in that order.
+///
+/// Appending and removing any key has a constant cost, which makes it a good fit for queues
+/// and membership lists (hooks, validator sets...) that are modified in arbitrary places.
+pub struct LinkedMap<'a, K, T, Ser = Json> {
+    list: LinkedKeys<'a>,
+    // see https://doc.rust-lang.org/std/marker/struct.PhantomData.html#unused-type-parameters for why this is needed
+    key_type: PhantomData<K>,
+    data_type: PhantomData<T>,
+    serialization_type: PhantomData<*const Ser>,
+}
+
+impl<'a, K, T, Ser> LinkedMap<'a, K, T, Ser> {
+    pub const fn new(namespace: &'a str) -> Self {
+        LinkedMap {
+            list: LinkedKeys::new(namespace),
+            key_type: PhantomData,
+            data_type: PhantomData,
+            serialization_type: PhantomData,
+        }
+    }
+
+    pub fn namespace(&self) -> &'a [u8] {
+        self.list.namespace
+    }
+
+    /// returns the number of keys in the map
+    pub fn len(&self, storage: &dyn Storage) -> StdResult<u32> {
+        self.list.len(storage)
+    }
+
+    /// checks if the map has any keys
+    pub fn is_empty(&self, storage: &dyn Storage) -> StdResult<bool> {
+        Ok(self.len(storage)? == 0)
+    }
+}
+
+impl<'a, K, T, Ser> LinkedMap<'a, K, T, Ser>
+where
+    K: PrimaryKey<'a>,
+    Ser: Serde<T>,
+{
+    /// Stores `data` at the end of the map, or replaces the value in place if the key is
+    /// already present. Returns whether the key was newly inserted.
+    pub fn push_back(&self, storage: &mut dyn Storage, k: K, data: &T) -> StdResult<bool> {
+        let key = k.joined_key();
+        storage.set(&self.list.value_key(&key), &Ser::serialize(data)?);
+        self.list.push_back(storage, &key)
+    }
+
+    /// Removes the key and its value, returning whether it was present.
+    pub fn remove(&self, storage: &mut dyn Storage, k: K) -> StdResult<bool> {
+        let key = k.joined_key();
+        storage.remove(&self.list.value_key(&key));
+        self.list.remove(storage, &key)
+    }
+
+    /// contains returns true if the key is present, without reading its value
+    pub fn contains(&self, storage: &dyn Storage, k: K) -> bool {
+        self.list.contains(storage, &k.joined_key())
+    }
+
+    /// load will return an error if no data is set at the given key, or on parse error
+    pub fn load(&self, storage: &dyn Storage, k: K) -> StdResult<T> {
+        self.may_load(storage, k)?
+            .ok_or_else(|| StdError::not_found(type_name::<T>()))
+    }
+
+    /// may_load will parse the data stored at the key if present, returns Ok(None) if no data there.
+    /// returns an error on issues parsing
+    pub fn may_load(&self, storage: &dyn Storage, k: K) -> StdResult<Option<T>> {
+        self.load_raw(storage, &k.joined_key())
+    }
+
+    /// If you import the proper LinkedMap from the remote contract, this will let you read the
+    /// data from a remote contract in a type-safe way using WasmQuery::RawQuery
+    pub fn query<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+        k: K,
+    ) -> StdResult<Option<T>> {
+        query_deserialize::<T, Ser, Q>(
+            querier,
+            remote_contract,
+            remote_contract_code_hash,
+            &self.list.value_key(&k.joined_key()),
+        )
+    }
+
+    fn load_raw(&self, storage: &dyn Storage, key: &[u8]) -> StdResult<Option<T>> {
+        storage
+            .get(&self.list.value_key(key))
+            .map(|data| Ser::deserialize(&data))
+            .transpose()
+    }
+}
+
+impl<'a, K, T, Ser> LinkedMap<'a, K, T, Ser>
+where
+    K: PrimaryKey<'a> + KeyDeserialize,
+    Ser: Serde<T>,
+{
+    /// Iterates over the entries in insertion order
+    pub fn iter<'c>(&'c self, storage: &'c dyn Storage) -> StdResult<Entries<'c, K::Output, T>>
+    where
+        T: 'c,
+        K::Output: 'static,
+    {
+        self.entries(storage, None, false)
+    }
+
+    /// Iterates over the entries from the most recently inserted one
+    pub fn iter_rev<'c>(&'c self, storage: &'c dyn Storage) -> StdResult<Entries<'c, K::Output, T>>
+    where
+        T: 'c,
+        K::Output: 'static,
+    {
+        self.entries(storage, None, true)
+    }
+
+    /// Returns up to `limit` entries in insertion order, starting after the `start_after` key.
+    ///
+    /// The last key returned can be used as the cursor for the next page, so every page has a
+    /// cost proportional to its size.
+    pub fn paging(
+        &self,
+        storage: &dyn Storage,
+        start_after: Option<K>,
+        limit: u32,
+    ) -> StdResult<Vec<(K::Output, T)>>
+    where
+        K::Output: 'static,
+    {
+        let cursor = start_after.map(|k| k.joined_key());
+        self.entries(storage, cursor.as_deref(), false)?
+            .take(limit as usize)
+            .collect()
+    }
+
+    /// Same as `paging`, but starting from the most recently inserted entry.
+    pub fn paging_reverse(
+        &self,
+        storage: &dyn Storage,
+        start_before: Option<K>,
+        limit: u32,
+    ) -> StdResult<Vec<(K::Output, T)>>
+    where
+        K::Output: 'static,
+    {
+        let cursor = start_before.map(|k| k.joined_key());
+        self.entries(storage, cursor.as_deref(), true)?
+            .take(limit as usize)
+            .collect()
+    }
+
+    fn entries<'c>(
+        &'c self,
+        storage: &'c dyn Storage,
+        cursor: Option<&[u8]>,
+        reverse: bool,
+    ) -> StdResult<Entries<'c, K::Output, T>>
+    where
+        T: 'c,
+        K::Output: 'static,
+    {
+        let keys = self.list.keys(storage, cursor, reverse)?;
+        let entries = keys.map(move |key| {
+            let key = key?;
+            let value = self
+                .load_raw(storage, &key)?
+                .ok_or_else(|| StdError::not_found(type_name::<T>()))?;
+            Ok((K::from_vec(key)?, value))
+        });
+        Ok(Box::new(entries))
+    }
+}
+
+/// LinkedSet is a set of keys that remembers the order in which they were inserted and
+/// iterates in that order.
+///
+/// Appending and removing any key has a constant cost, see `LinkedMap`.
+#[derive(Debug, Clone)]
+pub struct LinkedSet<'a, K> {
+    list: LinkedKeys<'a>,
+    // see https://doc.rust-lang.org/std/marker/struct.PhantomData.html#unused-type-parameters for why this is needed
+    key_type: PhantomData<K>,
+}
+
+impl<'a, K> LinkedSet<'a, K> {
+    pub const fn new(namespace: &'a str) -> Self {
+        LinkedSet {
+            list: LinkedKeys::new(namespace),
+            key_type: PhantomData,
+        }
+    }
+
+    pub fn namespace(&self) -> &'a [u8] {
+        self.list.namespace
+    }
+
+    /// returns the number of keys in the set
+    pub fn len(&self, storage: &dyn Storage) -> StdResult<u32> {
+        self.list.len(storage)
+    }
+
+    /// checks if the set has any keys
+    pub fn is_empty(&self, storage: &dyn Storage) -> StdResult<bool> {
+        Ok(self.len(storage)? == 0)
+    }
+}
+
+impl<'a, K> LinkedSet<'a, K>
+where
+    K: PrimaryKey<'a>,
+{
+    /// Appends the key to the set, returning false if it was already present, in which case
+    /// its position does not change.
+    pub fn push_back(&self, storage: &mut dyn Storage, k: K) -> StdResult<bool> {
+        self.list.push_back(storage, &k.joined_key())
+    }
+
+    /// Removes the key, returning whether it was present.
+    pub fn remove(&self, storage: &mut dyn Storage, k: K) -> StdResult<bool> {
+        self.list.remove(storage, &k.joined_key())
+    }
+
+    /// contains returns true if the key is present
+    pub fn contains(&self, storage: &dyn Storage, k: K) -> bool {
+        self.list.contains(storage, &k.joined_key())
+    }
+
+    /// If you import the proper LinkedSet from the remote contract, this will let you check
+    /// whether a key is present in a remote contract using WasmQuery::RawQuery
+    pub fn query_contains<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+        k: K,
+    ) -> StdResult<bool> {
+        self.list.query_contains(
+            querier,
+            remote_contract,
+            remote_contract_code_hash,
+            &k.joined_key(),
+        )
+    }
+}
+
+impl<'a, K> LinkedSet<'a, K>
+where
+    K: PrimaryKey<'a> + KeyDeserialize,
+{
+    /// Iterates over the keys in insertion order
+    pub fn iter<'c>(
+        &'c self,
+        storage: &'c dyn Storage,
+    ) -> StdResult<Box<dyn Iterator<Item = StdResult<K::Output>> + 'c>>
+    where
+        K::Output: 'static,
+    {
+        self.members(storage, None, false)
+    }
+
+    /// Iterates over the keys from the most recently inserted one
+    pub fn iter_rev<'c>(
+        &'c self,
+        storage: &'c dyn Storage,
+    ) -> StdResult<Box<dyn Iterator<Item = StdResult<K::Output>> + 'c>>
+    where
+        K::Output: 'static,
+    {
+        self.members(storage, None, true)
+    }
+
+    /// Returns up to `limit` keys in insertion order, starting after the `start_after` key.
+    ///
+    /// The last key returned can be used as the cursor for the next page, so every page has a
+    /// cost proportional to its size.
+    pub fn paging(
+        &self,
+        storage: &dyn Storage,
+        start_after: Option<K>,
+        limit: u32,
+    ) -> StdResult<Vec<K::Output>>
+    where
+        K::Output: 'static,
+    {
+        let cursor = start_after.map(|k| k.joined_key());
+        self.members(storage, cursor.as_deref(), false)?
+            .take(limit as usize)
+            .collect()
+    }
+
+    /// Same as `paging`, but starting from the most recently inserted key.
+    pub fn paging_reverse(
+        &self,
+        storage: &dyn Storage,
+        start_before: Option<K>,
+        limit: u32,
+    ) -> StdResult<Vec<K::Output>>
+    where
+        K::Output: 'static,
+    {
+        let cursor = start_before.map(|k| k.joined_key());
+        self.members(storage, cursor.as_deref(), true)?
+            .take(limit as usize)
+            .collect()
+    }
+
+    fn members<'c>(
+        &'c self,
+        storage: &'c dyn Storage,
+        cursor: Option<&[u8]>,
+        reverse: bool,
+    ) -> StdResult<Box<dyn Iterator<Item = StdResult<K::Output>> + 'c>>
+    where
+        K::Output: 'static,
+    {
+        let keys = self.list.keys(storage, cursor, reverse)?;
+        Ok(Box::new(keys.map(|key| K::from_vec(key?))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cosmwasm_std::testing::MockStorage;
+    use cosmwasm_std::Addr;
+    use cosmwasm_std::Empty;
+
+    use crate::helpers::mock_remote_querier;
+    use crate::Bincode2;
+
+    const HOOKS: LinkedSet<&Addr> = LinkedSet::new("hooks");
+    const QUEUE: LinkedMap<&str, u64, Bincode2> = LinkedMap::new("queue");
+
+    fn addr(name: &str) -> Addr {
+        Addr::unchecked(name)
+    }
+
+    fn hooks(storage: &dyn Storage) -> Vec<String> {
+        HOOKS
+            .iter(storage)
+            .unwrap()
+            .map(|k| k.unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn link_encoding_round_trips() {
+        for link in [
+            Link::default(),
+            Link {
+                prev: Some(b"a".to_vec()),
+                next: None,
+            },
+            Link {
+                prev: None,
+                next: Some(vec![]),
+            },
+            Link {
+                prev: Some(b"prev".to_vec()),
+                next: Some(b"next".to_vec()),
+            },
+        ] {
+            assert_eq!(link, Link::from_bytes(&link.to_bytes()).unwrap());
+        }
+        assert!(Link::from_bytes(&[]).is_err());
+        assert!(Link::from_bytes(&[1, 0, 5, b'a']).is_err());
+    }
+
+    #[test]
+    fn set_push_and_remove() {
+        let mut storage = MockStorage::new();
+        assert!(HOOKS.is_empty(&storage).unwrap());
+
+        for name in ["a", "b", "c", "d"] {
+            assert!(HOOKS.push_back(&mut storage, &addr(name)).unwrap());
+        }
+        // pushing a member again does not move it
+        assert!(!HOOKS.push_back(&mut storage, &addr("b")).unwrap());
+        assert_eq!(hooks(&storage), vec!["a", "b", "c", "d"]);
+        assert_eq!(HOOKS.len(&storage).unwrap(), 4);
+
+        // middle
+        assert!(HOOKS.remove(&mut storage, &addr("b")).unwrap());
+        assert_eq!(hooks(&storage), vec!["a", "c", "d"]);
+        // head
+        assert!(HOOKS.remove(&mut storage, &addr("a")).unwrap());
+        assert_eq!(hooks(&storage), vec!["c", "d"]);
+        // tail
+        assert!(HOOKS.remove(&mut storage, &addr("d")).unwrap());
+        assert_eq!(hooks(&storage), vec!["c"]);
+        assert!(!HOOKS.remove(&mut storage, &addr("d")).unwrap());
+
+        assert!(HOOKS.contains(&storage, &addr("c")));
+        assert!(!HOOKS.contains(&storage, &addr("a")));
+
+        // removed members can be pushed again, at the end
+        HOOKS.push_back(&mut storage, &addr("a")).unwrap();
+        assert_eq!(hooks(&storage), vec!["c", "a"]);
+
+        HOOKS.remove(&mut storage, &addr("c")).unwrap();
+        HOOKS.remove(&mut storage, &addr("a")).unwrap();
+        assert!(HOOKS.is_empty(&storage).unwrap());
+        assert_eq!(hooks(&storage), Vec::<String>::new());
+
+        HOOKS.push_back(&mut storage, &addr("e")).unwrap();
+        assert_eq!(hooks(&storage), vec!["e"]);
+    }
+
+    #[test]
+    fn set_iter_rev_and_paging() {
+        let mut storage = MockStorage::new();
+        let set: LinkedSet<u32> = LinkedSet::new("set");
+        for i in [5, 3, 9, 1, 7] {
+            set.push_back(&mut storage, i).unwrap();
+        }
+
+        let rev: StdResult<Vec<_>> = set.iter_rev(&storage).unwrap().collect();
+        assert_eq!(rev.unwrap(), vec![7, 1, 9, 3, 5]);
+
+        assert_eq!(set.paging(&storage, None, 2).unwrap(), vec![5, 3]);
+        assert_eq!(set.paging(&storage, Some(3), 2).unwrap(), vec![9, 1]);
+        assert_eq!(set.paging(&storage, Some(1), 2).unwrap(), vec![7]);
+        assert_eq!(set.paging(&storage, Some(7), 2).unwrap(), Vec::<u32>::new());
+        assert_eq!(
+            set.paging_reverse(&storage, None, 3).unwrap(),
+            vec![7, 1, 9]
+        );
+        assert_eq!(
+            set.paging_reverse(&storage, Some(9), 3).unwrap(),
+            vec![3, 5]
+        );
+
+        // unknown cursors are an error
+        assert!(set.paging(&storage, Some(4), 2).is_err());
+    }
+
+    #[test]
+    fn map_push_load_remove() {
+        let mut storage = MockStorage::new();
+
+        assert!(QUEUE.push_back(&mut storage, "john", &1).unwrap());
+        assert!(QUEUE.push_back(&mut storage, "jim", &2).unwrap());
+        assert!(QUEUE.push_back(&mut storage, "jane", &3).unwrap());
+
+        // replacing a value keeps its position
+        assert!(!QUEUE.push_back(&mut storage, "john", &10).unwrap());
+        assert_eq!(QUEUE.load(&storage, "john").unwrap(), 10);
+        assert_eq!(QUEUE.len(&storage).unwrap(), 3);
+
+        let all: StdResult<Vec<_>> = QUEUE.iter(&storage).unwrap().collect();
+        assert_eq!(
+            all.unwrap(),
+            vec![
+                ("john".to_string(), 10),
+                ("jim".to_string(), 2),
+                ("jane".to_string(), 3)
+            ]
+        );
+
+        assert!(QUEUE.remove(&mut storage, "jim").unwrap());
+        assert!(!QUEUE.contains(&storage, "jim"));
+        assert_eq!(QUEUE.may_load(&storage, "jim").unwrap(), None);
+        assert!(QUEUE.load(&storage, "jim").is_err());
+
+        let all: StdResult<Vec<_>> = QUEUE.iter_rev(&storage).unwrap().collect();
+        assert_eq!(
+            all.unwrap(),
+            vec![("jane".to_string(), 3), ("john".to_string(), 10)]
+        );
+        assert_eq!(
+            QUEUE.paging(&storage, Some("john"), 10).unwrap(),
+            vec![("jane".to_string(), 3)]
+        );
+        assert_eq!(
+            QUEUE.paging_reverse(&storage, Some("john"), 10).unwrap(),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn map_composite_keys() {
+        let mut storage = MockStorage::new();
+        let map: LinkedMap<(&str, u64), String> = LinkedMap::new("composite");
+
+        map.push_back(&mut storage, ("b", 2), &"b2".to_string())
+            .unwrap();
+        map.push_back(&mut storage, ("a", 1), &"a1".to_string())
+            .unwrap();
+        map.push_back(&mut storage, ("b", 1), &"b1".to_string())
+            .unwrap();
+        map.remove(&mut storage, ("a", 1)).unwrap();
+
+        let all: StdResult<Vec<_>> = map.iter(&storage).unwrap().collect();
+        assert_eq!(
+            all.unwrap(),
+            vec![
+                (("b".to_string(), 2), "b2".to_string()),
+                (("b".to_string(), 1), "b1".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn separate_namespaces_are_isolated() {
+        let mut storage = MockStorage::new();
+        let first: LinkedSet<&str> = LinkedSet::new("set");
+        let second: LinkedSet<&str> = LinkedSet::new("set2");
+
+        first.push_back(&mut storage, "head").unwrap();
+        first.push_back(&mut storage, "len").unwrap();
+        second.push_back(&mut storage, "a").unwrap();
+
+        assert_eq!(first.len(&storage).unwrap(), 2);
+        assert_eq!(second.len(&storage).unwrap(), 1);
+        assert_eq!(
+            first.paging(&storage, None, 10).unwrap(),
+            vec!["head", "len"]
+        );
+        assert_eq!(second.paging(&storage, None, 10).unwrap(), vec!["a"]);
+    }
+
+    #[test]
+    fn query() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        let map: LinkedMap<&str, u64, Bincode2> = LinkedMap::new("map");
+        let set: LinkedSet<&str> = LinkedSet::new("set");
+        map.push_back(&mut storage, "a", &1)?;
+        set.push_back(&mut storage, "a")?;
+
+        let querier = mock_remote_querier(storage);
+        let querier = QuerierWrapper::<Empty>::new(&querier);
+        let remote = || Addr::unchecked("remote");
+        let hash = || "hash".to_string();
+
+        assert_eq!(map.query(&querier, remote(), hash(), "a")?, Some(1));
+        assert_eq!(map.query(&querier, remote(), hash(), "b")?, None);
+        assert!(set.query_contains(&querier, remote(), hash(), "a")?);
+        assert!(!set.query_contains(&querier, remote(), hash(), "b")?);
+
+        Ok(())
+    }
+}
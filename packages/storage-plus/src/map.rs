@@ -1,13 +1,10 @@
-use serde::de::DeserializeOwned;
-use serde::Serialize;
 use std::marker::PhantomData;
 
-use crate::{Serde, Json};
 #[cfg(feature = "iterator")]
 use crate::bound::{Bound, PrefixBound};
 #[cfg(feature = "iterator")]
 use crate::de::KeyDeserialize;
-use crate::helpers::query_raw;
+use crate::helpers::query_deserialize;
 #[cfg(feature = "iterator")]
 use crate::iter_helpers::{deserialize_kv, deserialize_v};
 #[cfg(feature = "iterator")]
@@ -16,7 +13,9 @@ use crate::keys::{Key, PrimaryKey};
 use crate::path::Path;
 #[cfg(feature = "iterator")]
 use crate::prefix::{namespaced_prefix_range, Prefix};
-use cosmwasm_std::{from_slice, Addr, CustomQuery, QuerierWrapper, StdError, StdResult, Storage};
+use crate::serialization::{Upgrade, Versioned};
+use crate::{Json, Serde};
+use cosmwasm_std::{Addr, CustomQuery, QuerierWrapper, StdError, StdResult, Storage};
 
 #[derive(Debug, Clone)]
 pub struct Map<'a, K, T, Ser = Json> {
@@ -27,9 +26,7 @@ pub struct Map<'a, K, T, Ser = Json> {
     serialization_type: PhantomData<*const Ser>,
 }
 
-impl<'a, K, T, Ser> Map<'a, K, T, Ser>
-    where Ser: Serde,
-{
+impl<'a, K, T, Ser> Map<'a, K, T, Ser> {
     pub const fn new(namespace: &'a str) -> Self {
         Map {
             namespace: namespace.as_bytes(),
@@ -46,9 +43,8 @@ impl<'a, K, T, Ser> Map<'a, K, T, Ser>
 
 impl<'a, K, T, Ser> Map<'a, K, T, Ser>
 where
-    T: Serialize + DeserializeOwned,
     K: PrimaryKey<'a>,
-    Ser: Serde,
+    Ser: Serde<T>,
 {
     pub fn key(&self, k: K) -> Path<T, Ser> {
         Path::new(
@@ -58,7 +54,7 @@ where
     }
 
     #[cfg(feature = "iterator")]
-    pub(crate) fn no_prefix_raw(&self) -> Prefix<Vec<u8>, T, K> {
+    pub(crate) fn no_prefix_raw(&self) -> Prefix<Vec<u8>, T, K, Ser> {
         Prefix::new(self.namespace, &[])
     }
 
@@ -100,7 +96,8 @@ where
     }
 
     /// If you import the proper Map from the remote contract, this will let you read the data
-    /// from a remote contract in a type-safe way using WasmQuery::RawQuery
+    /// from a remote contract in a type-safe way using WasmQuery::RawQuery.
+    /// The data is decoded with the same `Ser` it was stored with.
     pub fn query<Q: CustomQuery>(
         &self,
         querier: &QuerierWrapper<Q>,
@@ -108,39 +105,51 @@ where
         remote_contract_code_hash: String,
         k: K,
     ) -> StdResult<Option<T>> {
-        let key = self.key(k).storage_key.into();
-        let result = query_raw(querier, remote_contract, remote_contract_code_hash, key)?;
-        if result.is_empty() {
-            Ok(None)
-        } else {
-            from_slice(&result).map(Some)
-        }
+        query_deserialize::<T, Ser, Q>(
+            querier,
+            remote_contract,
+            remote_contract_code_hash,
+            &self.key(k).storage_key,
+        )
+    }
+}
+
+impl<'a, K, T, Inner> Map<'a, K, T, Versioned<Inner>>
+where
+    K: PrimaryKey<'a>,
+    T: Upgrade<Inner>,
+    Inner: Serde<T>,
+{
+    /// may_load_and_upgrade works like may_load, but if the data was stored under an older
+    /// version it is written back in the current one, so the upgrade only runs once.
+    pub fn may_load_and_upgrade(&self, store: &mut dyn Storage, k: K) -> StdResult<Option<T>> {
+        self.key(k).may_load_and_upgrade(store)
     }
 }
 
 #[cfg(feature = "iterator")]
-impl<'a, K, T> Map<'a, K, T>
+impl<'a, K, T, Ser> Map<'a, K, T, Ser>
 where
-    T: Serialize + DeserializeOwned,
     K: PrimaryKey<'a>,
+    Ser: Serde<T>,
 {
-    pub fn sub_prefix(&self, p: K::SubPrefix) -> Prefix<K::SuperSuffix, T, K::SuperSuffix> {
+    pub fn sub_prefix(&self, p: K::SubPrefix) -> Prefix<K::SuperSuffix, T, K::SuperSuffix, Ser> {
         Prefix::new(self.namespace, &p.prefix())
     }
 
-    pub fn prefix(&self, p: K::Prefix) -> Prefix<K::Suffix, T, K::Suffix> {
+    pub fn prefix(&self, p: K::Prefix) -> Prefix<K::Suffix, T, K::Suffix, Ser> {
         Prefix::new(self.namespace, &p.prefix())
     }
 }
 
 // short-cut for simple keys, rather than .prefix(()).range_raw(...)
 #[cfg(feature = "iterator")]
-impl<'a, K, T> Map<'a, K, T>
+impl<'a, K, T, Ser> Map<'a, K, T, Ser>
 where
-    T: Serialize + DeserializeOwned,
     // TODO: this should only be when K::Prefix == ()
     // Other cases need to call prefix() first
     K: PrimaryKey<'a>,
+    Ser: Serde<T>,
 {
     /// While `range_raw` over a `prefix` fixes the prefix to one element and iterates over the
     /// remaining, `prefix_range_raw` accepts bounds for the lowest and highest elements of the `Prefix`
@@ -157,18 +166,19 @@ where
     where
         T: 'c,
         'a: 'c,
+        Ser: 'c,
     {
-        let mapped =
-            namespaced_prefix_range(store, self.namespace, min, max, order).map(deserialize_v);
+        let mapped = namespaced_prefix_range(store, self.namespace, min, max, order)
+            .map(deserialize_v::<T, Ser>);
         Box::new(mapped)
     }
 }
 
 #[cfg(feature = "iterator")]
-impl<'a, K, T> Map<'a, K, T>
+impl<'a, K, T, Ser> Map<'a, K, T, Ser>
 where
-    T: Serialize + DeserializeOwned,
     K: PrimaryKey<'a> + KeyDeserialize,
+    Ser: Serde<T>,
 {
     /// While `range` over a `prefix` fixes the prefix to one element and iterates over the
     /// remaining, `prefix_range` accepts bounds for the lowest and highest elements of the
@@ -188,22 +198,23 @@ where
         'a: 'c,
         K: 'c,
         K::Output: 'static,
+        Ser: 'c,
     {
         let mapped = namespaced_prefix_range(store, self.namespace, min, max, order)
-            .map(deserialize_kv::<K, T>);
+            .map(deserialize_kv::<K, T, Ser>);
         Box::new(mapped)
     }
 
-    fn no_prefix(&self) -> Prefix<K, T, K> {
+    fn no_prefix(&self) -> Prefix<K, T, K, Ser> {
         Prefix::new(self.namespace, &[])
     }
 }
 
 #[cfg(feature = "iterator")]
-impl<'a, K, T> Map<'a, K, T>
+impl<'a, K, T, Ser> Map<'a, K, T, Ser>
 where
-    T: Serialize + DeserializeOwned,
     K: PrimaryKey<'a>,
+    Ser: Serde<T>,
 {
     pub fn range_raw<'c>(
         &self,
@@ -230,13 +241,27 @@ where
     {
         self.no_prefix_raw().keys_raw(store, min, max, order)
     }
+
+    /// Removes the entries of the map from storage, at most `limit` of them if given.
+    ///
+    /// Calling it again resumes where the previous call stopped, so a large map can be cleared
+    /// over several transactions. Returns true once the map is empty.
+    /// Use `prefix(..).clear(..)` to only remove the entries under a prefix.
+    pub fn clear(&self, store: &mut dyn Storage, limit: Option<usize>) -> bool {
+        self.no_prefix_raw().clear(store, limit)
+    }
+
+    /// Returns true if the map has no entries
+    pub fn is_empty(&self, store: &dyn Storage) -> bool {
+        self.no_prefix_raw().is_empty(store)
+    }
 }
 
 #[cfg(feature = "iterator")]
-impl<'a, K, T> Map<'a, K, T>
+impl<'a, K, T, Ser> Map<'a, K, T, Ser>
 where
-    T: Serialize + DeserializeOwned,
     K: PrimaryKey<'a> + KeyDeserialize,
+    Ser: Serde<T>,
 {
     pub fn range<'c>(
         &self,
@@ -270,23 +295,26 @@ where
 #[cfg(test)]
 mod test {
     use super::*;
+    use borsh::{BorshDeserialize, BorshSerialize};
     use rstest::*;
     use rstest_reuse::{self, *};
     use serde::{Deserialize, Serialize};
     use std::ops::Deref;
 
     use cosmwasm_std::testing::MockStorage;
+    use cosmwasm_std::Empty;
     #[cfg(feature = "iterator")]
     use cosmwasm_std::{Order, StdResult};
 
     #[cfg(feature = "iterator")]
     use crate::bound::Bounder;
 
-    use crate::{int_key::CwIntKey, Bincode2};
+    use crate::helpers::mock_remote_querier;
     #[cfg(feature = "iterator")]
     use crate::IntKeyOld;
+    use crate::{int_key::CwIntKey, Bincode2, Borsh};
 
-    #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+    #[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
     struct Data {
         pub name: String,
         pub age: i32,
@@ -294,39 +322,48 @@ mod test {
 
     const PEOPLE: Map<&[u8], Data> = Map::new("people");
     const B_PEOPLE: Map<&[u8], Data, Bincode2> = Map::new("people");
+    const BR_PEOPLE: Map<&[u8], Data, Borsh> = Map::new("people");
 
     #[cfg(feature = "iterator")]
     const PEOPLE_ID: Map<u32, Data> = Map::new("people_id");
     #[cfg(feature = "iterator")]
+    const B_PEOPLE_ID: Map<u32, Data, Bincode2> = Map::new("people_id");
+    #[cfg(feature = "iterator")]
+    const BR_PEOPLE_ID: Map<u32, Data, Borsh> = Map::new("people_id");
+    #[cfg(feature = "iterator")]
     const SIGNED_ID_OLD: Map<IntKeyOld<i32>, Data> = Map::new("signed_id");
     #[cfg(feature = "iterator")]
     const SIGNED_ID: Map<i32, Data> = Map::new("signed_id");
 
     const ALLOWANCE: Map<(&[u8], &[u8]), u64> = Map::new("allow");
     const B_ALLOWANCE: Map<(&[u8], &[u8]), u64, Bincode2> = Map::new("allow");
+    const BR_ALLOWANCE: Map<(&[u8], &[u8]), u64, Borsh> = Map::new("allow");
 
     const TRIPLE: Map<(&[u8], u8, &str), u64> = Map::new("triple");
     const B_TRIPLE: Map<(&[u8], u8, &str), u64, Bincode2> = Map::new("triple");
+    const BR_TRIPLE: Map<(&[u8], u8, &str), u64, Borsh> = Map::new("triple");
 
-    type Key<'a> = &'a[u8];
-    type AKey<'a> = (&'a[u8], &'a[u8]);
-    type TKey<'a> = (&'a[u8], u8, &'a str);
+    type Key<'a> = &'a [u8];
+    type AKey<'a> = (&'a [u8], &'a [u8]);
+    type TKey<'a> = (&'a [u8], u8, &'a str);
 
     #[template]
     #[rstest]
     #[case(PEOPLE, ALLOWANCE, TRIPLE)]
     #[case(B_PEOPLE, B_ALLOWANCE, B_TRIPLE)]
+    #[case(BR_PEOPLE, BR_ALLOWANCE, BR_TRIPLE)]
     fn serialization_3(
-        #[case] people: Map<Key, Data, impl Serde>,
-        #[case] allowance: Map<AKey, u64, impl Serde>,
-        #[case] triple: Map<TKey, u64, impl Serde>,
-    ) {}
+        #[case] people: Map<Key, Data, impl Serde<Data>>,
+        #[case] allowance: Map<AKey, u64, impl Serde<u64>>,
+        #[case] triple: Map<TKey, u64, impl Serde<u64>>,
+    ) {
+    }
 
     #[apply(serialization_3)]
     fn create_path(
-        #[case] people: Map<Key, Data, impl Serde>,
-        #[case] allowance: Map<AKey, u64, impl Serde>,
-        #[case] triple: Map<TKey, u64, impl Serde>,
+        #[case] people: Map<Key, Data, impl Serde<Data>>,
+        #[case] allowance: Map<AKey, u64, impl Serde<u64>>,
+        #[case] triple: Map<TKey, u64, impl Serde<u64>>,
     ) {
         let path = people.key(b"john");
         let key = path.deref();
@@ -360,7 +397,9 @@ mod test {
     }
 
     #[rstest]
-    fn save_and_load(#[values(PEOPLE, B_PEOPLE)] people: Map<Key, Data, impl Serde> ) {
+    fn save_and_load(
+        #[values(PEOPLE, B_PEOPLE, BR_PEOPLE)] people: Map<Key, Data, impl Serde<Data>>,
+    ) {
         let mut store = MockStorage::new();
 
         // save and load on one key
@@ -385,7 +424,32 @@ mod test {
     }
 
     #[rstest]
-    fn existence(#[values(PEOPLE, B_PEOPLE)] people: Map<Key, Data, impl Serde>) {
+    fn query_works(
+        #[values(PEOPLE, B_PEOPLE, BR_PEOPLE)] people: Map<Key, Data, impl Serde<Data>>,
+    ) {
+        let mut store = MockStorage::new();
+        let data = Data {
+            name: "John".to_string(),
+            age: 32,
+        };
+        people.save(&mut store, b"john", &data).unwrap();
+
+        let querier = mock_remote_querier(store);
+        let querier = QuerierWrapper::<Empty>::new(&querier);
+        let remote = || Addr::unchecked("remote");
+
+        let loaded = people
+            .query(&querier, remote(), "hash".to_string(), b"john")
+            .unwrap();
+        assert_eq!(Some(data), loaded);
+        let missing = people
+            .query(&querier, remote(), "hash".to_string(), b"jack")
+            .unwrap();
+        assert_eq!(None, missing);
+    }
+
+    #[rstest]
+    fn existence(#[values(PEOPLE, B_PEOPLE, BR_PEOPLE)] people: Map<Key, Data, impl Serde<Data>>) {
         let mut store = MockStorage::new();
 
         // set data in proper format
@@ -412,7 +476,9 @@ mod test {
     }
 
     #[rstest]
-    fn composite_keys(#[values(ALLOWANCE, B_ALLOWANCE)] allowance: Map<AKey, u64, impl Serde>) {
+    fn composite_keys(
+        #[values(ALLOWANCE, B_ALLOWANCE, BR_ALLOWANCE)] allowance: Map<AKey, u64, impl Serde<u64>>,
+    ) {
         let mut store = MockStorage::new();
 
         // save and load on a composite key
@@ -431,7 +497,7 @@ mod test {
     }
 
     #[rstest]
-    fn triple_keys(#[values(TRIPLE, B_TRIPLE)] triple: Map<TKey, u64, impl Serde>) {
+    fn triple_keys(#[values(TRIPLE, B_TRIPLE, BR_TRIPLE)] triple: Map<TKey, u64, impl Serde<u64>>) {
         let mut store = MockStorage::new();
 
         // save and load on a triple composite key
@@ -451,9 +517,11 @@ mod test {
         assert_eq!(1234, same);
     }
 
-    #[test]
+    #[rstest]
     #[cfg(feature = "iterator")]
-    fn range_raw_simple_key() {
+    fn range_raw_simple_key(
+        #[values(PEOPLE, B_PEOPLE, BR_PEOPLE)] people: Map<Key, Data, impl Serde<Data>>,
+    ) {
         let mut store = MockStorage::new();
 
         // save and load on two keys
@@ -470,7 +538,7 @@ mod test {
         people.save(&mut store, b"jim", &data2).unwrap();
 
         // let's try to iterate!
-        let all: StdResult<Vec<_>> = PEOPLE
+        let all: StdResult<Vec<_>> = people
             .range_raw(&store, None, None, Order::Ascending)
             .collect();
         let all = all.unwrap();
@@ -484,7 +552,7 @@ mod test {
         );
 
         // let's try to iterate over a range
-        let all: StdResult<Vec<_>> = PEOPLE
+        let all: StdResult<Vec<_>> = people
             .range_raw(
                 &store,
                 Some(Bound::inclusive(b"j" as &[u8])),
@@ -500,7 +568,7 @@ mod test {
         );
 
         // let's try to iterate over a more restrictive range
-        let all: StdResult<Vec<_>> = PEOPLE
+        let all: StdResult<Vec<_>> = people
             .range_raw(
                 &store,
                 Some(Bound::inclusive(b"jo" as &[u8])),
@@ -513,9 +581,11 @@ mod test {
         assert_eq!(all, vec![(b"john".to_vec(), data)]);
     }
 
-    #[test]
+    #[rstest]
     #[cfg(feature = "iterator")]
-    fn range_simple_string_key() {
+    fn range_simple_string_key(
+        #[values(PEOPLE, B_PEOPLE, BR_PEOPLE)] people: Map<Key, Data, impl Serde<Data>>,
+    ) {
         let mut store = MockStorage::new();
 
         // save and load on three keys
@@ -551,7 +621,7 @@ mod test {
         );
 
         // let's try to iterate over a range
-        let all: StdResult<Vec<_>> = PEOPLE
+        let all: StdResult<Vec<_>> = people
             .range(&store, b"j".inclusive_bound(), None, Order::Ascending)
             .collect();
         let all = all.unwrap();
@@ -562,7 +632,7 @@ mod test {
         );
 
         // let's try to iterate over a more restrictive range
-        let all: StdResult<Vec<_>> = PEOPLE
+        let all: StdResult<Vec<_>> = people
             .range(&store, b"jo".inclusive_bound(), None, Order::Ascending)
             .collect();
         let all = all.unwrap();
@@ -570,9 +640,11 @@ mod test {
         assert_eq!(all, vec![(b"john".to_vec(), data)]);
     }
 
-    #[test]
+    #[rstest]
     #[cfg(feature = "iterator")]
-    fn range_simple_integer_key() {
+    fn range_simple_integer_key(
+        #[values(PEOPLE_ID, B_PEOPLE_ID, BR_PEOPLE_ID)] people_id: Map<u32, Data, impl Serde<Data>>,
+    ) {
         let mut store = MockStorage::new();
 
         // save and load on two keys
@@ -580,16 +652,16 @@ mod test {
             name: "John".to_string(),
             age: 32,
         };
-        PEOPLE_ID.save(&mut store, 1234, &data).unwrap();
+        people_id.save(&mut store, 1234, &data).unwrap();
 
         let data2 = Data {
             name: "Jim".to_string(),
             age: 44,
         };
-        PEOPLE_ID.save(&mut store, 56, &data2).unwrap();
+        people_id.save(&mut store, 56, &data2).unwrap();
 
         // let's try to iterate!
-        let all: StdResult<Vec<_>> = PEOPLE_ID
+        let all: StdResult<Vec<_>> = people_id
             .range(&store, None, None, Order::Ascending)
             .collect();
         let all = all.unwrap();
@@ -597,7 +669,7 @@ mod test {
         assert_eq!(all, vec![(56, data2.clone()), (1234, data.clone())]);
 
         // let's try to iterate over a range
-        let all: StdResult<Vec<_>> = PEOPLE_ID
+        let all: StdResult<Vec<_>> = people_id
             .range(
                 &store,
                 Some(Bound::inclusive(56u32)),
@@ -610,7 +682,7 @@ mod test {
         assert_eq!(all, vec![(56, data2), (1234, data.clone())]);
 
         // let's try to iterate over a more restrictive range
-        let all: StdResult<Vec<_>> = PEOPLE_ID
+        let all: StdResult<Vec<_>> = people_id
             .range(
                 &store,
                 Some(Bound::inclusive(57u32)),
@@ -857,24 +929,26 @@ mod test {
         assert_eq!(new, vec![(-1234, data), (-56, data2), (50, data3)]);
     }
 
-    #[test]
+    #[rstest]
     #[cfg(feature = "iterator")]
-    fn range_raw_composite_key() {
+    fn range_raw_composite_key(
+        #[values(ALLOWANCE, B_ALLOWANCE, BR_ALLOWANCE)] allowance: Map<AKey, u64, impl Serde<u64>>,
+    ) {
         let mut store = MockStorage::new();
 
         // save and load on three keys, one under different owner
-        ALLOWANCE
+        allowance
             .save(&mut store, (b"owner", b"spender"), &1000)
             .unwrap();
-        ALLOWANCE
+        allowance
             .save(&mut store, (b"owner", b"spender2"), &3000)
             .unwrap();
-        ALLOWANCE
+        allowance
             .save(&mut store, (b"owner2", b"spender"), &5000)
             .unwrap();
 
         // let's try to iterate!
-        let all: StdResult<Vec<_>> = ALLOWANCE
+        let all: StdResult<Vec<_>> = allowance
             .range_raw(&store, None, None, Order::Ascending)
             .collect();
         let all = all.unwrap();
@@ -889,7 +963,7 @@ mod test {
         );
 
         // let's try to iterate over a prefix
-        let all: StdResult<Vec<_>> = ALLOWANCE
+        let all: StdResult<Vec<_>> = allowance
             .prefix(b"owner")
             .range_raw(&store, None, None, Order::Ascending)
             .collect();
@@ -901,24 +975,26 @@ mod test {
         );
     }
 
-    #[test]
+    #[rstest]
     #[cfg(feature = "iterator")]
-    fn range_composite_key() {
+    fn range_composite_key(
+        #[values(ALLOWANCE, B_ALLOWANCE, BR_ALLOWANCE)] allowance: Map<AKey, u64, impl Serde<u64>>,
+    ) {
         let mut store = MockStorage::new();
 
         // save and load on three keys, one under different owner
-        ALLOWANCE
+        allowance
             .save(&mut store, (b"owner", b"spender"), &1000)
             .unwrap();
-        ALLOWANCE
+        allowance
             .save(&mut store, (b"owner", b"spender2"), &3000)
             .unwrap();
-        ALLOWANCE
+        allowance
             .save(&mut store, (b"owner2", b"spender"), &5000)
             .unwrap();
 
         // let's try to iterate!
-        let all: StdResult<Vec<_>> = ALLOWANCE
+        let all: StdResult<Vec<_>> = allowance
             .range(&store, None, None, Order::Ascending)
             .collect();
         let all = all.unwrap();
@@ -933,7 +1009,7 @@ mod test {
         );
 
         // let's try to iterate over a prefix
-        let all: StdResult<Vec<_>> = ALLOWANCE
+        let all: StdResult<Vec<_>> = allowance
             .prefix(b"owner")
             .range(&store, None, None, Order::Ascending)
             .collect();
@@ -945,7 +1021,7 @@ mod test {
         );
 
         // let's try to iterate over a prefixed restricted inclusive range
-        let all: StdResult<Vec<_>> = ALLOWANCE
+        let all: StdResult<Vec<_>> = allowance
             .prefix(b"owner")
             .range(&store, b"spender".inclusive_bound(), None, Order::Ascending)
             .collect();
@@ -957,7 +1033,7 @@ mod test {
         );
 
         // let's try to iterate over a prefixed restricted exclusive range
-        let all: StdResult<Vec<_>> = ALLOWANCE
+        let all: StdResult<Vec<_>> = allowance
             .prefix(b"owner")
             .range(&store, b"spender".exclusive_bound(), None, Order::Ascending)
             .collect();
@@ -966,27 +1042,29 @@ mod test {
         assert_eq!(all, vec![(b"spender2".to_vec(), 3000),]);
     }
 
-    #[test]
+    #[rstest]
     #[cfg(feature = "iterator")]
-    fn range_raw_triple_key() {
+    fn range_raw_triple_key(
+        #[values(TRIPLE, B_TRIPLE, BR_TRIPLE)] triple: Map<TKey, u64, impl Serde<u64>>,
+    ) {
         let mut store = MockStorage::new();
 
         // save and load on three keys, one under different owner
-        TRIPLE
+        triple
             .save(&mut store, (b"owner", 9, "recipient"), &1000)
             .unwrap();
-        TRIPLE
+        triple
             .save(&mut store, (b"owner", 9, "recipient2"), &3000)
             .unwrap();
-        TRIPLE
+        triple
             .save(&mut store, (b"owner", 10, "recipient3"), &3000)
             .unwrap();
-        TRIPLE
+        triple
             .save(&mut store, (b"owner2", 9, "recipient"), &5000)
             .unwrap();
 
         // let's try to iterate!
-        let all: StdResult<Vec<_>> = TRIPLE
+        let all: StdResult<Vec<_>> = triple
             .range_raw(&store, None, None, Order::Ascending)
             .collect();
         let all = all.unwrap();
@@ -1014,7 +1092,7 @@ mod test {
         );
 
         // let's iterate over a prefix
-        let all: StdResult<Vec<_>> = TRIPLE
+        let all: StdResult<Vec<_>> = triple
             .prefix((b"owner", 9))
             .range_raw(&store, None, None, Order::Ascending)
             .collect();
@@ -1029,7 +1107,7 @@ mod test {
         );
 
         // let's iterate over a sub prefix
-        let all: StdResult<Vec<_>> = TRIPLE
+        let all: StdResult<Vec<_>> = triple
             .sub_prefix(b"owner")
             .range_raw(&store, None, None, Order::Ascending)
             .collect();
@@ -1046,22 +1124,24 @@ mod test {
         );
     }
 
-    #[test]
+    #[rstest]
     #[cfg(feature = "iterator")]
-    fn range_triple_key() {
+    fn range_triple_key(
+        #[values(TRIPLE, B_TRIPLE, BR_TRIPLE)] triple: Map<TKey, u64, impl Serde<u64>>,
+    ) {
         let mut store = MockStorage::new();
 
         // save and load on three keys, one under different owner
-        TRIPLE
+        triple
             .save(&mut store, (b"owner", 9u8, "recipient"), &1000)
             .unwrap();
-        TRIPLE
+        triple
             .save(&mut store, (b"owner", 9u8, "recipient2"), &3000)
             .unwrap();
-        TRIPLE
+        triple
             .save(&mut store, (b"owner", 10u8, "recipient3"), &3000)
             .unwrap();
-        TRIPLE
+        triple
             .save(&mut store, (b"owner2", 9u8, "recipient"), &5000)
             .unwrap();
 
@@ -1080,7 +1160,7 @@ mod test {
         );
 
         // let's iterate over a sub_prefix
-        let all: StdResult<Vec<_>> = TRIPLE
+        let all: StdResult<Vec<_>> = triple
             .sub_prefix(b"owner")
             .range(&store, None, None, Order::Ascending)
             .collect();
@@ -1096,7 +1176,7 @@ mod test {
         );
 
         // let's iterate over a prefix
-        let all: StdResult<Vec<_>> = TRIPLE
+        let all: StdResult<Vec<_>> = triple
             .prefix((b"owner", 9))
             .range(&store, None, None, Order::Ascending)
             .collect();
@@ -1111,7 +1191,7 @@ mod test {
         );
 
         // let's try to iterate over a prefixed restricted inclusive range
-        let all: StdResult<Vec<_>> = TRIPLE
+        let all: StdResult<Vec<_>> = triple
             .prefix((b"owner", 9))
             .range(
                 &store,
@@ -1131,7 +1211,7 @@ mod test {
         );
 
         // let's try to iterate over a prefixed restricted exclusive range
-        let all: StdResult<Vec<_>> = TRIPLE
+        let all: StdResult<Vec<_>> = triple
             .prefix((b"owner", 9))
             .range(
                 &store,
@@ -1146,7 +1226,9 @@ mod test {
     }
 
     #[rstest]
-    fn basic_update(#[values(ALLOWANCE, B_ALLOWANCE)] allowance: Map<AKey, u64, impl Serde>) {
+    fn basic_update(
+        #[values(ALLOWANCE, B_ALLOWANCE, BR_ALLOWANCE)] allowance: Map<AKey, u64, impl Serde<u64>>,
+    ) {
         let mut store = MockStorage::new();
 
         let add_ten = |a: Option<u64>| -> StdResult<_> { Ok(a.unwrap_or_default() + 10) };
@@ -1161,7 +1243,46 @@ mod test {
     }
 
     #[rstest]
-    fn readme_works(#[values(PEOPLE, B_PEOPLE)] people: Map<Key, Data, impl Serde>) -> StdResult<()> {
+    #[cfg(feature = "iterator")]
+    fn clear_works(
+        #[values(ALLOWANCE, B_ALLOWANCE, BR_ALLOWANCE)] allowance: Map<AKey, u64, impl Serde<u64>>,
+    ) {
+        let mut store = MockStorage::new();
+        for owner in [b"alice", b"bobby"] {
+            for spender in [b"one", b"two", b"six"] {
+                allowance.save(&mut store, (owner, spender), &1).unwrap();
+            }
+        }
+        PEOPLE_ID
+            .save(
+                &mut store,
+                1,
+                &Data {
+                    name: "John".to_string(),
+                    age: 32,
+                },
+            )
+            .unwrap();
+
+        // clear a single owner
+        assert!(allowance.prefix(b"alice").clear(&mut store, None));
+        assert!(allowance.prefix(b"alice").is_empty(&store));
+        assert!(!allowance.prefix(b"bobby").is_empty(&store));
+
+        // clear the rest of the map in two steps
+        assert!(!allowance.clear(&mut store, Some(2)));
+        assert!(allowance.has(&store, (b"bobby", b"two")));
+        assert!(allowance.clear(&mut store, Some(2)));
+        assert!(allowance.is_empty(&store));
+
+        // other maps are left alone
+        assert!(!PEOPLE_ID.is_empty(&store));
+    }
+
+    #[rstest]
+    fn readme_works(
+        #[values(PEOPLE, B_PEOPLE, BR_PEOPLE)] people: Map<Key, Data, impl Serde<Data>>,
+    ) -> StdResult<()> {
         let mut store = MockStorage::new();
         let data = Data {
             name: "John".to_string(),
@@ -1214,7 +1335,9 @@ mod test {
     }
 
     #[rstest]
-    fn readme_works_composite_keys(#[values(ALLOWANCE, B_ALLOWANCE)] allowance: Map<AKey, u64, impl Serde>) -> StdResult<()> {
+    fn readme_works_composite_keys(
+        #[values(ALLOWANCE, B_ALLOWANCE, BR_ALLOWANCE)] allowance: Map<AKey, u64, impl Serde<u64>>,
+    ) -> StdResult<()> {
         let mut store = MockStorage::new();
 
         // save and load on a composite key
@@ -1239,9 +1362,10 @@ mod test {
     }
 
     #[rstest]
-    fn readme_works_with_path(#[values(PEOPLE, B_PEOPLE)] people: Map<Key, Data, impl Serde>,
-    #[values(ALLOWANCE, B_ALLOWANCE)] allowance: Map<AKey, u64, impl Serde>
-) -> StdResult<()> {
+    fn readme_works_with_path(
+        #[values(PEOPLE, B_PEOPLE, BR_PEOPLE)] people: Map<Key, Data, impl Serde<Data>>,
+        #[values(ALLOWANCE, B_ALLOWANCE, BR_ALLOWANCE)] allowance: Map<AKey, u64, impl Serde<u64>>,
+    ) -> StdResult<()> {
         let mut store = MockStorage::new();
         let data = Data {
             name: "John".to_string(),
@@ -1275,9 +1399,12 @@ mod test {
         Ok(())
     }
 
-    #[test]
+    #[rstest]
     #[cfg(feature = "iterator")]
-    fn readme_with_range_raw() -> StdResult<()> {
+    fn readme_with_range_raw(
+        #[values(PEOPLE, B_PEOPLE, BR_PEOPLE)] people: Map<Key, Data, impl Serde<Data>>,
+        #[values(ALLOWANCE, B_ALLOWANCE, BR_ALLOWANCE)] allowance: Map<AKey, u64, impl Serde<u64>>,
+    ) -> StdResult<()> {
         let mut store = MockStorage::new();
 
         // save and load on two keys
@@ -1293,7 +1420,7 @@ mod test {
         people.save(&mut store, b"jim", &data2)?;
 
         // iterate over them all
-        let all: StdResult<Vec<_>> = PEOPLE
+        let all: StdResult<Vec<_>> = people
             .range_raw(&store, None, None, Order::Ascending)
             .collect();
         assert_eq!(
@@ -1302,7 +1429,7 @@ mod test {
         );
 
         // or just show what is after jim
-        let all: StdResult<Vec<_>> = PEOPLE
+        let all: StdResult<Vec<_>> = people
             .range_raw(
                 &store,
                 Some(Bound::exclusive(b"jim" as &[u8])),
@@ -1318,7 +1445,7 @@ mod test {
         allowance.save(&mut store, (b"owner2", b"spender"), &5000)?;
 
         // get all under one key
-        let all: StdResult<Vec<_>> = ALLOWANCE
+        let all: StdResult<Vec<_>> = allowance
             .prefix(b"owner")
             .range_raw(&store, None, None, Order::Ascending)
             .collect();
@@ -1328,7 +1455,7 @@ mod test {
         );
 
         // Or ranges between two items (even reverse)
-        let all: StdResult<Vec<_>> = ALLOWANCE
+        let all: StdResult<Vec<_>> = allowance
             .prefix(b"owner")
             .range_raw(
                 &store,
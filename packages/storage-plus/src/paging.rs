@@ -0,0 +1,32 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// A page of items read from an `AppendStore` or `DequeStore`, together with the total number
+/// of items in the collection so that clients can tell how many pages there are.
+///
+/// This can be returned from queries as it is.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: u32,
+}
+
+// cosmwasm-std only exports `Order` with its `iterator` feature, but reading by position
+// doesn't iterate over storage, so the same enum is defined here when that feature is off.
+
+/// The order `AppendStore` and `DequeStore` pages and ranges are read in.
+#[cfg(feature = "iterator")]
+pub use cosmwasm_std::Order;
+
+/// The order `AppendStore` and `DequeStore` pages and ranges are read in.
+#[cfg(not(feature = "iterator"))]
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Order {
+    Ascending = 1,
+    Descending = 2,
+}
+
+/// Items of an `AppendStore` or `DequeStore` paired with their positions.
+pub(crate) type PositionedItems<'c, T> =
+    Box<dyn Iterator<Item = cosmwasm_std::StdResult<(u32, T)>> + 'c>;
@@ -1,8 +1,7 @@
-use serde::de::DeserializeOwned;
-use serde::Serialize;
 use std::marker::PhantomData;
 
 use crate::{Json, Serde};
+use crate::serialization::{may_load_and_upgrade, Upgrade, Versioned};
 use crate::helpers::{may_deserialize, must_deserialize, nested_namespaces_with_key};
 use crate::keys::Key;
 use cosmwasm_std::{StdError, StdResult, Storage};
@@ -11,8 +10,7 @@ use std::ops::Deref;
 #[derive(Debug, Clone)]
 pub struct Path<T, Ser = Json>
 where
-    T: Serialize + DeserializeOwned,
-    Ser: Serde,
+    Ser: Serde<T>,
 {
     /// all namespaces prefixes and concatenated with the key
     pub(crate) storage_key: Vec<u8>,
@@ -23,8 +21,7 @@ where
 
 impl<T, Ser> Deref for Path<T, Ser>
 where
-    T: Serialize + DeserializeOwned,
-    Ser: Serde
+    Ser: Serde<T>
 {
     type Target = [u8];
 
@@ -35,8 +32,7 @@ where
 
 impl<T, Ser> Path<T, Ser>
 where
-    T: Serialize + DeserializeOwned,
-    Ser: Serde,
+    Ser: Serde<T>,
 {
     pub fn new(namespace: &[u8], keys: &[&[u8]]) -> Self {
         let l = keys.len();
@@ -100,3 +96,15 @@ where
         Ok(output)
     }
 }
+
+impl<T, Inner> Path<T, Versioned<Inner>>
+where
+    T: Upgrade<Inner>,
+    Inner: Serde<T>,
+{
+    /// may_load_and_upgrade works like may_load, but if the data was stored under an older
+    /// version it is written back in the current one, so the upgrade only runs once.
+    pub fn may_load_and_upgrade(&self, store: &mut dyn Storage) -> StdResult<Option<T>> {
+        may_load_and_upgrade::<T, Inner>(store, &self.storage_key)
+    }
+}
@@ -1,6 +1,4 @@
 #![cfg(feature = "iterator")]
-use serde::de::DeserializeOwned;
-use serde::Serialize;
 use std::marker::PhantomData;
 
 use cosmwasm_std::{Order, Record, StdResult, Storage};
@@ -11,48 +9,47 @@ use crate::de::KeyDeserialize;
 use crate::helpers::{namespaces_with_key, nested_namespaces_with_key};
 use crate::iter_helpers::{concat, deserialize_kv, deserialize_v, trim};
 use crate::keys::Key;
-use crate::{Bound, Prefixer, PrimaryKey};
+use crate::{Bound, Json, Prefixer, PrimaryKey, Serde};
 
 type DeserializeVFn<T> = fn(&dyn Storage, &[u8], Record) -> StdResult<Record<T>>;
 
 type DeserializeKvFn<K, T> =
     fn(&dyn Storage, &[u8], Record) -> StdResult<(<K as KeyDeserialize>::Output, T)>;
 
-pub fn default_deserializer_v<T: DeserializeOwned>(
+pub fn default_deserializer_v<T, Ser: Serde<T>>(
     _: &dyn Storage,
     _: &[u8],
     raw: Record,
 ) -> StdResult<Record<T>> {
-    deserialize_v(raw)
+    deserialize_v::<T, Ser>(raw)
 }
 
-pub fn default_deserializer_kv<K: KeyDeserialize, T: DeserializeOwned>(
+pub fn default_deserializer_kv<K: KeyDeserialize, T, Ser: Serde<T>>(
     _: &dyn Storage,
     _: &[u8],
     raw: Record,
 ) -> StdResult<(K::Output, T)> {
-    deserialize_kv::<K, T>(raw)
+    deserialize_kv::<K, T, Ser>(raw)
 }
 
 #[derive(Clone)]
-pub struct Prefix<K, T, B = Vec<u8>>
+pub struct Prefix<K, T, B = Vec<u8>, Ser = Json>
 where
     K: KeyDeserialize,
-    T: Serialize + DeserializeOwned,
 {
     /// all namespaces prefixes and concatenated with the key
     storage_prefix: Vec<u8>,
     // see https://doc.rust-lang.org/std/marker/struct.PhantomData.html#unused-type-parameters for why this is needed
-    data: PhantomData<(T, B)>,
+    data: PhantomData<(T, B, Ser)>,
     pk_name: Vec<u8>,
     de_fn_kv: DeserializeKvFn<K, T>,
     de_fn_v: DeserializeVFn<T>,
 }
 
-impl<K, T> Deref for Prefix<K, T>
+impl<K, T, Ser> Deref for Prefix<K, T, Vec<u8>, Ser>
 where
     K: KeyDeserialize,
-    T: Serialize + DeserializeOwned,
+    Ser: Serde<T>,
 {
     type Target = [u8];
 
@@ -61,18 +58,18 @@ where
     }
 }
 
-impl<K, T, B> Prefix<K, T, B>
+impl<K, T, B, Ser> Prefix<K, T, B, Ser>
 where
     K: KeyDeserialize,
-    T: Serialize + DeserializeOwned,
+    Ser: Serde<T>,
 {
     pub fn new(top_name: &[u8], sub_names: &[Key]) -> Self {
         Prefix::with_deserialization_functions(
             top_name,
             sub_names,
             &[],
-            default_deserializer_kv::<K, T>,
-            default_deserializer_v,
+            default_deserializer_kv::<K, T, Ser>,
+            default_deserializer_v::<T, Ser>,
         )
     }
 
@@ -92,13 +89,46 @@ where
             de_fn_v,
         }
     }
+
+    /// Removes the entries under this prefix from storage, at most `limit` of them if given.
+    ///
+    /// Calling it again resumes where the previous call stopped, so a large prefix can be cleared
+    /// over several transactions. Returns true once no entries are left.
+    pub fn clear(&self, store: &mut dyn Storage, limit: Option<usize>) -> bool {
+        // only hold a few keys at a time, as the prefix may be large
+        const TAKE: usize = 10;
+        let mut left = limit.unwrap_or(usize::MAX);
+        while left > 0 {
+            let take = TAKE.min(left);
+            let keys: Vec<_> =
+                range_with_prefix(store, &self.storage_prefix, None, None, Order::Ascending)
+                    .take(take)
+                    .map(|(k, _)| concat(&self.storage_prefix, &k))
+                    .collect();
+            for key in &keys {
+                store.remove(key);
+            }
+            if keys.len() < take {
+                return true;
+            }
+            left -= take;
+        }
+        self.is_empty(store)
+    }
+
+    /// Returns true if there are no entries under this prefix
+    pub fn is_empty(&self, store: &dyn Storage) -> bool {
+        range_with_prefix(store, &self.storage_prefix, None, None, Order::Ascending)
+            .next()
+            .is_none()
+    }
 }
 
-impl<'b, K, T, B> Prefix<K, T, B>
+impl<'b, K, T, B, Ser> Prefix<K, T, B, Ser>
 where
     B: PrimaryKey<'b>,
     K: KeyDeserialize,
-    T: Serialize + DeserializeOwned,
+    Ser: Serde<T>,
 {
     pub fn range_raw<'a>(
         &self,
@@ -304,10 +334,10 @@ mod test {
         // manually create this - not testing nested prefixes here
         let prefix: Prefix<Vec<u8>, u64> = Prefix {
             storage_prefix: b"foo".to_vec(),
-            data: PhantomData::<(u64, _)>,
+            data: PhantomData::<(u64, _, _)>,
             pk_name: vec![],
-            de_fn_kv: |_, _, kv| deserialize_kv::<Vec<u8>, u64>(kv),
-            de_fn_v: |_, _, kv| deserialize_v(kv),
+            de_fn_kv: |_, _, kv| deserialize_kv::<Vec<u8>, u64, Json>(kv),
+            de_fn_v: |_, _, kv| deserialize_v::<u64, Json>(kv),
         };
 
         // set some data, we care about "foo" prefix
@@ -438,4 +468,32 @@ mod test {
             .collect();
         assert_eq!(res.unwrap().as_slice(), &[]);
     }
+
+    #[test]
+    fn clear_in_batches() {
+        let mut store = MockStorage::new();
+        let prefix: Prefix<Vec<u8>, u64> = Prefix::new(b"foo", &[]);
+        let other: Prefix<Vec<u8>, u64> = Prefix::new(b"fo", &[]);
+
+        for i in 0..25u8 {
+            store.set(&concat(&prefix, &[i]), b"1");
+        }
+        store.set(&concat(&other, b"o"), b"2");
+        assert!(!prefix.is_empty(&store));
+
+        assert!(!prefix.clear(&mut store, Some(12)));
+        assert_eq!(
+            prefix
+                .keys_raw(&store, None, None, Order::Ascending)
+                .count(),
+            13
+        );
+        // removing exactly what is left still reports the prefix as cleared
+        assert!(prefix.clear(&mut store, Some(13)));
+        assert!(prefix.is_empty(&store));
+        assert!(prefix.clear(&mut store, None));
+
+        // other prefixes are left alone
+        assert!(!other.is_empty(&store));
+    }
 }
@@ -0,0 +1,150 @@
+use cosmwasm_std::{
+    Addr, CustomQuery, OverflowError, OverflowOperation, QuerierWrapper, StdError, StdResult,
+    Storage,
+};
+
+use crate::helpers::query_deserialize;
+use crate::{Item, Json, Serde};
+
+/// Sequence hands out auto-incrementing ids, starting at 1.
+///
+/// It is stored like an `Item<u64>` under the given key, and reads as 0 until the first id has
+/// been taken.
+///
+/// ```rust
+/// # use cosmwasm_std::testing::MockStorage;
+/// use secret_storage_plus::Sequence;
+///
+/// const PROPOSAL_ID: Sequence = Sequence::new("proposal_id");
+///
+/// # let mut store = MockStorage::new();
+/// assert_eq!(PROPOSAL_ID.next_val(&mut store).unwrap(), 1);
+/// assert_eq!(PROPOSAL_ID.next_val(&mut store).unwrap(), 2);
+/// assert_eq!(PROPOSAL_ID.current(&store).unwrap(), 2);
+/// ```
+pub struct Sequence<'a, Ser = Json> {
+    item: Item<'a, u64, Ser>,
+}
+
+impl<'a, Ser> Sequence<'a, Ser> {
+    pub const fn new(storage_key: &'a str) -> Self {
+        Sequence {
+            item: Item::new(storage_key),
+        }
+    }
+}
+
+impl<'a, Ser> Sequence<'a, Ser>
+where
+    Ser: Serde<u64>,
+{
+    // this gets the path of the data to use elsewhere
+    pub fn as_slice(&self) -> &[u8] {
+        self.item.as_slice()
+    }
+
+    /// current returns the last id that was handed out, or 0 if there was none
+    pub fn current(&self, store: &dyn Storage) -> StdResult<u64> {
+        Ok(self.item.may_load(store)?.unwrap_or_default())
+    }
+
+    /// next_val increments the sequence and returns the new value.
+    /// Returns an error if the sequence has reached `u64::MAX`.
+    pub fn next_val(&self, store: &mut dyn Storage) -> StdResult<u64> {
+        let current = self.current(store)?;
+        let next = current.checked_add(1).ok_or_else(|| {
+            StdError::overflow(OverflowError::new(OverflowOperation::Add, current, 1))
+        })?;
+        self.item.save(store, &next)?;
+        Ok(next)
+    }
+
+    /// set moves the sequence to `value`, so the next id handed out will be `value + 1`
+    pub fn set(&self, store: &mut dyn Storage, value: u64) -> StdResult<()> {
+        self.item.save(store, &value)
+    }
+
+    /// If you import the proper Sequence from the remote contract, this will let you read its
+    /// current value from a remote contract in a type-safe way.
+    pub fn query<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+    ) -> StdResult<u64> {
+        Ok(query_deserialize::<u64, Ser, Q>(
+            querier,
+            remote_contract,
+            remote_contract_code_hash,
+            self.as_slice(),
+        )?
+        .unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use cosmwasm_std::testing::{MockQuerier, MockStorage};
+    use cosmwasm_std::{to_binary, ContractResult, Empty, SystemResult, WasmQuery};
+
+    use crate::Bincode2;
+
+    const SEQ: Sequence = Sequence::new("seq");
+
+    #[test]
+    fn next_val_increments() {
+        let mut store = MockStorage::new();
+        assert_eq!(0, SEQ.current(&store).unwrap());
+
+        assert_eq!(1, SEQ.next_val(&mut store).unwrap());
+        assert_eq!(2, SEQ.next_val(&mut store).unwrap());
+        assert_eq!(3, SEQ.next_val(&mut store).unwrap());
+        assert_eq!(3, SEQ.current(&store).unwrap());
+
+        // it is stored as a plain item
+        assert_eq!(3, Item::<u64>::new("seq").load(&store).unwrap());
+    }
+
+    #[test]
+    fn set_and_overflow() {
+        let mut store = MockStorage::new();
+        let seq: Sequence<Bincode2> = Sequence::new("seq");
+
+        seq.set(&mut store, 41).unwrap();
+        assert_eq!(42, seq.next_val(&mut store).unwrap());
+
+        seq.set(&mut store, u64::MAX).unwrap();
+        assert!(matches!(
+            seq.next_val(&mut store),
+            Err(StdError::Overflow { .. })
+        ));
+        assert_eq!(u64::MAX, seq.current(&store).unwrap());
+    }
+
+    #[test]
+    fn query_remote_sequence() {
+        let mut querier = MockQuerier::<Empty>::new(&[]);
+        querier.update_wasm(|query| {
+            let value = match query {
+                WasmQuery::Smart { contract_addr, .. } if contract_addr == "counter" => {
+                    to_binary(&7u64).unwrap()
+                }
+                _ => Default::default(),
+            };
+            SystemResult::Ok(ContractResult::Ok(value))
+        });
+        let querier = QuerierWrapper::<Empty>::new(&querier);
+
+        let value = SEQ
+            .query(&querier, Addr::unchecked("counter"), "hash".to_string())
+            .unwrap();
+        assert_eq!(7, value);
+
+        let value = SEQ
+            .query(&querier, Addr::unchecked("fresh"), "hash".to_string())
+            .unwrap();
+        assert_eq!(0, value);
+    }
+}
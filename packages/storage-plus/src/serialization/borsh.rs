@@ -0,0 +1,20 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use cosmwasm_std::{StdError, StdResult};
+use std::any::type_name;
+
+use crate::Serde;
+
+/// Use borsh for serialization.
+#[derive(Copy, Clone, Debug)]
+pub struct Borsh;
+
+impl<T: BorshSerialize + BorshDeserialize> Serde<T> for Borsh {
+    fn serialize(obj: &T) -> StdResult<Vec<u8>> {
+        obj.try_to_vec()
+            .map_err(|err| StdError::serialize_err(type_name::<T>(), err))
+    }
+
+    fn deserialize(data: &[u8]) -> StdResult<T> {
+        T::try_from_slice(data).map_err(|err| StdError::parse_err(type_name::<T>(), err))
+    }
+}
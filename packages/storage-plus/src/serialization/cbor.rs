@@ -0,0 +1,24 @@
+use crate::Serde;
+use cosmwasm_std::{StdError, StdResult};
+use serde::{de::DeserializeOwned, Serialize};
+use std::any::type_name;
+
+/// Use CBOR for serialization.
+///
+/// Like json, structs are encoded with their field names, so fields can be added to a
+/// type (with `#[serde(default)]`) without breaking data that is already stored.
+#[derive(Copy, Clone, Debug)]
+pub struct Cbor;
+
+impl<T: Serialize + DeserializeOwned> Serde<T> for Cbor {
+    fn serialize(obj: &T) -> StdResult<Vec<u8>> {
+        let mut buf = Vec::new();
+        ciborium::ser::into_writer(obj, &mut buf)
+            .map_err(|err| StdError::serialize_err(type_name::<T>(), err))?;
+        Ok(buf)
+    }
+
+    fn deserialize(data: &[u8]) -> StdResult<T> {
+        ciborium::de::from_reader(data).map_err(|err| StdError::parse_err(type_name::<T>(), err))
+    }
+}
@@ -0,0 +1,155 @@
+use std::any::type_name;
+use std::marker::PhantomData;
+
+use cosmwasm_std::{StdError, StdResult};
+
+use crate::{Json, Serde};
+
+/// Values that serialize to fewer bytes than this are stored uncompressed by default.
+pub const DEFAULT_COMPRESSION_THRESHOLD: usize = 256;
+
+const RAW: u8 = 0;
+const DEFLATE: u8 = 1;
+
+// favour output size over speed, since storage writes are charged per byte
+const LEVEL: u8 = 9;
+
+/// Wraps another serialization backend and deflates its output once it reaches `THRESHOLD`
+/// bytes, which lowers the gas spent on writing large values.
+///
+/// Every value is prefixed with a flag byte telling whether it was compressed, so small values
+/// stay readable as they are and only pay one extra byte. Values that do not shrink when
+/// compressed are also stored raw.
+///
+/// ```rust
+/// use secret_storage_plus::{Bincode2, Compressed, Item};
+///
+/// // compress anything over 1KB
+/// const ROOTS: Item<Vec<String>, Compressed<Bincode2, 1024>> = Item::new("roots");
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct Compressed<Inner = Json, const THRESHOLD: usize = DEFAULT_COMPRESSION_THRESHOLD>(
+    PhantomData<Inner>,
+);
+
+impl<T, Inner, const THRESHOLD: usize> Serde<T> for Compressed<Inner, THRESHOLD>
+where
+    Inner: Serde<T>,
+{
+    fn serialize(obj: &T) -> StdResult<Vec<u8>> {
+        let raw = Inner::serialize(obj)?;
+        if raw.len() >= THRESHOLD {
+            let compressed = miniz_oxide::deflate::compress_to_vec(&raw, LEVEL);
+            if compressed.len() < raw.len() {
+                let mut data = Vec::with_capacity(compressed.len() + 1);
+                data.push(DEFLATE);
+                data.extend(compressed);
+                return Ok(data);
+            }
+        }
+
+        let mut data = Vec::with_capacity(raw.len() + 1);
+        data.push(RAW);
+        data.extend(raw);
+        Ok(data)
+    }
+
+    fn deserialize(data: &[u8]) -> StdResult<T> {
+        match data.split_first() {
+            Some((&RAW, raw)) => Inner::deserialize(raw),
+            Some((&DEFLATE, compressed)) => {
+                let raw = miniz_oxide::inflate::decompress_to_vec(compressed)
+                    .map_err(|err| StdError::parse_err(type_name::<T>(), err))?;
+                Inner::deserialize(&raw)
+            }
+            Some((flag, _)) => Err(StdError::parse_err(
+                type_name::<T>(),
+                format!("unknown compression flag {}", flag),
+            )),
+            None => Err(StdError::parse_err(
+                type_name::<T>(),
+                "value is missing its compression flag",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cosmwasm_std::testing::MockStorage;
+    use cosmwasm_std::Storage;
+
+    use crate::{AppendStore, Bincode2, Item};
+
+    const SMALL: Item<String, Compressed<Bincode2>> = Item::new("small");
+    const BIG: Item<String, Compressed<Bincode2>> = Item::new("big");
+    const TIGHT: Item<String, Compressed<Bincode2, 16>> = Item::new("tight");
+
+    #[test]
+    fn small_values_stay_raw() {
+        let mut store = MockStorage::new();
+        let value = "hello".to_string();
+        SMALL.save(&mut store, &value).unwrap();
+
+        let stored = store.get(b"small").unwrap();
+        assert_eq!(stored[0], RAW);
+        assert_eq!(
+            &stored[1..],
+            Bincode2::serialize(&value).unwrap().as_slice()
+        );
+        assert_eq!(value, SMALL.load(&store).unwrap());
+    }
+
+    #[test]
+    fn large_values_are_compressed() {
+        let mut store = MockStorage::new();
+        let value = "merkle root ".repeat(100);
+        BIG.save(&mut store, &value).unwrap();
+
+        let stored = store.get(b"big").unwrap();
+        assert_eq!(stored[0], DEFLATE);
+        assert!(stored.len() < Bincode2::serialize(&value).unwrap().len());
+        assert_eq!(value, BIG.load(&store).unwrap());
+    }
+
+    #[test]
+    fn threshold_is_configurable() {
+        let mut store = MockStorage::new();
+        let value = "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".to_string();
+        TIGHT.save(&mut store, &value).unwrap();
+        assert_eq!(store.get(b"tight").unwrap()[0], DEFLATE);
+        assert_eq!(value, TIGHT.load(&store).unwrap());
+
+        SMALL.save(&mut store, &value).unwrap();
+        assert_eq!(store.get(b"small").unwrap()[0], RAW);
+    }
+
+    #[test]
+    fn incompressible_values_stay_raw() {
+        let value: Vec<u8> = (0..=255u8).map(|i| i.wrapping_mul(167) ^ 0x5a).collect();
+        let data = Compressed::<Bincode2, 16>::serialize(&value).unwrap();
+        assert_eq!(data[0], RAW);
+        let loaded: Vec<u8> = Compressed::<Bincode2, 16>::deserialize(&data).unwrap();
+        assert_eq!(value, loaded);
+    }
+
+    #[test]
+    fn rejects_unknown_flags() {
+        assert!(<Compressed<Bincode2> as Serde<String>>::deserialize(&[]).is_err());
+        assert!(<Compressed<Bincode2> as Serde<String>>::deserialize(&[7, 0]).is_err());
+    }
+
+    #[test]
+    fn works_with_append_store() {
+        let mut store = MockStorage::new();
+        let entries: AppendStore<String, Compressed<Bincode2>> = AppendStore::new("entries");
+        let big = "x".repeat(1000);
+        entries.push(&mut store, &big).unwrap();
+        entries.push(&mut store, &"y".to_string()).unwrap();
+
+        assert_eq!(big, entries.get_at(&store, 0).unwrap());
+        assert_eq!("y", entries.get_at(&store, 1).unwrap());
+    }
+}
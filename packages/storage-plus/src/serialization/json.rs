@@ -8,12 +8,12 @@ use crate::Serde;
 #[derive(Copy, Clone, Debug)]
 pub struct Json;
 
-impl Serde for Json {
-    fn serialize<T: Serialize>(obj: &T) -> StdResult<Vec<u8>> {
+impl<T: Serialize + DeserializeOwned> Serde<T> for Json {
+    fn serialize(obj: &T) -> StdResult<Vec<u8>> {
         cosmwasm_std::to_vec(obj)
     }
 
-    fn deserialize<T: DeserializeOwned>(data: &[u8]) -> StdResult<T> {
+    fn deserialize(data: &[u8]) -> StdResult<T> {
         cosmwasm_std::from_slice(data)
     }
 }
@@ -1,19 +1,41 @@
 use cosmwasm_std::StdResult;
-use serde::{de::DeserializeOwned, Serialize};
 
 mod bincode2;
+mod borsh;
+#[cfg(feature = "cbor")]
+mod cbor;
+#[cfg(feature = "compression")]
+mod compressed;
 mod json;
+#[cfg(feature = "msgpack")]
+mod msgpack;
+#[cfg(feature = "postcard")]
+mod postcard;
+mod versioned;
 
-/// This trait represents the ability to both serialize and deserialize using a specific format.
+/// This trait represents the ability to both serialize and deserialize `T` using a specific format.
 ///
 /// This is useful for types that want to have a default mode of serialization, but want
 /// to allow users to override it if they want to.
 ///
-/// It is intentionally simple at the moment to keep the implementation easy.
-pub trait Serde {
-    fn serialize<T: Serialize>(obj: &T) -> StdResult<Vec<u8>>;
-    fn deserialize<T: DeserializeOwned>(data: &[u8]) -> StdResult<T>;
+/// The trait is generic over the value type rather than bound to serde, so that formats
+/// with their own traits (such as borsh) can be plugged in as well. Implementors will
+/// usually provide a blanket implementation for every type their format supports.
+pub trait Serde<T> {
+    fn serialize(obj: &T) -> StdResult<Vec<u8>>;
+    fn deserialize(data: &[u8]) -> StdResult<T>;
 }
 
 pub use self::bincode2::Bincode2;
+pub use self::borsh::Borsh;
+#[cfg(feature = "cbor")]
+pub use self::cbor::Cbor;
+#[cfg(feature = "compression")]
+pub use self::compressed::{Compressed, DEFAULT_COMPRESSION_THRESHOLD};
 pub use self::json::Json;
+#[cfg(feature = "msgpack")]
+pub use self::msgpack::MsgPack;
+#[cfg(feature = "postcard")]
+pub use self::postcard::Postcard;
+pub(crate) use self::versioned::may_load_and_upgrade;
+pub use self::versioned::{Upgrade, Version, Versioned};
@@ -0,0 +1,21 @@
+use crate::Serde;
+use cosmwasm_std::{StdError, StdResult};
+use serde::{de::DeserializeOwned, Serialize};
+use std::any::type_name;
+
+/// Use MessagePack for serialization.
+///
+/// Structs are encoded as maps keyed by field name, so fields can be added to a type
+/// (with `#[serde(default)]`) without breaking data that is already stored.
+#[derive(Copy, Clone, Debug)]
+pub struct MsgPack;
+
+impl<T: Serialize + DeserializeOwned> Serde<T> for MsgPack {
+    fn serialize(obj: &T) -> StdResult<Vec<u8>> {
+        rmp_serde::to_vec_named(obj).map_err(|err| StdError::serialize_err(type_name::<T>(), err))
+    }
+
+    fn deserialize(data: &[u8]) -> StdResult<T> {
+        rmp_serde::from_slice(data).map_err(|err| StdError::parse_err(type_name::<T>(), err))
+    }
+}
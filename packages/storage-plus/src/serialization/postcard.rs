@@ -0,0 +1,21 @@
+use crate::Serde;
+use cosmwasm_std::{StdError, StdResult};
+use serde::{de::DeserializeOwned, Serialize};
+use std::any::type_name;
+
+/// Use postcard for serialization.
+///
+/// This produces the smallest output of the available formats, but like bincode2 it
+/// does not store field names, so changing a stored type requires a migration.
+#[derive(Copy, Clone, Debug)]
+pub struct Postcard;
+
+impl<T: Serialize + DeserializeOwned> Serde<T> for Postcard {
+    fn serialize(obj: &T) -> StdResult<Vec<u8>> {
+        postcard::to_allocvec(obj).map_err(|err| StdError::serialize_err(type_name::<T>(), err))
+    }
+
+    fn deserialize(data: &[u8]) -> StdResult<T> {
+        postcard::from_bytes(data).map_err(|err| StdError::parse_err(type_name::<T>(), err))
+    }
+}
@@ -0,0 +1,314 @@
+use std::any::type_name;
+use std::marker::PhantomData;
+
+use cosmwasm_std::{StdError, StdResult, Storage};
+
+use crate::{Json, Serde};
+
+/// Version tag written in front of every value stored through [`Versioned`].
+pub type Version = u16;
+
+const TAG_LEN: usize = std::mem::size_of::<Version>();
+
+/// Wraps another serialization backend and prefixes every value with the version of its type,
+/// so that values written by an older version of a contract can still be read after the stored
+/// type has changed.
+///
+/// The stored type declares its current version and how to read older ones through [`Upgrade`].
+/// Values are upgraded in memory whenever they are loaded; use `may_load_and_upgrade` on `Item`
+/// or `Map` to also write the upgraded value back.
+///
+/// Note that data written without this wrapper carries no version tag and cannot be read by it.
+#[derive(Copy, Clone, Debug)]
+pub struct Versioned<Inner = Json>(PhantomData<Inner>);
+
+impl<Inner> Versioned<Inner> {
+    /// Returns the version tag of a serialized value.
+    pub fn version_of(data: &[u8]) -> StdResult<Version> {
+        match data.get(..TAG_LEN) {
+            Some(tag) => Ok(Version::from_be_bytes([tag[0], tag[1]])),
+            None => Err(StdError::parse_err(
+                type_name::<Self>(),
+                "value is missing its version tag",
+            )),
+        }
+    }
+}
+
+impl<T, Inner> Serde<T> for Versioned<Inner>
+where
+    T: Upgrade<Inner>,
+    Inner: Serde<T>,
+{
+    fn serialize(obj: &T) -> StdResult<Vec<u8>> {
+        let mut data = T::VERSION.to_be_bytes().to_vec();
+        data.extend(Inner::serialize(obj)?);
+        Ok(data)
+    }
+
+    fn deserialize(data: &[u8]) -> StdResult<T> {
+        let version = Self::version_of(data)?;
+        T::decode(version, &data[TAG_LEN..])
+    }
+}
+
+/// Declares the current version of a type stored through [`Versioned`], and how values written
+/// under older versions are turned into it.
+///
+/// Upgrades are chained by implementing this trait for every previous version of the type as
+/// well, each one reading its predecessor:
+///
+/// ```rust
+/// # use cosmwasm_std::StdResult;
+/// # use serde::{Deserialize, Serialize};
+/// use secret_storage_plus::{Json, Upgrade, Version};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct ConfigV1 {
+///     owner: String,
+/// }
+///
+/// impl Upgrade<Json> for ConfigV1 {
+///     const VERSION: Version = 1;
+/// }
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Config {
+///     owner: String,
+///     paused: bool,
+/// }
+///
+/// impl Upgrade<Json> for Config {
+///     const VERSION: Version = 2;
+///
+///     fn upgrade(version: Version, data: &[u8]) -> StdResult<Self> {
+///         let old = ConfigV1::decode(version, data)?;
+///         Ok(Config { owner: old.owner, paused: false })
+///     }
+/// }
+/// ```
+pub trait Upgrade<Ser>: Sized {
+    /// The version tag written for values of this type.
+    const VERSION: Version;
+
+    /// Reads `data` that was written under an older `version` of this type.
+    ///
+    /// The default implementation accepts no older versions.
+    fn upgrade(version: Version, data: &[u8]) -> StdResult<Self> {
+        let _ = data;
+        Err(StdError::parse_err(
+            type_name::<Self>(),
+            format!("unsupported version {}", version),
+        ))
+    }
+
+    /// Reads `data` written under `version`, upgrading it if it is not the current one.
+    fn decode(version: Version, data: &[u8]) -> StdResult<Self>
+    where
+        Ser: Serde<Self>,
+    {
+        if version == Self::VERSION {
+            Ser::deserialize(data)
+        } else {
+            Self::upgrade(version, data)
+        }
+    }
+}
+
+/// Loads the value at `key` and, if it was written under an older version, saves it back in the
+/// current one.
+pub(crate) fn may_load_and_upgrade<T, Inner>(
+    store: &mut dyn Storage,
+    key: &[u8],
+) -> StdResult<Option<T>>
+where
+    T: Upgrade<Inner>,
+    Inner: Serde<T>,
+{
+    let data = match store.get(key) {
+        Some(data) => data,
+        None => return Ok(None),
+    };
+    let value: T = Versioned::<Inner>::deserialize(&data)?;
+    if Versioned::<Inner>::version_of(&data)? != T::VERSION {
+        store.set(key, &Versioned::<Inner>::serialize(&value)?);
+    }
+    Ok(Some(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cosmwasm_std::testing::MockStorage;
+    use serde::{Deserialize, Serialize};
+
+    use crate::{Bincode2, Item, Map};
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct ConfigV1 {
+        owner: String,
+    }
+
+    impl Upgrade<Bincode2> for ConfigV1 {
+        const VERSION: Version = 1;
+    }
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct ConfigV2 {
+        owner: String,
+        max_tokens: u32,
+    }
+
+    impl Upgrade<Bincode2> for ConfigV2 {
+        const VERSION: Version = 2;
+
+        fn upgrade(version: Version, data: &[u8]) -> StdResult<Self> {
+            let old = ConfigV1::decode(version, data)?;
+            Ok(ConfigV2 {
+                owner: old.owner,
+                max_tokens: 100,
+            })
+        }
+    }
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Config {
+        owner: String,
+        max_tokens: u32,
+        paused: bool,
+    }
+
+    impl Upgrade<Bincode2> for Config {
+        const VERSION: Version = 3;
+
+        fn upgrade(version: Version, data: &[u8]) -> StdResult<Self> {
+            let old = ConfigV2::decode(version, data)?;
+            Ok(Config {
+                owner: old.owner,
+                max_tokens: old.max_tokens,
+                paused: false,
+            })
+        }
+    }
+
+    const CONFIG_V1: Item<ConfigV1, Versioned<Bincode2>> = Item::new("config");
+    const CONFIG_V2: Item<ConfigV2, Versioned<Bincode2>> = Item::new("config");
+    const CONFIG: Item<Config, Versioned<Bincode2>> = Item::new("config");
+
+    #[test]
+    fn prefixes_version_tag() {
+        let mut store = MockStorage::new();
+        let cfg = ConfigV1 {
+            owner: "admin".to_string(),
+        };
+        CONFIG_V1.save(&mut store, &cfg).unwrap();
+
+        let raw = store.get(b"config").unwrap();
+        assert_eq!(Versioned::<Bincode2>::version_of(&raw).unwrap(), 1);
+        assert_eq!(
+            &raw[TAG_LEN..],
+            Bincode2::serialize(&cfg).unwrap().as_slice()
+        );
+        assert_eq!(cfg, CONFIG_V1.load(&store).unwrap());
+    }
+
+    #[test]
+    fn upgrades_through_every_version_on_load() {
+        let mut store = MockStorage::new();
+        CONFIG_V1
+            .save(
+                &mut store,
+                &ConfigV1 {
+                    owner: "admin".to_string(),
+                },
+            )
+            .unwrap();
+
+        let expected = Config {
+            owner: "admin".to_string(),
+            max_tokens: 100,
+            paused: false,
+        };
+        assert_eq!(expected, CONFIG.load(&store).unwrap());
+
+        // loading does not touch the stored value
+        let raw = store.get(b"config").unwrap();
+        assert_eq!(Versioned::<Bincode2>::version_of(&raw).unwrap(), 1);
+
+        CONFIG_V2
+            .save(
+                &mut store,
+                &ConfigV2 {
+                    owner: "admin".to_string(),
+                    max_tokens: 7,
+                },
+            )
+            .unwrap();
+        assert_eq!(7, CONFIG.load(&store).unwrap().max_tokens);
+    }
+
+    #[test]
+    fn may_load_and_upgrade_writes_back() {
+        let mut store = MockStorage::new();
+        assert_eq!(None, CONFIG.may_load_and_upgrade(&mut store).unwrap());
+
+        CONFIG_V1
+            .save(
+                &mut store,
+                &ConfigV1 {
+                    owner: "admin".to_string(),
+                },
+            )
+            .unwrap();
+
+        let upgraded = CONFIG.may_load_and_upgrade(&mut store).unwrap().unwrap();
+        let raw = store.get(b"config").unwrap();
+        assert_eq!(Versioned::<Bincode2>::version_of(&raw).unwrap(), 3);
+        assert_eq!(upgraded, CONFIG.load(&store).unwrap());
+
+        // the old type can no longer read it
+        assert!(CONFIG_V1.load(&store).is_err());
+    }
+
+    #[test]
+    fn map_may_load_and_upgrade_writes_back() {
+        let mut store = MockStorage::new();
+        let old: Map<&str, ConfigV1, Versioned<Bincode2>> = Map::new("configs");
+        let new: Map<&str, Config, Versioned<Bincode2>> = Map::new("configs");
+
+        old.save(
+            &mut store,
+            "john",
+            &ConfigV1 {
+                owner: "john".to_string(),
+            },
+        )
+        .unwrap();
+
+        let upgraded = new.may_load_and_upgrade(&mut store, "john").unwrap();
+        assert_eq!(
+            Some(Config {
+                owner: "john".to_string(),
+                max_tokens: 100,
+                paused: false,
+            }),
+            upgraded
+        );
+        assert_eq!(upgraded, new.may_load(&store, "john").unwrap());
+        assert!(old.load(&store, "john").is_err());
+        assert_eq!(None, new.may_load_and_upgrade(&mut store, "jim").unwrap());
+    }
+
+    #[test]
+    fn rejects_unknown_and_untagged_values() {
+        let mut store = MockStorage::new();
+        let mut raw = 9u16.to_be_bytes().to_vec();
+        raw.extend(Bincode2::serialize(&"admin".to_string()).unwrap());
+        store.set(b"config", &raw);
+        assert!(CONFIG.load(&store).is_err());
+
+        store.set(b"config", &[1]);
+        assert!(CONFIG.load(&store).is_err());
+    }
+}
@@ -1,21 +1,19 @@
-use serde::de::DeserializeOwned;
-use serde::Serialize;
-
-use cosmwasm_std::{StdError, StdResult, Storage};
+use cosmwasm_std::{Addr, CustomQuery, QuerierWrapper, StdError, StdResult, Storage};
 
 use crate::snapshot::{ChangeSet, Snapshot};
-use crate::{Item, Map, Strategy};
+use crate::{Item, Json, Map, Serde, Strategy};
 
 /// Item that maintains a snapshot of one or more checkpoints.
 /// We can query historical data as well as current state.
 /// What data is snapshotted depends on the Strategy.
-pub struct SnapshotItem<'a, T> {
-    primary: Item<'a, T>,
+/// The current value, the changelog and the checkpoints are all encoded with `Ser`.
+pub struct SnapshotItem<'a, T, Ser = Json> {
+    primary: Item<'a, T, Ser>,
     changelog_namespace: &'a str,
-    snapshots: Snapshot<'a, (), T>,
+    snapshots: Snapshot<'a, (), T, Ser>,
 }
 
-impl<'a, T> SnapshotItem<'a, T> {
+impl<'a, T, Ser> SnapshotItem<'a, T, Ser> {
     /// Example:
     ///
     /// ```rust
@@ -39,7 +37,12 @@ impl<'a, T> SnapshotItem<'a, T> {
             snapshots: Snapshot::new(checkpoints, changelog, strategy),
         }
     }
+}
 
+impl<'a, T, Ser> SnapshotItem<'a, T, Ser>
+where
+    Ser: Serde<u32>,
+{
     pub fn add_checkpoint(&self, store: &mut dyn Storage, height: u64) -> StdResult<()> {
         self.snapshots.add_checkpoint(store, height)
     }
@@ -48,15 +51,16 @@ impl<'a, T> SnapshotItem<'a, T> {
         self.snapshots.remove_checkpoint(store, height)
     }
 
-    pub fn changelog(&self) -> Map<u64, ChangeSet<T>> {
+    pub fn changelog(&self) -> Map<u64, ChangeSet<T>, Ser> {
         // Build and return a compatible Map with the proper key type
         Map::new(self.changelog_namespace)
     }
 }
 
-impl<'a, T> SnapshotItem<'a, T>
+impl<'a, T, Ser> SnapshotItem<'a, T, Ser>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Clone,
+    Ser: Serde<T> + Serde<u32> + Serde<ChangeSet<T>>,
 {
     /// load old value and store changelog
     fn write_change(&self, store: &mut dyn Storage, height: u64) -> StdResult<()> {
@@ -95,6 +99,22 @@ where
         self.primary.may_load(store)
     }
 
+    /// If you import the proper SnapshotItem from the remote contract, this will let you read the
+    /// current data from a remote contract in a type-safe way using WasmQuery::RawQuery.
+    ///
+    /// Historical values cannot be read this way. Finding them takes a range over the changelog,
+    /// and raw queries can only read single keys, so the remote contract has to expose a smart
+    /// query that calls `may_load_at_height` instead.
+    pub fn query<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+    ) -> StdResult<T> {
+        self.primary
+            .query(querier, remote_contract, remote_contract_code_hash)
+    }
+
     pub fn may_load_at_height(&self, store: &dyn Storage, height: u64) -> StdResult<Option<T>> {
         let snapshot = self.snapshots.may_load_at_height(store, (), height)?;
 
@@ -133,9 +153,12 @@ where
 mod tests {
     use super::*;
     use crate::bound::Bound;
+    use crate::snapshot::tests::TestSerde;
+    use crate::{Bincode2, Borsh};
     use cosmwasm_std::testing::MockStorage;
+    use rstest::*;
 
-    type TestItem = SnapshotItem<'static, u64>;
+    type TestItem<Ser = Json> = SnapshotItem<'static, u64, Ser>;
 
     const NEVER: TestItem =
         SnapshotItem::new("never", "never__check", "never__change", Strategy::Never);
@@ -152,6 +175,35 @@ mod tests {
         Strategy::Selected,
     );
 
+    const B_NEVER: TestItem<Bincode2> =
+        SnapshotItem::new("never", "never__check", "never__change", Strategy::Never);
+    const BR_NEVER: TestItem<Borsh> =
+        SnapshotItem::new("never", "never__check", "never__change", Strategy::Never);
+    const B_EVERY: TestItem<Bincode2> = SnapshotItem::new(
+        "every",
+        "every__check",
+        "every__change",
+        Strategy::EveryBlock,
+    );
+    const BR_EVERY: TestItem<Borsh> = SnapshotItem::new(
+        "every",
+        "every__check",
+        "every__change",
+        Strategy::EveryBlock,
+    );
+    const B_SELECT: TestItem<Bincode2> = SnapshotItem::new(
+        "select",
+        "select__check",
+        "select__change",
+        Strategy::Selected,
+    );
+    const BR_SELECT: TestItem<Borsh> = SnapshotItem::new(
+        "select",
+        "select__check",
+        "select__change",
+        Strategy::Selected,
+    );
+
     // Fills an item (u64) with the following writes:
     // 1: 5
     // 2: 7
@@ -164,7 +216,7 @@ mod tests {
     // Final value: 22
     // Value at beginning of 3 -> 7
     // Value at beginning of 5 -> 1
-    fn init_data(item: &TestItem, storage: &mut dyn Storage) {
+    fn init_data(item: &TestItem<impl TestSerde>, storage: &mut dyn Storage) {
         item.save(storage, &5, 1).unwrap();
         item.save(storage, &7, 2).unwrap();
 
@@ -194,13 +246,13 @@ mod tests {
 
     const VALUE_START_5: Option<u64> = Some(13);
 
-    fn assert_final_value(item: &TestItem, storage: &dyn Storage) {
+    fn assert_final_value(item: &TestItem<impl TestSerde>, storage: &dyn Storage) {
         assert_eq!(FINAL_VALUE, item.may_load(storage).unwrap());
     }
 
     #[track_caller]
     fn assert_value_at_height(
-        item: &TestItem,
+        item: &TestItem<impl TestSerde>,
         storage: &dyn Storage,
         height: u64,
         value: Option<u64>,
@@ -208,96 +260,112 @@ mod tests {
         assert_eq!(value, item.may_load_at_height(storage, height).unwrap());
     }
 
-    fn assert_missing_checkpoint(item: &TestItem, storage: &dyn Storage, height: u64) {
+    fn assert_missing_checkpoint(
+        item: &TestItem<impl TestSerde>,
+        storage: &dyn Storage,
+        height: u64,
+    ) {
         assert!(item.may_load_at_height(storage, height).is_err());
     }
 
-    #[test]
-    fn never_works_like_normal_item() {
+    #[rstest]
+    fn never_works_like_normal_item(
+        #[values(NEVER, B_NEVER, BR_NEVER)] never: TestItem<impl TestSerde>,
+    ) {
         let mut storage = MockStorage::new();
-        init_data(&NEVER, &mut storage);
-        assert_final_value(&NEVER, &storage);
+        init_data(&never, &mut storage);
+        assert_final_value(&never, &storage);
 
         // historical queries return error
-        assert_missing_checkpoint(&NEVER, &storage, 3);
-        assert_missing_checkpoint(&NEVER, &storage, 5);
+        assert_missing_checkpoint(&never, &storage, 3);
+        assert_missing_checkpoint(&never, &storage, 5);
     }
 
-    #[test]
-    fn every_blocks_stores_present_and_past() {
+    #[rstest]
+    fn every_blocks_stores_present_and_past(
+        #[values(EVERY, B_EVERY, BR_EVERY)] every: TestItem<impl TestSerde>,
+    ) {
         let mut storage = MockStorage::new();
-        init_data(&EVERY, &mut storage);
-        assert_final_value(&EVERY, &storage);
+        init_data(&every, &mut storage);
+        assert_final_value(&every, &storage);
 
         // historical queries return historical values
-        assert_value_at_height(&EVERY, &storage, 3, VALUE_START_3);
-        assert_value_at_height(&EVERY, &storage, 5, VALUE_START_5);
+        assert_value_at_height(&every, &storage, 3, VALUE_START_3);
+        assert_value_at_height(&every, &storage, 5, VALUE_START_5);
     }
 
-    #[test]
-    fn selected_shows_3_not_5() {
+    #[rstest]
+    #[case(NEVER, SELECT)]
+    #[case(B_NEVER, B_SELECT)]
+    #[case(BR_NEVER, BR_SELECT)]
+    fn selected_shows_3_not_5(
+        #[case] never: TestItem<impl TestSerde>,
+        #[case] select: TestItem<impl TestSerde>,
+    ) {
         let mut storage = MockStorage::new();
-        init_data(&SELECT, &mut storage);
-        assert_final_value(&SELECT, &storage);
+        init_data(&select, &mut storage);
+        assert_final_value(&select, &storage);
 
         // historical queries return historical values
-        assert_value_at_height(&SELECT, &storage, 3, VALUE_START_3);
+        assert_value_at_height(&select, &storage, 3, VALUE_START_3);
         // never checkpointed
-        assert_missing_checkpoint(&NEVER, &storage, 1);
+        assert_missing_checkpoint(&never, &storage, 1);
         // deleted checkpoint
-        assert_missing_checkpoint(&NEVER, &storage, 5);
+        assert_missing_checkpoint(&never, &storage, 5);
     }
 
-    #[test]
-    fn handle_multiple_writes_in_one_block() {
+    #[rstest]
+    fn handle_multiple_writes_in_one_block(
+        #[values(EVERY, B_EVERY, BR_EVERY)] every: TestItem<impl TestSerde>,
+    ) {
         let mut storage = MockStorage::new();
 
         println!("SETUP");
-        EVERY.save(&mut storage, &5, 1).unwrap();
-        EVERY.save(&mut storage, &7, 2).unwrap();
-        EVERY.save(&mut storage, &2, 2).unwrap();
+        every.save(&mut storage, &5, 1).unwrap();
+        every.save(&mut storage, &7, 2).unwrap();
+        every.save(&mut storage, &2, 2).unwrap();
 
         // update and save - query at 3 => 2, at 4 => 12
-        EVERY
+        every
             .update(&mut storage, 3, |_| -> StdResult<u64> { Ok(9) })
             .unwrap();
-        EVERY.save(&mut storage, &12, 3).unwrap();
-        assert_eq!(Some(5), EVERY.may_load_at_height(&storage, 2).unwrap());
-        assert_eq!(Some(2), EVERY.may_load_at_height(&storage, 3).unwrap());
-        assert_eq!(Some(12), EVERY.may_load_at_height(&storage, 4).unwrap());
+        every.save(&mut storage, &12, 3).unwrap();
+        assert_eq!(Some(5), every.may_load_at_height(&storage, 2).unwrap());
+        assert_eq!(Some(2), every.may_load_at_height(&storage, 3).unwrap());
+        assert_eq!(Some(12), every.may_load_at_height(&storage, 4).unwrap());
 
         // save and remove - query at 4 => 1, at 5 => None
-        EVERY.save(&mut storage, &17, 4).unwrap();
-        EVERY.remove(&mut storage, 4).unwrap();
-        assert_eq!(Some(12), EVERY.may_load_at_height(&storage, 4).unwrap());
-        assert_eq!(None, EVERY.may_load_at_height(&storage, 5).unwrap());
+        every.save(&mut storage, &17, 4).unwrap();
+        every.remove(&mut storage, 4).unwrap();
+        assert_eq!(Some(12), every.may_load_at_height(&storage, 4).unwrap());
+        assert_eq!(None, every.may_load_at_height(&storage, 5).unwrap());
 
         // remove and update - query at 5 => 2, at 6 => 13
-        EVERY.remove(&mut storage, 5).unwrap();
-        EVERY
+        every.remove(&mut storage, 5).unwrap();
+        every
             .update(&mut storage, 5, |_| -> StdResult<u64> { Ok(2) })
             .unwrap();
-        assert_eq!(None, EVERY.may_load_at_height(&storage, 5).unwrap());
-        assert_eq!(Some(2), EVERY.may_load_at_height(&storage, 6).unwrap());
+        assert_eq!(None, every.may_load_at_height(&storage, 5).unwrap());
+        assert_eq!(Some(2), every.may_load_at_height(&storage, 6).unwrap());
     }
 
-    #[test]
+    #[rstest]
     #[cfg(feature = "iterator")]
-    fn changelog_range_works() {
+    fn changelog_range_works(#[values(EVERY, B_EVERY, BR_EVERY)] every: TestItem<impl TestSerde>) {
         use cosmwasm_std::Order;
 
         let mut store = MockStorage::new();
 
         // simple data for testing
-        EVERY.save(&mut store, &5, 1u64).unwrap();
-        EVERY.save(&mut store, &7, 2u64).unwrap();
-        EVERY
+        every.save(&mut store, &5, 1u64).unwrap();
+        every.save(&mut store, &7, 2u64).unwrap();
+        every
             .update(&mut store, 3u64, |_| -> StdResult<u64> { Ok(8) })
             .unwrap();
-        EVERY.remove(&mut store, 4u64).unwrap();
+        every.remove(&mut store, 4u64).unwrap();
 
         // let's try to iterate over the changelog
-        let all: StdResult<Vec<_>> = EVERY
+        let all: StdResult<Vec<_>> = every
             .changelog()
             .range(&store, None, None, Order::Ascending)
             .collect();
@@ -314,7 +382,7 @@ mod tests {
         );
 
         // let's try to iterate over a changelog range
-        let all: StdResult<Vec<_>> = EVERY
+        let all: StdResult<Vec<_>> = every
             .changelog()
             .range(&store, Some(Bound::exclusive(3u64)), None, Order::Ascending)
             .collect();
@@ -1,7 +1,4 @@
-use serde::de::DeserializeOwned;
-use serde::Serialize;
-
-use cosmwasm_std::{StdError, StdResult, Storage};
+use cosmwasm_std::{Addr, CustomQuery, QuerierWrapper, StdError, StdResult, Storage};
 
 use crate::bound::PrefixBound;
 use crate::de::KeyDeserialize;
@@ -11,17 +8,18 @@ use crate::map::Map;
 use crate::path::Path;
 use crate::prefix::{namespaced_prefix_range, Prefix};
 use crate::snapshot::{ChangeSet, Snapshot};
-use crate::{Bound, Prefixer, Strategy};
+use crate::{Bound, Json, Prefixer, Serde, Strategy};
 
 /// Map that maintains a snapshots of one or more checkpoints.
 /// We can query historical data as well as current state.
 /// What data is snapshotted depends on the Strategy.
-pub struct SnapshotMap<'a, K, T> {
-    primary: Map<'a, K, T>,
-    snapshots: Snapshot<'a, K, T>,
+/// The current values, the changelog and the checkpoints are all encoded with `Ser`.
+pub struct SnapshotMap<'a, K, T, Ser = Json> {
+    primary: Map<'a, K, T, Ser>,
+    snapshots: Snapshot<'a, K, T, Ser>,
 }
 
-impl<'a, K, T> SnapshotMap<'a, K, T> {
+impl<'a, K, T, Ser> SnapshotMap<'a, K, T, Ser> {
     /// Example:
     ///
     /// ```rust
@@ -46,15 +44,16 @@ impl<'a, K, T> SnapshotMap<'a, K, T> {
         }
     }
 
-    pub fn changelog(&self) -> &Map<'a, (K, u64), ChangeSet<T>> {
+    pub fn changelog(&self) -> &Map<'a, (K, u64), ChangeSet<T>, Ser> {
         &self.snapshots.changelog
     }
 }
 
-impl<'a, K, T> SnapshotMap<'a, K, T>
+impl<'a, K, T, Ser> SnapshotMap<'a, K, T, Ser>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Clone,
     K: PrimaryKey<'a> + Prefixer<'a>,
+    Ser: Serde<T> + Serde<u32> + Serde<ChangeSet<T>>,
 {
     pub fn add_checkpoint(&self, store: &mut dyn Storage, height: u64) -> StdResult<()> {
         self.snapshots.add_checkpoint(store, height)
@@ -65,16 +64,17 @@ where
     }
 }
 
-impl<'a, K, T> SnapshotMap<'a, K, T>
+impl<'a, K, T, Ser> SnapshotMap<'a, K, T, Ser>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Clone,
     K: PrimaryKey<'a> + Prefixer<'a> + KeyDeserialize,
+    Ser: Serde<T> + Serde<u32> + Serde<ChangeSet<T>>,
 {
-    pub fn key(&self, k: K) -> Path<T> {
+    pub fn key(&self, k: K) -> Path<T, Ser> {
         self.primary.key(k)
     }
 
-    fn no_prefix_raw(&self) -> Prefix<Vec<u8>, T, K> {
+    fn no_prefix_raw(&self) -> Prefix<Vec<u8>, T, K, Ser> {
         self.primary.no_prefix_raw()
     }
 
@@ -115,6 +115,23 @@ where
         self.primary.may_load(store, k)
     }
 
+    /// If you import the proper SnapshotMap from the remote contract, this will let you read the
+    /// current data from a remote contract in a type-safe way using WasmQuery::RawQuery.
+    ///
+    /// Historical values cannot be read this way. Finding them takes a range over the changelog,
+    /// and raw queries can only read single keys, so the remote contract has to expose a smart
+    /// query that calls `may_load_at_height` instead.
+    pub fn query<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+        remote_contract_code_hash: String,
+        k: K,
+    ) -> StdResult<Option<T>> {
+        self.primary
+            .query(querier, remote_contract, remote_contract_code_hash, k)
+    }
+
     pub fn may_load_at_height(
         &self,
         store: &dyn Storage,
@@ -162,10 +179,11 @@ where
 }
 
 // short-cut for simple keys, rather than .prefix(()).range_raw(...)
-impl<'a, K, T> SnapshotMap<'a, K, T>
+impl<'a, K, T, Ser> SnapshotMap<'a, K, T, Ser>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Clone,
     K: PrimaryKey<'a> + Prefixer<'a> + KeyDeserialize,
+    Ser: Serde<T> + Serde<u32> + Serde<ChangeSet<T>>,
 {
     // I would prefer not to copy code from Prefix, but no other way
     // with lifetimes (create Prefix inside function and return ref = no no)
@@ -197,10 +215,10 @@ where
 }
 
 #[cfg(feature = "iterator")]
-impl<'a, K, T> SnapshotMap<'a, K, T>
+impl<'a, K, T, Ser> SnapshotMap<'a, K, T, Ser>
 where
-    T: Serialize + DeserializeOwned,
     K: PrimaryKey<'a> + KeyDeserialize,
+    Ser: Serde<T>,
 {
     /// While `range` over a `prefix` fixes the prefix to one element and iterates over the
     /// remaining, `prefix_range` accepts bounds for the lowest and highest elements of the
@@ -219,10 +237,11 @@ where
         T: 'c,
         'a: 'c,
         K: 'c,
+        Ser: 'c,
         K::Output: 'static,
     {
         let mapped = namespaced_prefix_range(store, self.primary.namespace(), min, max, order)
-            .map(deserialize_kv::<K, T>);
+            .map(deserialize_kv::<K, T, Ser>);
         Box::new(mapped)
     }
 
@@ -254,15 +273,15 @@ where
         self.no_prefix().keys(store, min, max, order)
     }
 
-    pub fn prefix(&self, p: K::Prefix) -> Prefix<K::Suffix, T, K::Suffix> {
+    pub fn prefix(&self, p: K::Prefix) -> Prefix<K::Suffix, T, K::Suffix, Ser> {
         Prefix::new(self.primary.namespace(), &p.prefix())
     }
 
-    pub fn sub_prefix(&self, p: K::SubPrefix) -> Prefix<K::SuperSuffix, T, K::SuperSuffix> {
+    pub fn sub_prefix(&self, p: K::SubPrefix) -> Prefix<K::SuperSuffix, T, K::SuperSuffix, Ser> {
         Prefix::new(self.primary.namespace(), &p.prefix())
     }
 
-    fn no_prefix(&self) -> Prefix<K, T, K> {
+    fn no_prefix(&self) -> Prefix<K, T, K, Ser> {
         Prefix::new(self.primary.namespace(), &[])
     }
 }
@@ -270,10 +289,14 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::snapshot::tests::TestSerde;
+    use crate::{Bincode2, Borsh};
     use cosmwasm_std::testing::MockStorage;
+    use rstest::*;
 
-    type TestMap = SnapshotMap<'static, &'static str, u64>;
-    type TestMapCompositeKey = SnapshotMap<'static, (&'static str, &'static str), u64>;
+    type TestMap<Ser = Json> = SnapshotMap<'static, &'static str, u64, Ser>;
+    type TestMapCompositeKey<Ser = Json> =
+        SnapshotMap<'static, (&'static str, &'static str), u64, Ser>;
 
     const NEVER: TestMap =
         SnapshotMap::new("never", "never__check", "never__change", Strategy::Never);
@@ -296,6 +319,47 @@ mod tests {
         Strategy::Selected,
     );
 
+    const B_NEVER: TestMap<Bincode2> =
+        SnapshotMap::new("never", "never__check", "never__change", Strategy::Never);
+    const BR_NEVER: TestMap<Borsh> =
+        SnapshotMap::new("never", "never__check", "never__change", Strategy::Never);
+    const B_EVERY: TestMap<Bincode2> = SnapshotMap::new(
+        "every",
+        "every__check",
+        "every__change",
+        Strategy::EveryBlock,
+    );
+    const BR_EVERY: TestMap<Borsh> = SnapshotMap::new(
+        "every",
+        "every__check",
+        "every__change",
+        Strategy::EveryBlock,
+    );
+    const B_EVERY_COMPOSITE_KEY: TestMapCompositeKey<Bincode2> = SnapshotMap::new(
+        "every",
+        "every__check",
+        "every__change",
+        Strategy::EveryBlock,
+    );
+    const BR_EVERY_COMPOSITE_KEY: TestMapCompositeKey<Borsh> = SnapshotMap::new(
+        "every",
+        "every__check",
+        "every__change",
+        Strategy::EveryBlock,
+    );
+    const B_SELECT: TestMap<Bincode2> = SnapshotMap::new(
+        "select",
+        "select__check",
+        "select__change",
+        Strategy::Selected,
+    );
+    const BR_SELECT: TestMap<Borsh> = SnapshotMap::new(
+        "select",
+        "select__check",
+        "select__change",
+        Strategy::Selected,
+    );
+
     // Fills a map &[u8] -> u64 with the following writes:
     // 1: A = 5
     // 2: B = 7
@@ -305,7 +369,7 @@ mod tests {
     // Final values -> C = 13, D = 22
     // Values at beginning of 3 -> A = 5, B = 7
     // Values at beginning of 5 -> A = 8, C = 13
-    fn init_data(map: &TestMap, storage: &mut dyn Storage) {
+    fn init_data(map: &TestMap<impl TestSerde>, storage: &mut dyn Storage) {
         map.save(storage, "A", &5, 1).unwrap();
         map.save(storage, "B", &7, 2).unwrap();
 
@@ -339,7 +403,10 @@ mod tests {
         &[("A", Some(8)), ("B", None), ("C", Some(13)), ("D", None)];
 
     // Same as `init_data`, but we have a composite key for testing range.
-    fn init_data_composite_key(map: &TestMapCompositeKey, storage: &mut dyn Storage) {
+    fn init_data_composite_key(
+        map: &TestMapCompositeKey<impl TestSerde>,
+        storage: &mut dyn Storage,
+    ) {
         map.save(storage, ("A", "B"), &5, 1).unwrap();
         map.save(storage, ("B", "A"), &7, 2).unwrap();
 
@@ -363,14 +430,14 @@ mod tests {
         map.remove_checkpoint(storage, 5).unwrap();
     }
 
-    fn assert_final_values(map: &TestMap, storage: &dyn Storage) {
+    fn assert_final_values(map: &TestMap<impl TestSerde>, storage: &dyn Storage) {
         for (k, v) in FINAL_VALUES.iter().cloned() {
             assert_eq!(v, map.may_load(storage, k).unwrap());
         }
     }
 
     fn assert_values_at_height(
-        map: &TestMap,
+        map: &TestMap<impl TestSerde>,
         storage: &dyn Storage,
         height: u64,
         values: &[(&str, Option<u64>)],
@@ -380,106 +447,122 @@ mod tests {
         }
     }
 
-    fn assert_missing_checkpoint(map: &TestMap, storage: &dyn Storage, height: u64) {
+    fn assert_missing_checkpoint(
+        map: &TestMap<impl TestSerde>,
+        storage: &dyn Storage,
+        height: u64,
+    ) {
         for k in &["A", "B", "C", "D"] {
             assert!(map.may_load_at_height(storage, *k, height).is_err());
         }
     }
 
-    #[test]
-    fn never_works_like_normal_map() {
+    #[rstest]
+    fn never_works_like_normal_map(
+        #[values(NEVER, B_NEVER, BR_NEVER)] never: TestMap<impl TestSerde>,
+    ) {
         let mut storage = MockStorage::new();
-        init_data(&NEVER, &mut storage);
-        assert_final_values(&NEVER, &storage);
+        init_data(&never, &mut storage);
+        assert_final_values(&never, &storage);
 
         // historical queries return error
-        assert_missing_checkpoint(&NEVER, &storage, 3);
-        assert_missing_checkpoint(&NEVER, &storage, 5);
+        assert_missing_checkpoint(&never, &storage, 3);
+        assert_missing_checkpoint(&never, &storage, 5);
     }
 
-    #[test]
-    fn every_blocks_stores_present_and_past() {
+    #[rstest]
+    fn every_blocks_stores_present_and_past(
+        #[values(EVERY, B_EVERY, BR_EVERY)] every: TestMap<impl TestSerde>,
+    ) {
         let mut storage = MockStorage::new();
-        init_data(&EVERY, &mut storage);
-        assert_final_values(&EVERY, &storage);
+        init_data(&every, &mut storage);
+        assert_final_values(&every, &storage);
 
         // historical queries return historical values
-        assert_values_at_height(&EVERY, &storage, 3, VALUES_START_3);
-        assert_values_at_height(&EVERY, &storage, 5, VALUES_START_5);
+        assert_values_at_height(&every, &storage, 3, VALUES_START_3);
+        assert_values_at_height(&every, &storage, 5, VALUES_START_5);
     }
 
-    #[test]
-    fn selected_shows_3_not_5() {
+    #[rstest]
+    #[case(NEVER, SELECT)]
+    #[case(B_NEVER, B_SELECT)]
+    #[case(BR_NEVER, BR_SELECT)]
+    fn selected_shows_3_not_5(
+        #[case] never: TestMap<impl TestSerde>,
+        #[case] select: TestMap<impl TestSerde>,
+    ) {
         let mut storage = MockStorage::new();
-        init_data(&SELECT, &mut storage);
-        assert_final_values(&SELECT, &storage);
+        init_data(&select, &mut storage);
+        assert_final_values(&select, &storage);
 
         // historical queries return historical values
-        assert_values_at_height(&SELECT, &storage, 3, VALUES_START_3);
+        assert_values_at_height(&select, &storage, 3, VALUES_START_3);
         // never checkpointed
-        assert_missing_checkpoint(&NEVER, &storage, 1);
+        assert_missing_checkpoint(&never, &storage, 1);
         // deleted checkpoint
-        assert_missing_checkpoint(&NEVER, &storage, 5);
+        assert_missing_checkpoint(&never, &storage, 5);
     }
 
-    #[test]
-    fn handle_multiple_writes_in_one_block() {
+    #[rstest]
+    fn handle_multiple_writes_in_one_block(
+        #[values(EVERY, B_EVERY, BR_EVERY)] every: TestMap<impl TestSerde>,
+    ) {
         let mut storage = MockStorage::new();
 
         println!("SETUP");
-        EVERY.save(&mut storage, "A", &5, 1).unwrap();
-        EVERY.save(&mut storage, "B", &7, 2).unwrap();
-        EVERY.save(&mut storage, "C", &2, 2).unwrap();
+        every.save(&mut storage, "A", &5, 1).unwrap();
+        every.save(&mut storage, "B", &7, 2).unwrap();
+        every.save(&mut storage, "C", &2, 2).unwrap();
 
         // update and save - A query at 3 => 5, at 4 => 12
-        EVERY
+        every
             .update(&mut storage, "A", 3, |_| -> StdResult<u64> { Ok(9) })
             .unwrap();
-        EVERY.save(&mut storage, "A", &12, 3).unwrap();
-        assert_eq!(Some(5), EVERY.may_load_at_height(&storage, "A", 2).unwrap());
-        assert_eq!(Some(5), EVERY.may_load_at_height(&storage, "A", 3).unwrap());
+        every.save(&mut storage, "A", &12, 3).unwrap();
+        assert_eq!(Some(5), every.may_load_at_height(&storage, "A", 2).unwrap());
+        assert_eq!(Some(5), every.may_load_at_height(&storage, "A", 3).unwrap());
         assert_eq!(
             Some(12),
-            EVERY.may_load_at_height(&storage, "A", 4).unwrap()
+            every.may_load_at_height(&storage, "A", 4).unwrap()
         );
 
         // save and remove - B query at 4 => 7, at 5 => None
-        EVERY.save(&mut storage, "B", &17, 4).unwrap();
-        EVERY.remove(&mut storage, "B", 4).unwrap();
-        assert_eq!(Some(7), EVERY.may_load_at_height(&storage, "B", 3).unwrap());
-        assert_eq!(Some(7), EVERY.may_load_at_height(&storage, "B", 4).unwrap());
-        assert_eq!(None, EVERY.may_load_at_height(&storage, "B", 5).unwrap());
+        every.save(&mut storage, "B", &17, 4).unwrap();
+        every.remove(&mut storage, "B", 4).unwrap();
+        assert_eq!(Some(7), every.may_load_at_height(&storage, "B", 3).unwrap());
+        assert_eq!(Some(7), every.may_load_at_height(&storage, "B", 4).unwrap());
+        assert_eq!(None, every.may_load_at_height(&storage, "B", 5).unwrap());
 
         // remove and update - C query at 5 => 2, at 6 => 16
-        EVERY.remove(&mut storage, "C", 5).unwrap();
-        EVERY
+        every.remove(&mut storage, "C", 5).unwrap();
+        every
             .update(&mut storage, "C", 5, |_| -> StdResult<u64> { Ok(16) })
             .unwrap();
-        assert_eq!(Some(2), EVERY.may_load_at_height(&storage, "C", 4).unwrap());
-        assert_eq!(Some(2), EVERY.may_load_at_height(&storage, "C", 5).unwrap());
+        assert_eq!(Some(2), every.may_load_at_height(&storage, "C", 4).unwrap());
+        assert_eq!(Some(2), every.may_load_at_height(&storage, "C", 5).unwrap());
         assert_eq!(
             Some(16),
-            EVERY.may_load_at_height(&storage, "C", 6).unwrap()
+            every.may_load_at_height(&storage, "C", 6).unwrap()
         );
     }
 
-    #[test]
+    #[rstest]
     #[cfg(feature = "iterator")]
-    fn changelog_range_works() {
+    fn changelog_range_works(#[values(EVERY, B_EVERY, BR_EVERY)] every: TestMap<impl TestSerde>) {
         use cosmwasm_std::Order;
 
         let mut store = MockStorage::new();
 
         // simple data for testing
-        EVERY.save(&mut store, "A", &5, 1).unwrap();
-        EVERY.save(&mut store, "B", &7, 2).unwrap();
-        EVERY
+        every.save(&mut store, "A", &5, 1).unwrap();
+        every.save(&mut store, "B", &7, 2).unwrap();
+        every
             .update(&mut store, "A", 3, |_| -> StdResult<u64> { Ok(8) })
             .unwrap();
-        EVERY.remove(&mut store, "B", 4).unwrap();
+        every.remove(&mut store, "B", 4).unwrap();
 
         // let's try to iterate over the changelog
-        let all: StdResult<Vec<_>> = EVERY
+        let all: StdResult<Vec<_>> = every
             .changelog()
             .range(&store, None, None, Order::Ascending)
             .collect();
@@ -496,7 +579,7 @@ mod tests {
         );
 
         // let's try to iterate over a changelog key/prefix
-        let all: StdResult<Vec<_>> = EVERY
+        let all: StdResult<Vec<_>> = every
             .changelog()
             .prefix("B")
             .range(&store, None, None, Order::Ascending)
@@ -512,7 +595,7 @@ mod tests {
         );
 
         // let's try to iterate over a changelog prefixed range
-        let all: StdResult<Vec<_>> = EVERY
+        let all: StdResult<Vec<_>> = every
             .changelog()
             .prefix("A")
             .range(&store, Some(Bound::inclusive(3u64)), None, Order::Ascending)
@@ -522,22 +605,22 @@ mod tests {
         assert_eq!(all, vec![(3, ChangeSet { old: Some(5) }),]);
     }
 
-    #[test]
+    #[rstest]
     #[cfg(feature = "iterator")]
-    fn range_simple_string_key() {
+    fn range_simple_string_key(#[values(EVERY, B_EVERY, BR_EVERY)] every: TestMap<impl TestSerde>) {
         use cosmwasm_std::Order;
 
         let mut store = MockStorage::new();
-        init_data(&EVERY, &mut store);
+        init_data(&every, &mut store);
 
         // let's try to iterate!
-        let all: StdResult<Vec<_>> = EVERY.range(&store, None, None, Order::Ascending).collect();
+        let all: StdResult<Vec<_>> = every.range(&store, None, None, Order::Ascending).collect();
         let all = all.unwrap();
         assert_eq!(2, all.len());
         assert_eq!(all, vec![("C".into(), 13), ("D".into(), 22)]);
 
         // let's try to iterate over a range
-        let all: StdResult<Vec<_>> = EVERY
+        let all: StdResult<Vec<_>> = every
             .range(&store, Some(Bound::inclusive("C")), None, Order::Ascending)
             .collect();
         let all = all.unwrap();
@@ -545,7 +628,7 @@ mod tests {
         assert_eq!(all, vec![("C".into(), 13), ("D".into(), 22)]);
 
         // let's try to iterate over a more restrictive range
-        let all: StdResult<Vec<_>> = EVERY
+        let all: StdResult<Vec<_>> = every
             .range(&store, Some(Bound::inclusive("D")), None, Order::Ascending)
             .collect();
         let all = all.unwrap();
@@ -553,18 +636,19 @@ mod tests {
         assert_eq!(all, vec![("D".into(), 22)]);
     }
 
-    #[test]
+    #[rstest]
     #[cfg(feature = "iterator")]
-    fn range_composite_key() {
+    fn range_composite_key(
+        #[values(EVERY_COMPOSITE_KEY, B_EVERY_COMPOSITE_KEY, BR_EVERY_COMPOSITE_KEY)]
+        every: TestMapCompositeKey<impl TestSerde>,
+    ) {
         use cosmwasm_std::Order;
 
         let mut store = MockStorage::new();
-        init_data_composite_key(&EVERY_COMPOSITE_KEY, &mut store);
+        init_data_composite_key(&every, &mut store);
 
         // let's try to iterate!
-        let all: StdResult<Vec<_>> = EVERY_COMPOSITE_KEY
-            .range(&store, None, None, Order::Ascending)
-            .collect();
+        let all: StdResult<Vec<_>> = every.range(&store, None, None, Order::Ascending).collect();
         let all = all.unwrap();
         assert_eq!(2, all.len());
         assert_eq!(
@@ -576,16 +660,19 @@ mod tests {
         );
     }
 
-    #[test]
+    #[rstest]
     #[cfg(feature = "iterator")]
-    fn prefix_range_composite_key() {
+    fn prefix_range_composite_key(
+        #[values(EVERY_COMPOSITE_KEY, B_EVERY_COMPOSITE_KEY, BR_EVERY_COMPOSITE_KEY)]
+        every: TestMapCompositeKey<impl TestSerde>,
+    ) {
         use cosmwasm_std::Order;
 
         let mut store = MockStorage::new();
-        init_data_composite_key(&EVERY_COMPOSITE_KEY, &mut store);
+        init_data_composite_key(&every, &mut store);
 
         // let's prefix-range and iterate
-        let all: StdResult<Vec<_>> = EVERY_COMPOSITE_KEY
+        let all: StdResult<Vec<_>> = every
             .prefix_range(
                 &store,
                 None,
@@ -598,16 +685,19 @@ mod tests {
         assert_eq!(all, vec![(("B".into(), "B".into()), 13)]);
     }
 
-    #[test]
+    #[rstest]
     #[cfg(feature = "iterator")]
-    fn prefix_composite_key() {
+    fn prefix_composite_key(
+        #[values(EVERY_COMPOSITE_KEY, B_EVERY_COMPOSITE_KEY, BR_EVERY_COMPOSITE_KEY)]
+        every: TestMapCompositeKey<impl TestSerde>,
+    ) {
         use cosmwasm_std::Order;
 
         let mut store = MockStorage::new();
-        init_data_composite_key(&EVERY_COMPOSITE_KEY, &mut store);
+        init_data_composite_key(&every, &mut store);
 
         // let's prefix and iterate
-        let all: StdResult<Vec<_>> = EVERY_COMPOSITE_KEY
+        let all: StdResult<Vec<_>> = every
             .prefix("C")
             .range(&store, None, None, Order::Ascending)
             .collect();
@@ -616,18 +706,21 @@ mod tests {
         assert_eq!(all, vec![("A".into(), 22),]);
     }
 
-    #[test]
+    #[rstest]
     #[cfg(feature = "iterator")]
-    fn sub_prefix_composite_key() {
+    fn sub_prefix_composite_key(
+        #[values(EVERY_COMPOSITE_KEY, B_EVERY_COMPOSITE_KEY, BR_EVERY_COMPOSITE_KEY)]
+        every: TestMapCompositeKey<impl TestSerde>,
+    ) {
         use cosmwasm_std::Order;
 
         let mut store = MockStorage::new();
-        init_data_composite_key(&EVERY_COMPOSITE_KEY, &mut store);
+        init_data_composite_key(&every, &mut store);
 
         // Let's sub-prefix and iterate.
         // This is similar to calling range() directly, but added here for completeness /
         // sub_prefix type checks
-        let all: StdResult<Vec<_>> = EVERY_COMPOSITE_KEY
+        let all: StdResult<Vec<_>> = every
             .sub_prefix(())
             .range(&store, None, None, Order::Ascending)
             .collect();
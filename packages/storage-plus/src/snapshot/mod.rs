@@ -7,40 +7,47 @@ pub use map::SnapshotMap;
 
 use crate::bound::Bound;
 use crate::de::KeyDeserialize;
-use crate::{Map, Prefixer, PrimaryKey};
+use crate::{Json, Map, Prefixer, PrimaryKey, Serde};
+use borsh::{BorshDeserialize, BorshSerialize};
 use cosmwasm_std::{Order, StdError, StdResult, Storage};
-use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 /// Structure holding a map of checkpoints composited from
 /// height (as u64) and counter of how many times it has
 /// been checkpointed (as u32).
 /// Stores all changes in changelog.
+/// Checkpoints and changelog entries are encoded with the same `Ser` as the
+/// snapshotted collection.
 #[derive(Debug, Clone)]
-pub(crate) struct Snapshot<'a, K, T> {
-    checkpoints: Map<'a, u64, u32>,
+pub(crate) struct Snapshot<'a, K, T, Ser = Json> {
+    checkpoints: Map<'a, u64, u32, Ser>,
 
     // this stores all changes (key, height). Must differentiate between no data written,
     // and explicit None (just inserted)
-    pub changelog: Map<'a, (K, u64), ChangeSet<T>>,
+    pub changelog: Map<'a, (K, u64), ChangeSet<T>, Ser>,
 
     // How aggressive we are about checkpointing all data
     strategy: Strategy,
 }
 
-impl<'a, K, T> Snapshot<'a, K, T> {
+impl<'a, K, T, Ser> Snapshot<'a, K, T, Ser> {
     pub const fn new(
         checkpoints: &'a str,
         changelog: &'a str,
         strategy: Strategy,
-    ) -> Snapshot<'a, K, T> {
+    ) -> Snapshot<'a, K, T, Ser> {
         Snapshot {
             checkpoints: Map::new(checkpoints),
             changelog: Map::new(changelog),
             strategy,
         }
     }
+}
 
+impl<'a, K, T, Ser> Snapshot<'a, K, T, Ser>
+where
+    Ser: Serde<u32>,
+{
     pub fn add_checkpoint(&self, store: &mut dyn Storage, height: u64) -> StdResult<()> {
         self.checkpoints
             .update::<_, StdError>(store, height, |count| Ok(count.unwrap_or_default() + 1))?;
@@ -61,10 +68,11 @@ impl<'a, K, T> Snapshot<'a, K, T> {
     }
 }
 
-impl<'a, K, T> Snapshot<'a, K, T>
+impl<'a, K, T, Ser> Snapshot<'a, K, T, Ser>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Clone,
     K: PrimaryKey<'a> + Prefixer<'a> + KeyDeserialize,
+    Ser: Serde<u32> + Serde<ChangeSet<T>>,
 {
     /// should_checkpoint looks at the strategy and determines if we want to checkpoint
     pub fn should_checkpoint(&self, store: &dyn Storage, k: &K) -> StdResult<bool> {
@@ -172,17 +180,27 @@ pub enum Strategy {
     Selected,
 }
 
-#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[derive(
+    Clone, Copy, PartialEq, Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize,
+)]
 pub struct ChangeSet<T> {
     pub old: Option<T>,
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
+    use crate::{Bincode2, Borsh};
     use cosmwasm_std::testing::MockStorage;
+    use rstest::*;
+    use rstest_reuse::{self, *};
 
-    type TestSnapshot = Snapshot<'static, &'static str, u64>;
+    /// Everything a `u64` snapshot stores through its backend
+    pub(crate) trait TestSerde: Serde<u64> + Serde<u32> + Serde<ChangeSet<u64>> {}
+
+    impl<S> TestSerde for S where S: Serde<u64> + Serde<u32> + Serde<ChangeSet<u64>> {}
+
+    type TestSnapshot<Ser = Json> = Snapshot<'static, &'static str, u64, Ser>;
 
     const NEVER: TestSnapshot = Snapshot::new("never__check", "never__change", Strategy::Never);
     const EVERY: TestSnapshot =
@@ -190,202 +208,243 @@ mod tests {
     const SELECT: TestSnapshot =
         Snapshot::new("select__check", "select__change", Strategy::Selected);
 
+    const B_NEVER: TestSnapshot<Bincode2> =
+        Snapshot::new("never__check", "never__change", Strategy::Never);
+    const BR_NEVER: TestSnapshot<Borsh> =
+        Snapshot::new("never__check", "never__change", Strategy::Never);
+    const B_EVERY: TestSnapshot<Bincode2> =
+        Snapshot::new("every__check", "every__change", Strategy::EveryBlock);
+    const BR_EVERY: TestSnapshot<Borsh> =
+        Snapshot::new("every__check", "every__change", Strategy::EveryBlock);
+    const B_SELECT: TestSnapshot<Bincode2> =
+        Snapshot::new("select__check", "select__change", Strategy::Selected);
+    const BR_SELECT: TestSnapshot<Borsh> =
+        Snapshot::new("select__check", "select__change", Strategy::Selected);
+
     const DUMMY_KEY: &str = "dummy";
 
-    #[test]
-    fn should_checkpoint() {
+    #[template]
+    #[rstest]
+    #[case(NEVER, EVERY, SELECT)]
+    #[case(B_NEVER, B_EVERY, B_SELECT)]
+    #[case(BR_NEVER, BR_EVERY, BR_SELECT)]
+    fn serialization(
+        #[case] never: TestSnapshot<impl TestSerde>,
+        #[case] every: TestSnapshot<impl TestSerde>,
+        #[case] select: TestSnapshot<impl TestSerde>,
+    ) {
+    }
+
+    #[apply(serialization)]
+    fn should_checkpoint(
+        #[case] never: TestSnapshot<impl TestSerde>,
+        #[case] every: TestSnapshot<impl TestSerde>,
+        #[case] select: TestSnapshot<impl TestSerde>,
+    ) {
         let storage = MockStorage::new();
 
-        assert_eq!(NEVER.should_checkpoint(&storage, &DUMMY_KEY), Ok(false));
-        assert_eq!(EVERY.should_checkpoint(&storage, &DUMMY_KEY), Ok(true));
-        assert_eq!(SELECT.should_checkpoint(&storage, &DUMMY_KEY), Ok(false));
+        assert_eq!(never.should_checkpoint(&storage, &DUMMY_KEY), Ok(false));
+        assert_eq!(every.should_checkpoint(&storage, &DUMMY_KEY), Ok(true));
+        assert_eq!(select.should_checkpoint(&storage, &DUMMY_KEY), Ok(false));
     }
 
-    #[test]
-    fn assert_checkpointed() {
+    #[apply(serialization)]
+    fn assert_checkpointed(
+        #[case] never: TestSnapshot<impl TestSerde>,
+        #[case] every: TestSnapshot<impl TestSerde>,
+        #[case] select: TestSnapshot<impl TestSerde>,
+    ) {
         let mut storage = MockStorage::new();
 
         assert_eq!(
-            NEVER.assert_checkpointed(&storage, 1),
+            never.assert_checkpointed(&storage, 1),
             Err(StdError::not_found("checkpoint"))
         );
-        assert_eq!(EVERY.assert_checkpointed(&storage, 1), Ok(()));
+        assert_eq!(every.assert_checkpointed(&storage, 1), Ok(()));
         assert_eq!(
-            SELECT.assert_checkpointed(&storage, 1),
+            select.assert_checkpointed(&storage, 1),
             Err(StdError::not_found("checkpoint"))
         );
 
         // Add a checkpoint at 1
-        NEVER.add_checkpoint(&mut storage, 1).unwrap();
-        EVERY.add_checkpoint(&mut storage, 1).unwrap();
-        SELECT.add_checkpoint(&mut storage, 1).unwrap();
+        never.add_checkpoint(&mut storage, 1).unwrap();
+        every.add_checkpoint(&mut storage, 1).unwrap();
+        select.add_checkpoint(&mut storage, 1).unwrap();
 
         assert_eq!(
-            NEVER.assert_checkpointed(&storage, 1),
+            never.assert_checkpointed(&storage, 1),
             Err(StdError::not_found("checkpoint"))
         );
-        assert_eq!(EVERY.assert_checkpointed(&storage, 1), Ok(()));
-        assert_eq!(SELECT.assert_checkpointed(&storage, 1), Ok(()));
+        assert_eq!(every.assert_checkpointed(&storage, 1), Ok(()));
+        assert_eq!(select.assert_checkpointed(&storage, 1), Ok(()));
 
         // Remove checkpoint
-        NEVER.remove_checkpoint(&mut storage, 1).unwrap();
-        EVERY.remove_checkpoint(&mut storage, 1).unwrap();
-        SELECT.remove_checkpoint(&mut storage, 1).unwrap();
+        never.remove_checkpoint(&mut storage, 1).unwrap();
+        every.remove_checkpoint(&mut storage, 1).unwrap();
+        select.remove_checkpoint(&mut storage, 1).unwrap();
 
         assert_eq!(
-            NEVER.assert_checkpointed(&storage, 1),
+            never.assert_checkpointed(&storage, 1),
             Err(StdError::not_found("checkpoint"))
         );
-        assert_eq!(EVERY.assert_checkpointed(&storage, 1), Ok(()));
+        assert_eq!(every.assert_checkpointed(&storage, 1), Ok(()));
         assert_eq!(
-            SELECT.assert_checkpointed(&storage, 1),
+            select.assert_checkpointed(&storage, 1),
             Err(StdError::not_found("checkpoint"))
         );
     }
 
-    #[test]
-    fn has_changelog() {
+    #[apply(serialization)]
+    fn has_changelog(
+        #[case] never: TestSnapshot<impl TestSerde>,
+        #[case] every: TestSnapshot<impl TestSerde>,
+        #[case] select: TestSnapshot<impl TestSerde>,
+    ) {
         let mut storage = MockStorage::new();
 
-        assert_eq!(NEVER.has_changelog(&mut storage, DUMMY_KEY, 1), Ok(false));
-        assert_eq!(EVERY.has_changelog(&mut storage, DUMMY_KEY, 1), Ok(false));
-        assert_eq!(SELECT.has_changelog(&mut storage, DUMMY_KEY, 1), Ok(false));
+        assert_eq!(never.has_changelog(&mut storage, DUMMY_KEY, 1), Ok(false));
+        assert_eq!(every.has_changelog(&mut storage, DUMMY_KEY, 1), Ok(false));
+        assert_eq!(select.has_changelog(&mut storage, DUMMY_KEY, 1), Ok(false));
 
-        assert_eq!(NEVER.has_changelog(&mut storage, DUMMY_KEY, 2), Ok(false));
-        assert_eq!(EVERY.has_changelog(&mut storage, DUMMY_KEY, 2), Ok(false));
-        assert_eq!(SELECT.has_changelog(&mut storage, DUMMY_KEY, 2), Ok(false));
+        assert_eq!(never.has_changelog(&mut storage, DUMMY_KEY, 2), Ok(false));
+        assert_eq!(every.has_changelog(&mut storage, DUMMY_KEY, 2), Ok(false));
+        assert_eq!(select.has_changelog(&mut storage, DUMMY_KEY, 2), Ok(false));
 
-        assert_eq!(NEVER.has_changelog(&mut storage, DUMMY_KEY, 3), Ok(false));
-        assert_eq!(EVERY.has_changelog(&mut storage, DUMMY_KEY, 3), Ok(false));
-        assert_eq!(SELECT.has_changelog(&mut storage, DUMMY_KEY, 3), Ok(false));
+        assert_eq!(never.has_changelog(&mut storage, DUMMY_KEY, 3), Ok(false));
+        assert_eq!(every.has_changelog(&mut storage, DUMMY_KEY, 3), Ok(false));
+        assert_eq!(select.has_changelog(&mut storage, DUMMY_KEY, 3), Ok(false));
 
         // Write a changelog at 2
-        NEVER
+        never
             .write_changelog(&mut storage, DUMMY_KEY, 2, Some(3))
             .unwrap();
-        EVERY
+        every
             .write_changelog(&mut storage, DUMMY_KEY, 2, Some(4))
             .unwrap();
-        SELECT
+        select
             .write_changelog(&mut storage, DUMMY_KEY, 2, Some(5))
             .unwrap();
 
-        assert_eq!(NEVER.has_changelog(&mut storage, DUMMY_KEY, 1), Ok(false));
-        assert_eq!(EVERY.has_changelog(&mut storage, DUMMY_KEY, 1), Ok(false));
-        assert_eq!(SELECT.has_changelog(&mut storage, DUMMY_KEY, 1), Ok(false));
+        assert_eq!(never.has_changelog(&mut storage, DUMMY_KEY, 1), Ok(false));
+        assert_eq!(every.has_changelog(&mut storage, DUMMY_KEY, 1), Ok(false));
+        assert_eq!(select.has_changelog(&mut storage, DUMMY_KEY, 1), Ok(false));
 
-        assert_eq!(NEVER.has_changelog(&mut storage, DUMMY_KEY, 2), Ok(true));
-        assert_eq!(EVERY.has_changelog(&mut storage, DUMMY_KEY, 2), Ok(true));
-        assert_eq!(SELECT.has_changelog(&mut storage, DUMMY_KEY, 2), Ok(true));
+        assert_eq!(never.has_changelog(&mut storage, DUMMY_KEY, 2), Ok(true));
+        assert_eq!(every.has_changelog(&mut storage, DUMMY_KEY, 2), Ok(true));
+        assert_eq!(select.has_changelog(&mut storage, DUMMY_KEY, 2), Ok(true));
 
-        assert_eq!(NEVER.has_changelog(&mut storage, DUMMY_KEY, 3), Ok(false));
-        assert_eq!(EVERY.has_changelog(&mut storage, DUMMY_KEY, 3), Ok(false));
-        assert_eq!(SELECT.has_changelog(&mut storage, DUMMY_KEY, 3), Ok(false));
+        assert_eq!(never.has_changelog(&mut storage, DUMMY_KEY, 3), Ok(false));
+        assert_eq!(every.has_changelog(&mut storage, DUMMY_KEY, 3), Ok(false));
+        assert_eq!(select.has_changelog(&mut storage, DUMMY_KEY, 3), Ok(false));
     }
 
-    #[test]
-    fn may_load_at_height() {
+    #[apply(serialization)]
+    fn may_load_at_height(
+        #[case] never: TestSnapshot<impl TestSerde>,
+        #[case] every: TestSnapshot<impl TestSerde>,
+        #[case] select: TestSnapshot<impl TestSerde>,
+    ) {
         let mut storage = MockStorage::new();
 
         assert_eq!(
-            NEVER.may_load_at_height(&storage, DUMMY_KEY, 3),
+            never.may_load_at_height(&storage, DUMMY_KEY, 3),
             Err(StdError::not_found("checkpoint"))
         );
-        assert_eq!(EVERY.may_load_at_height(&storage, DUMMY_KEY, 3), Ok(None));
+        assert_eq!(every.may_load_at_height(&storage, DUMMY_KEY, 3), Ok(None));
         assert_eq!(
-            SELECT.may_load_at_height(&storage, DUMMY_KEY, 3),
+            select.may_load_at_height(&storage, DUMMY_KEY, 3),
             Err(StdError::not_found("checkpoint"))
         );
 
         // Add a checkpoint at 3
-        NEVER.add_checkpoint(&mut storage, 3).unwrap();
-        EVERY.add_checkpoint(&mut storage, 3).unwrap();
-        SELECT.add_checkpoint(&mut storage, 3).unwrap();
+        never.add_checkpoint(&mut storage, 3).unwrap();
+        every.add_checkpoint(&mut storage, 3).unwrap();
+        select.add_checkpoint(&mut storage, 3).unwrap();
 
         assert_eq!(
-            NEVER.may_load_at_height(&storage, DUMMY_KEY, 3),
+            never.may_load_at_height(&storage, DUMMY_KEY, 3),
             Err(StdError::not_found("checkpoint"))
         );
-        assert_eq!(EVERY.may_load_at_height(&storage, DUMMY_KEY, 3), Ok(None));
-        assert_eq!(SELECT.may_load_at_height(&storage, DUMMY_KEY, 3), Ok(None));
+        assert_eq!(every.may_load_at_height(&storage, DUMMY_KEY, 3), Ok(None));
+        assert_eq!(select.may_load_at_height(&storage, DUMMY_KEY, 3), Ok(None));
 
         // Write a changelog at 3
-        NEVER
+        never
             .write_changelog(&mut storage, DUMMY_KEY, 3, Some(100))
             .unwrap();
-        EVERY
+        every
             .write_changelog(&mut storage, DUMMY_KEY, 3, Some(101))
             .unwrap();
-        SELECT
+        select
             .write_changelog(&mut storage, DUMMY_KEY, 3, Some(102))
             .unwrap();
 
         assert_eq!(
-            NEVER.may_load_at_height(&storage, DUMMY_KEY, 3),
+            never.may_load_at_height(&storage, DUMMY_KEY, 3),
             Err(StdError::not_found("checkpoint"))
         );
         assert_eq!(
-            EVERY.may_load_at_height(&storage, DUMMY_KEY, 3),
+            every.may_load_at_height(&storage, DUMMY_KEY, 3),
             Ok(Some(Some(101)))
         );
         assert_eq!(
-            SELECT.may_load_at_height(&storage, DUMMY_KEY, 3),
+            select.may_load_at_height(&storage, DUMMY_KEY, 3),
             Ok(Some(Some(102)))
         );
         // Check that may_load_at_height at a previous value will return the first change after that.
-        // (Only with EVERY).
+        // (Only with every).
         assert_eq!(
-            NEVER.may_load_at_height(&storage, DUMMY_KEY, 2),
+            never.may_load_at_height(&storage, DUMMY_KEY, 2),
             Err(StdError::not_found("checkpoint"))
         );
         assert_eq!(
-            EVERY.may_load_at_height(&storage, DUMMY_KEY, 2),
+            every.may_load_at_height(&storage, DUMMY_KEY, 2),
             Ok(Some(Some(101)))
         );
         assert_eq!(
-            SELECT.may_load_at_height(&storage, DUMMY_KEY, 2),
+            select.may_load_at_height(&storage, DUMMY_KEY, 2),
             Err(StdError::not_found("checkpoint"))
         );
 
         // Write a changelog at 4, removing the value
-        NEVER
+        never
             .write_changelog(&mut storage, DUMMY_KEY, 4, None)
             .unwrap();
-        EVERY
+        every
             .write_changelog(&mut storage, DUMMY_KEY, 4, None)
             .unwrap();
-        SELECT
+        select
             .write_changelog(&mut storage, DUMMY_KEY, 4, None)
             .unwrap();
         // And add a checkpoint at 4
-        NEVER.add_checkpoint(&mut storage, 4).unwrap();
-        EVERY.add_checkpoint(&mut storage, 4).unwrap();
-        SELECT.add_checkpoint(&mut storage, 4).unwrap();
+        never.add_checkpoint(&mut storage, 4).unwrap();
+        every.add_checkpoint(&mut storage, 4).unwrap();
+        select.add_checkpoint(&mut storage, 4).unwrap();
 
         assert_eq!(
-            NEVER.may_load_at_height(&storage, DUMMY_KEY, 4),
+            never.may_load_at_height(&storage, DUMMY_KEY, 4),
             Err(StdError::not_found("checkpoint"))
         );
         assert_eq!(
-            EVERY.may_load_at_height(&storage, DUMMY_KEY, 4),
+            every.may_load_at_height(&storage, DUMMY_KEY, 4),
             Ok(Some(None))
         );
         assert_eq!(
-            SELECT.may_load_at_height(&storage, DUMMY_KEY, 4),
+            select.may_load_at_height(&storage, DUMMY_KEY, 4),
             Ok(Some(None))
         );
 
         // Confirm old value at 3
         assert_eq!(
-            NEVER.may_load_at_height(&storage, DUMMY_KEY, 3),
+            never.may_load_at_height(&storage, DUMMY_KEY, 3),
             Err(StdError::not_found("checkpoint"))
         );
         assert_eq!(
-            EVERY.may_load_at_height(&storage, DUMMY_KEY, 3),
+            every.may_load_at_height(&storage, DUMMY_KEY, 3),
             Ok(Some(Some(101)))
         );
         assert_eq!(
-            SELECT.may_load_at_height(&storage, DUMMY_KEY, 3),
+            select.may_load_at_height(&storage, DUMMY_KEY, 3),
             Ok(Some(Some(102)))
         );
     }
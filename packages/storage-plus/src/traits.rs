@@ -1,10 +1,9 @@
 use cosmwasm_std::{StdError, StdResult, Storage};
-use serde::{de::DeserializeOwned, Serialize};
 use crate::{Item, Json, Map, PrimaryKey, Serde};
 
-pub trait NaiveItemStorage<Ser = Json>: Serialize + DeserializeOwned
+pub trait NaiveItemStorage<Ser = Json>: Sized
 where
-    Ser: Serde,
+    Ser: Serde<Self>,
 {
     fn load(storage: &dyn Storage, item: Item<Self, Ser>) -> StdResult<Self> {
         item.load(storage)
@@ -36,9 +35,9 @@ where
     }
 }
 
-pub trait ItemStorage<Ser = Json>: Serialize + DeserializeOwned
+pub trait ItemStorage<Ser = Json>: Sized
 where
-    Ser: Serde,
+    Ser: Serde<Self>,
 {
     const ITEM: Item<'static, Self, Ser>;
 
@@ -67,9 +66,9 @@ where
     }
 }
 
-pub trait GenericItemStorage<T: Serialize + DeserializeOwned, Ser = Json>
+pub trait GenericItemStorage<T, Ser = Json>
 where
-    Ser: Serde,
+    Ser: Serde<T>,
 {
     const ITEM: Item<'static, T, Ser>;
 
@@ -94,9 +93,9 @@ where
     }
 }
 
-pub trait NaiveMapStorage<'a, Ser = Json>: Serialize + DeserializeOwned
+pub trait NaiveMapStorage<'a, Ser = Json>: Sized
 where
-    Ser: Serde,
+    Ser: Serde<Self>,
 {
     fn load<K: PrimaryKey<'a>>(
         storage: &dyn Storage,
@@ -142,9 +141,9 @@ where
     }
 }
 
-pub trait MapStorage<'a, K: PrimaryKey<'a>, Ser = Json>: Serialize + DeserializeOwned
+pub trait MapStorage<'a, K: PrimaryKey<'a>, Ser = Json>: Sized
 where
-    Ser: Serde,
+    Ser: Serde<Self>,
 {
     const MAP: Map<'static, K, Self, Ser>;
 
@@ -173,9 +172,9 @@ where
     }
 }
 
-pub trait GenericMapStorage<'a, K: PrimaryKey<'a>, T: Serialize + DeserializeOwned, Ser = Json>
+pub trait GenericMapStorage<'a, K: PrimaryKey<'a>, T, Ser = Json>
 where
-    Ser: Serde,
+    Ser: Serde<T>,
 {
     const MAP: Map<'static, K, T, Ser>;
 
@@ -149,6 +149,7 @@ impl Mul<u64> for Duration {
 #[cfg(test)]
 mod test {
     use super::*;
+    use cosmwasm_std::testing::mock_env;
 
     #[test]
     fn compare_expiration() {
@@ -219,6 +220,7 @@ mod test {
             height: 1000,
             time: Timestamp::from_seconds(7777),
             chain_id: "foo".to_string(),
+            ..mock_env().block
         };
 
         let end = Duration::Height(456).after(&block);